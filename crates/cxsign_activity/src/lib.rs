@@ -8,13 +8,14 @@ pub mod sign;
 pub mod utils;
 
 use crate::sign::{RawSign, SignTrait};
+use cxsign_error::ResponseExt;
 use cxsign_store::ExcludeTable;
 use cxsign_types::Course;
 use cxsign_user::Session;
+use cxsign_utils::WorkerPool;
 use log::debug;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
 
 pub type ActivitiesSessionsMap = (
     HashMap<RawSign, Vec<Session>>,
@@ -33,7 +34,7 @@ impl Activity {
         table: ExcludeTable,
         session: &Session,
         course: &Course,
-    ) -> Result<Activities, Box<ureq::Error>> {
+    ) -> Result<Activities, cxsign_error::Error> {
         let mut v = Vec::new();
         let mut n = Vec::new();
         let mut o = Vec::new();
@@ -54,11 +55,11 @@ impl Activity {
             }
         }
         let id = course.get_id();
-        let excluded = table.has_exclude(id);
+        let excluded = table.has_exclude(id)?;
         if dont_exclude && excluded {
-            table.delete_exclude(id);
+            table.delete_exclude(id)?;
         } else if !dont_exclude && !excluded {
-            table.add_exclude(id);
+            table.add_exclude(id)?;
         }
         Ok((v, n, o))
     }
@@ -66,167 +67,144 @@ impl Activity {
         table: ExcludeTable,
         set_excludes: bool,
         courses: HashMap<Course, Vec<Session>>,
-    ) -> Result<ActivitiesSessionsMap, Box<ureq::Error>> {
-        let excludes = table.get_excludes();
+    ) -> Result<ActivitiesSessionsMap, cxsign_error::Error> {
+        let excludes = table.get_excludes()?;
         let set_excludes = set_excludes || excludes.is_empty();
+        let (activities, excludes) =
+            Self::get_activities_of_courses(&excludes, set_excludes, courses);
+        if set_excludes {
+            table.update_excludes(&excludes)?;
+        }
+        Ok(activities)
+    }
+    /// 获取课程的活动，不读写数据库。返回活动以及需要排除的课程。
+    fn get_activities_of_courses(
+        excludes: &[i64],
+        set_excludes: bool,
+        courses: HashMap<Course, Vec<Session>>,
+    ) -> (ActivitiesSessionsMap, Vec<i64>) {
         let course_sessions_map = courses;
         let courses = course_sessions_map
             .keys()
             .filter(|course| set_excludes || !excludes.contains(&course.get_id()))
             .cloned()
             .collect::<Vec<_>>();
-        let excludes = Arc::new(Mutex::new(Vec::new()));
-        let valid_signs = Arc::new(Mutex::new(HashMap::new()));
-        let other_signs = Arc::new(Mutex::new(HashMap::new()));
-        let other_activities = Arc::new(Mutex::new(HashMap::new()));
-        let thread_count = 256;
-        let len = courses.len();
-        let chunk_rest = len % thread_count;
-        let chunk_count = len / thread_count + if chunk_rest == 0 { 0 } else { 1 };
-        for i in 0..chunk_count {
-            let courses = &courses[i * thread_count..if i != chunk_count - 1 {
-                (i + 1) * thread_count
-            } else {
-                len
-            }];
-            let mut handles = Vec::new();
-            for course in courses {
-                debug!("加载课程{course}的签到。");
-                if let Some(session) = &course_sessions_map[course].first() {
-                    let course = course.clone();
-                    let session = (*session).clone();
-                    let valid_signs = Arc::clone(&valid_signs);
-                    let other_signs = Arc::clone(&other_signs);
-                    let other_activities = Arc::clone(&other_activities);
-                    let excludes = excludes.clone();
-                    let sessions = course_sessions_map[&course].clone();
-                    let handle = std::thread::spawn(move || {
-                        let activities =
-                            Self::get_list_from_course(&session, &course).unwrap_or(vec![]);
-                        let mut v = Vec::new();
-                        let mut n = Vec::new();
-                        let mut o = Vec::new();
-                        // NOTE: 此处也会将没有过签到的课程排除掉。
-                        // TODO: 需要修改。
-                        let mut dont_exclude = false;
-                        for activity in activities {
-                            if let Self::RawSign(sign) = activity {
-                                if set_excludes
-                                    && cxsign_utils::time_delta_since_to_now(sign.start_time_mills)
-                                        .num_days()
-                                        < 160
-                                {
-                                    dont_exclude = true;
-                                }
-
-                                if sign.is_valid() {
-                                    v.push(sign);
-                                } else {
-                                    n.push(sign);
-                                }
-                            } else if let Self::Other(other_activity) = activity {
-                                o.push(other_activity);
-                            }
-                        }
-                        for v in v {
-                            valid_signs.lock().unwrap().insert(v, sessions.clone());
-                        }
-                        for n in n {
-                            other_signs.lock().unwrap().insert(n, sessions.clone());
-                        }
-                        for o in o {
-                            other_activities.lock().unwrap().insert(o, sessions.clone());
-                        }
-                        debug!("course: list_activities, ok.");
-                        if set_excludes && !dont_exclude {
-                            excludes.lock().unwrap().push(course.get_id())
-                        }
-                    });
-                    handles.push(handle);
+        let jobs = courses
+            .into_iter()
+            .filter_map(|course| {
+                let sessions = course_sessions_map[&course].clone();
+                sessions
+                    .first()
+                    .cloned()
+                    .map(|session| (course, session, sessions))
+            })
+            .collect::<Vec<_>>();
+        let results = WorkerPool::global().map(jobs, move |(course, session, sessions)| {
+            debug!("加载课程{course}的签到。");
+            let activities = Self::get_list_from_course(&session, &course).unwrap_or(vec![]);
+            // NOTE: 此处也会将没有过签到的课程排除掉。
+            // TODO: 需要修改。
+            let dont_exclude = activities.iter().any(|activity| {
+                matches!(activity, Self::RawSign(sign) if set_excludes
+                    && cxsign_utils::time_delta_since_to_now(sign.start_time_mills).num_days()
+                        < 160)
+            });
+            debug!("course: list_activities, ok.");
+            (course.get_id(), activities, sessions, dont_exclude)
+        });
+        let mut valid_signs = HashMap::new();
+        let mut other_signs = HashMap::new();
+        let mut other_activities = HashMap::new();
+        let mut excludes = Vec::new();
+        for (course_id, activities, sessions, dont_exclude) in results {
+            for activity in activities {
+                match activity {
+                    Self::RawSign(sign) if sign.is_valid() => {
+                        valid_signs.insert(sign, sessions.clone());
+                    }
+                    Self::RawSign(sign) => {
+                        other_signs.insert(sign, sessions.clone());
+                    }
+                    Self::Other(other_activity) => {
+                        other_activities.insert(other_activity, sessions.clone());
+                    }
                 }
             }
-            for h in handles {
-                debug!("handle: {h:?} join.");
-                h.join().unwrap();
+            if set_excludes && !dont_exclude {
+                excludes.push(course_id)
             }
         }
-        let valid_signs = Arc::into_inner(valid_signs).unwrap().into_inner().unwrap();
-        let other_signs = Arc::into_inner(other_signs).unwrap().into_inner().unwrap();
-        let other_activities = Arc::into_inner(other_activities)
-            .unwrap()
-            .into_inner()
-            .unwrap();
-        if set_excludes {
-            table.update_excludes(&Arc::into_inner(excludes).unwrap().into_inner().unwrap());
-        }
-        Ok((valid_signs, other_signs, other_activities))
+        ((valid_signs, other_signs, other_activities), excludes)
     }
     pub fn get_all_activities<'a, Sessions: Iterator<Item = &'a Session> + Clone>(
         table: ExcludeTable,
         sessions: Sessions,
         set_excludes: bool,
-    ) -> Result<ActivitiesSessionsMap, Box<ureq::Error>> {
+    ) -> Result<ActivitiesSessionsMap, cxsign_error::Error> {
         let courses = Course::get_courses(sessions)?;
         Self::get_activities(table, set_excludes, courses)
     }
+    /// [`Activity::get_all_activities`] 的异步版本，请求在独立的线程中进行。
+    ///
+    /// 数据库连接不能在线程间共享，因此返回的 [`Future`](std::future::Future) 不是 `Send` 的。
+    #[cfg(feature = "async")]
+    pub async fn get_all_activities_async<'a, Sessions: Iterator<Item = &'a Session>>(
+        table: ExcludeTable<'_>,
+        sessions: Sessions,
+        set_excludes: bool,
+    ) -> Result<ActivitiesSessionsMap, cxsign_error::Error> {
+        let excludes = table.get_excludes()?;
+        let set_excludes = set_excludes || excludes.is_empty();
+        let sessions = sessions.cloned().collect::<Vec<_>>();
+        let (activities, excludes) = cxsign_utils::unblock(move || {
+            let courses = Course::get_courses(sessions.iter())?;
+            Ok::<_, cxsign_error::Error>(Self::get_activities_of_courses(
+                &excludes,
+                set_excludes,
+                courses,
+            ))
+        })
+        .await?;
+        if set_excludes {
+            table.update_excludes(&excludes)?;
+        }
+        Ok(activities)
+    }
     pub fn get_list_from_course(
         session: &Session,
         c: &Course,
-    ) -> Result<Vec<Self>, Box<ureq::Error>> {
-        let r = crate::protocol::active_list(session, c.clone())?;
-        let r: GetActivityR = r.into_json().unwrap();
-        let activities = Arc::new(Mutex::new(Vec::new()));
+    ) -> Result<Vec<Self>, cxsign_error::Error> {
+        let r = crate::protocol::active_list(session, &session.get_fid(), c.clone())?;
+        let r: GetActivityR = r.read_json("activelist")?;
+        let mut activities = Vec::new();
         if let Some(data) = r.data {
-            let thread_count = 1;
-            let len = data.active_list.len();
-            let chunk_rest = len % thread_count;
-            let chunk_count = len / thread_count + if chunk_rest == 0 { 0 } else { 1 };
-            for i in 0..chunk_count {
-                let ars = &data.active_list[i * thread_count..if i != chunk_count - 1 {
-                    (i + 1) * thread_count
+            for ar in data.active_list {
+                if let Some(other_id) = ar.other_id
+                    && other_id
+                        .parse::<i64>()
+                        .is_ok_and(|other_id| (0..=5).contains(&other_id))
+                {
+                    let active_id = ar.id.to_string();
+                    let base_sign = RawSign {
+                        active_id,
+                        name: ar.name_one,
+                        course: c.clone(),
+                        other_id,
+                        status_code: ar.status,
+                        start_time_mills: ar.start_time_mills,
+                    };
+                    activities.push(Self::RawSign(base_sign))
                 } else {
-                    len
-                }];
-                let mut handles = Vec::new();
-                for ar in ars {
-                    let ar = ar.clone();
-                    let c = c.clone();
-                    let activities = activities.clone();
-                    let handle = std::thread::spawn(move || {
-                        if let Some(other_id) = ar.other_id
-                            && {
-                                let other_id_i64: i64 = other_id.parse().unwrap();
-                                (0..=5).contains(&other_id_i64)
-                            }
-                        {
-                            let active_id = ar.id.to_string();
-                            let base_sign = RawSign {
-                                active_id,
-                                name: ar.name_one,
-                                course: c.clone(),
-                                other_id,
-                                status_code: ar.status,
-                                start_time_mills: ar.start_time_mills,
-                            };
-                            activities.lock().unwrap().push(Self::RawSign(base_sign))
-                        } else {
-                            activities.lock().unwrap().push(Self::Other(OtherActivity {
-                                id: ar.id.to_string(),
-                                name: ar.name_one,
-                                course: c.clone(),
-                                status: ar.status,
-                                start_time_mills: ar.start_time_mills,
-                            }))
-                        }
-                    });
-                    handles.push(handle);
-                }
-                for h in handles {
-                    h.join().unwrap();
+                    activities.push(Self::Other(OtherActivity {
+                        id: ar.id.to_string(),
+                        name: ar.name_one,
+                        course: c.clone(),
+                        status: ar.status,
+                        start_time_mills: ar.start_time_mills,
+                    }))
                 }
             }
         }
-        let activities = Arc::into_inner(activities).unwrap().into_inner().unwrap();
         Ok(activities)
     }
 }
@@ -1,21 +1,32 @@
+use cxsign_transport::endpoint::{Endpoint, Host};
+use cxsign_transport::{retry, Transport, TransportExt};
 use cxsign_types::Course;
 use log::debug;
-use ureq::{Agent, Response};
+use ureq::Response;
 
 // 查询活动
-static ACTIVE_LIST: &str = "https://mobilelearn.chaoxing.com/v2/apis/active/student/activelist";
+static ACTIVE_LIST: Endpoint =
+    Endpoint::new(Host::MobileLearn, "/v2/apis/active/student/activelist").idempotent();
 
-pub fn active_list(client: &Agent, course: Course) -> Result<Response, Box<ureq::Error>> {
+/// `fid` 为用户所属机构的 fid, 为空时使用 `0`.
+pub fn active_list(
+    client: &dyn Transport,
+    fid: &str,
+    course: Course,
+) -> Result<Response, cxsign_error::Error> {
+    let fid = if fid.is_empty() { "0" } else { fid };
     let time = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap()
         .as_secs()
         .to_string();
     let url = format!(
-        "{ACTIVE_LIST}?fid=0&courseId={}&classId={}&showNotStartedActive=0&_={time}",
+        "{ACTIVE_LIST}?fid={fid}&courseId={}&classId={}&showNotStartedActive=0&_={time}",
         course.get_id(),
         course.get_class_id(),
     );
     debug!("{url}");
-    Ok(client.get(&url).call()?)
+    retry::call(&ACTIVE_LIST, || {
+        client.get(&url).endpoint(&ACTIVE_LIST).call()
+    })
 }
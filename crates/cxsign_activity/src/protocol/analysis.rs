@@ -1,19 +1,21 @@
-use ureq::{Agent, Response};
+use cxsign_transport::endpoint::{Endpoint, Host};
+use cxsign_transport::{retry, Transport, TransportExt};
+use ureq::Response;
 
 // analysis
-static ANALYSIS: &str = "https://mobilelearn.chaoxing.com/pptSign/analysis";
+static ANALYSIS: Endpoint = Endpoint::new(Host::MobileLearn, "/pptSign/analysis").idempotent();
 
-pub fn analysis(client: &Agent, active_id: &str) -> Result<Response, Box<ureq::Error>> {
+pub fn analysis(client: &dyn Transport, active_id: &str) -> Result<Response, cxsign_error::Error> {
     let url = ANALYSIS;
     let url = format!("{url}?vs=1&DB_STRATEGY=RANDOM&aid={active_id}");
-    Ok(client.get(&url).call()?)
+    retry::call(&ANALYSIS, || client.get(&url).endpoint(&ANALYSIS).call())
 }
 
 // analysis 2
-static ANALYSIS2: &str = "https://mobilelearn.chaoxing.com/pptSign/analysis2";
+static ANALYSIS2: Endpoint = Endpoint::new(Host::MobileLearn, "/pptSign/analysis2").idempotent();
 
-pub fn analysis2(client: &Agent, code: &str) -> Result<Response, Box<ureq::Error>> {
+pub fn analysis2(client: &dyn Transport, code: &str) -> Result<Response, cxsign_error::Error> {
     let url = ANALYSIS2;
     let url = format!("{url}?DB_STRATEGY=RANDOM&code={code}");
-    Ok(client.get(&url).call()?)
+    retry::call(&ANALYSIS2, || client.get(&url).endpoint(&ANALYSIS2).call())
 }
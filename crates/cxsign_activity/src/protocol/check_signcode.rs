@@ -1,17 +1,21 @@
-use ureq::{Agent, Response};
+use cxsign_transport::endpoint::{Endpoint, Host};
+use cxsign_transport::{retry, Transport, TransportExt};
+use ureq::Response;
 
 // 签到码检查
-static CHECK_SIGNCODE: &str =
-    "https://mobilelearn.chaoxing.com/widget/sign/pcStuSignController/checkSignCode";
+static CHECK_SIGNCODE: Endpoint = Endpoint::new(
+    Host::MobileLearn,
+    "/widget/sign/pcStuSignController/checkSignCode",
+)
+.idempotent();
 
 pub fn check_signcode(
-    client: &Agent,
+    client: &dyn Transport,
     active_id: &str,
     signcode: &str,
-) -> Result<Response, Box<ureq::Error>> {
-    Ok(client
-        .get(&format!(
-            "{CHECK_SIGNCODE}?activeId={active_id}&signCode={signcode}"
-        ))
-        .call()?)
+) -> Result<Response, cxsign_error::Error> {
+    let url = format!("{CHECK_SIGNCODE}?activeId={active_id}&signCode={signcode}");
+    retry::call(&CHECK_SIGNCODE, || {
+        client.get(&url).endpoint(&CHECK_SIGNCODE).call()
+    })
 }
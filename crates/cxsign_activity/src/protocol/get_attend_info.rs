@@ -1,11 +1,39 @@
-use ureq::{Agent, Response};
+use cxsign_error::ResponseExt;
+use cxsign_transport::endpoint::{Endpoint, Host};
+use cxsign_transport::{retry, Transport, TransportExt};
+use serde::Deserialize;
+use ureq::Response;
 
 // 获取签到之后的信息，例如签到时的 ip, UA, 时间等
 // 参见 "http://mobilelearn.chaoxing.com/page/sign/signIn?courseId=$&classId=$&activeId=$&fid=$"
-static GET_ATTEND_INFO: &str = "https://mobilelearn.chaoxing.com/v2/apis/sign/getAttendInfo";
+static GET_ATTEND_INFO: Endpoint =
+    Endpoint::new(Host::MobileLearn, "/v2/apis/sign/getAttendInfo").idempotent();
 
-pub fn get_attend_info(client: &Agent, active_id: &str) -> Result<Response, Box<ureq::Error>> {
-    Ok(client
-        .get(&format!("{GET_ATTEND_INFO}?activeId={active_id}&type=1"))
-        .call()?)
+pub fn get_attend_info(
+    client: &dyn Transport,
+    active_id: &str,
+) -> Result<Response, cxsign_error::Error> {
+    let url = format!("{GET_ATTEND_INFO}?activeId={active_id}&type=1");
+    retry::call(&GET_ATTEND_INFO, || {
+        client.get(&url).endpoint(&GET_ATTEND_INFO).call()
+    })
+}
+
+/// 查询签到后状态，即 [`get_attend_info`] 返回的 `status`.
+pub fn get_attend_status(
+    client: &dyn Transport,
+    active_id: &str,
+) -> Result<i64, cxsign_error::Error> {
+    #[derive(Deserialize)]
+    struct Status {
+        status: i64,
+    }
+    #[derive(Deserialize)]
+    struct Data {
+        data: Status,
+    }
+    let Data {
+        data: Status { status },
+    } = get_attend_info(client, active_id)?.read_json("getAttendInfo")?;
+    Ok(status)
 }
@@ -1,31 +1,65 @@
+use crate::sign::SignResult;
+use crate::utils::guess_sign_result_by_text;
+use cxsign_error::ResponseExt;
+use cxsign_transport::endpoint::{Endpoint, Host};
+use cxsign_transport::{retry, Transport, TransportExt};
 use cxsign_types::Location;
 use cxsign_user::Session;
+use log::info;
 use ureq::Response;
 
 // 签到
-static PPT_SIGN: &str = "https://mobilelearn.chaoxing.com/pptSign/stuSignajax";
+static PPT_SIGN: Endpoint = Endpoint::new(Host::MobileLearn, "/pptSign/stuSignajax");
 
-pub fn ureq_get(agent: &ureq::Agent, url: &str) -> Result<Response, Box<ureq::Error>> {
-    Ok(agent.get(url).call()?)
+pub fn ureq_get(agent: &dyn Transport, url: &str) -> Result<Response, cxsign_error::Error> {
+    agent.get(url).call()
 }
 
-pub fn general_sign(session: &Session, active_id: &str) -> Result<Response, Box<ureq::Error>> {
+/// 提交签到，`url` 为签到接口的完整地址，返回根据响应内容判断的签到结果。
+///
+/// 签到接口不能直接重试：遇到暂时性的错误（包括读取响应内容时的错误）时，
+/// 先通过 [`get_attend_status`](super::get_attend_status) 确认是否已经签到成功，
+/// 已签到时直接返回 [`SignResult::Susses`], 否则才重新提交。
+pub fn submit_sign(
+    client: &dyn Transport,
+    active_id: &str,
+    url: &str,
+) -> Result<SignResult, cxsign_error::Error> {
+    retry::retry_with_check(
+        &retry::get_retry_policy(),
+        PPT_SIGN.get_path(),
+        || {
+            let text = client.get(url).call()?.read_string(PPT_SIGN.get_path())?;
+            Ok(guess_sign_result_by_text(&text))
+        },
+        || {
+            if super::get_attend_status(client, active_id)? == 1 {
+                info!("签到请求失败，但签到状态显示已签到成功，不再重试。");
+                Ok(Some(SignResult::Susses))
+            } else {
+                Ok(None)
+            }
+        },
+    )
+}
+
+pub fn general_sign(session: &Session, active_id: &str) -> Result<SignResult, cxsign_error::Error> {
     let uid = session.get_uid();
     let fid = session.get_fid();
     let stu_name = session.get_stu_name();
-    session.get(&format!("{PPT_SIGN}?activeId={active_id}&uid={uid}&clientip=&latitude=-1&longitude=-1&appType=15&fid={fid}&name={stu_name}")).call().map_err(|e| e.into())
+    submit_sign(session, active_id, &format!("{PPT_SIGN}?activeId={active_id}&uid={uid}&clientip=&latitude=-1&longitude=-1&appType=15&fid={fid}&name={stu_name}"))
 }
 
 pub fn photo_sign(
     session: &Session,
     active_id: &str,
     object_id: &str,
-) -> Result<Response, Box<ureq::Error>> {
+) -> Result<SignResult, cxsign_error::Error> {
     let uid = session.get_uid();
     let fid = session.get_fid();
     let stu_name = session.get_stu_name();
     // NOTE 存疑。
-    session.get(&format!("{PPT_SIGN}?activeId={active_id}&uid={uid}&clientip=&useragent=&latitude=-1&longitude=-1&appType=15&fid={fid}&objectId={object_id}&name={}", percent_encoding::utf8_percent_encode(stu_name, percent_encoding::NON_ALPHANUMERIC))).call().map_err(|e| e.into())
+    submit_sign(session, active_id, &format!("{PPT_SIGN}?activeId={active_id}&uid={uid}&clientip=&useragent=&latitude=-1&longitude=-1&appType=15&fid={fid}&objectId={object_id}&name={}", percent_encoding::utf8_percent_encode(stu_name, percent_encoding::NON_ALPHANUMERIC)))
 }
 
 pub fn qrcode_sign_url(
@@ -68,9 +102,9 @@ pub fn qrcode_sign(
     enc: &str,
     active_id: &str,
     location: Option<&Location>,
-) -> Result<Response, Box<ureq::Error>> {
+) -> Result<SignResult, cxsign_error::Error> {
     let url = qrcode_sign_url(session, enc, active_id, location);
-    Ok(session.get(&url).call()?)
+    submit_sign(session, active_id, &url)
 }
 pub fn location_sign_url(
     session: &Session,
@@ -92,25 +126,19 @@ pub fn location_sign(
     location: &Location,
     active_id: &str,
     is_auto_location: bool,
-) -> Result<Response, Box<ureq::Error>> {
-    Ok(session
-        .get(&location_sign_url(
-            session,
-            location,
-            active_id,
-            is_auto_location,
-        ))
-        .call()?)
+) -> Result<SignResult, cxsign_error::Error> {
+    let url = location_sign_url(session, location, active_id, is_auto_location);
+    submit_sign(session, active_id, &url)
 }
 
 pub fn signcode_sign(
     session: &Session,
     active_id: &str,
     signcode: &str,
-) -> Result<Response, Box<ureq::Error>> {
+) -> Result<SignResult, cxsign_error::Error> {
     let uid = session.get_uid();
     let fid = session.get_fid();
     let stu_name = session.get_stu_name();
     let url = format!("{PPT_SIGN}?activeId={active_id}&uid={uid}&clientip=&latitude=-1&longitude=-1&appType=15&fid={fid}&name={stu_name}&signCode={signcode}");
-    Ok(session.get(&url).call()?)
+    submit_sign(session, active_id, &url)
 }
@@ -1,35 +1,37 @@
+use cxsign_transport::endpoint::{Endpoint, Host};
+use cxsign_transport::{retry, Transport, TransportExt};
 use cxsign_types::Course;
-use ureq::{Agent, Response};
+use ureq::Response;
 
 // 预签到
-static PRE_SIGN: &str = "https://mobilelearn.chaoxing.com/newsign/preSign";
+static PRE_SIGN: Endpoint = Endpoint::new(Host::MobileLearn, "/newsign/preSign").idempotent();
 
 pub fn pre_sign(
-    client: &Agent,
+    client: &dyn Transport,
     course: Course,
     active_id: &str,
     uid: &str,
-) -> Result<Response, Box<ureq::Error>> {
+) -> Result<Response, cxsign_error::Error> {
     let course_id = course.get_id();
     let class_id = course.get_class_id();
     let url = PRE_SIGN;
     let url =
         format!("{url}?courseId={course_id}&classId={class_id}&activePrimaryId={active_id}&general=1&sys=1&ls=1&appType=15&&tid=&uid={uid}&ut=s&isTeacherViewOpen=0");
-    Ok(client.get(&url).call()?)
+    retry::call(&PRE_SIGN, || client.get(&url).endpoint(&PRE_SIGN).call())
 }
 pub fn pre_sign_for_qrcode_sign(
-    client: &Agent,
+    client: &dyn Transport,
     course: Course,
     active_id: &str,
     uid: &str,
     c: &str,
     enc: &str,
-) -> Result<Response, Box<ureq::Error>> {
+) -> Result<Response, cxsign_error::Error> {
     let course_id = course.get_id();
     let class_id = course.get_class_id();
     let url =
         format!("{PRE_SIGN}?courseId={course_id}&classId={class_id}&activePrimaryId={active_id}&general=1&sys=1&ls=1&appType=15&&tid=&uid={uid}&ut=s&isTeacherViewOpen=0&rcode={}", format_args!(
             "&rcode={}",percent_encoding::utf8_percent_encode(&format!("SIGNIN:aid={active_id}&source=15&Code={c}&enc={enc}"), percent_encoding::NON_ALPHANUMERIC)
         ));
-    Ok(client.get(&url).call()?)
+    retry::call(&PRE_SIGN, || client.get(&url).endpoint(&PRE_SIGN).call())
 }
@@ -1,11 +1,18 @@
+use cxsign_transport::endpoint::{Endpoint, Host};
+use cxsign_transport::{retry, Transport, TransportExt};
 use log::debug;
-use ureq::{Agent, Response};
+use ureq::Response;
 
 // 签到信息获取
-static SIGN_DETAIL: &str = "https://mobilelearn.chaoxing.com/newsign/signDetail";
+static SIGN_DETAIL: Endpoint = Endpoint::new(Host::MobileLearn, "/newsign/signDetail").idempotent();
 
-pub fn sign_detail(client: &Agent, active_id: &str) -> Result<Response, Box<ureq::Error>> {
+pub fn sign_detail(
+    client: &dyn Transport,
+    active_id: &str,
+) -> Result<Response, cxsign_error::Error> {
     let url = format!("{SIGN_DETAIL}?activePrimaryId={active_id}&type=1");
     debug!("{url}");
-    Ok(client.get(&url).call()?)
+    retry::call(&SIGN_DETAIL, || {
+        client.get(&url).endpoint(&SIGN_DETAIL).call()
+    })
 }
@@ -0,0 +1,46 @@
+use crate::sign::{PreSignResult, SignResult, SignState, SignTrait};
+use cxsign_user::Session;
+use std::future::Future;
+
+/// # [`AsyncSignTrait`]
+/// [`SignTrait`] 的异步版本，为所有可克隆的签到类型自动实现。
+///
+/// 各方法会克隆签到及会话，在独立的线程中调用 [`SignTrait`] 的对应方法，
+/// 不依赖特定的异步运行时，返回的 [`Future`] 均为 `Send` 的。
+pub trait AsyncSignTrait: SignTrait + Clone + Send + 'static {
+    /// 参见 [`SignTrait::get_sign_state`].
+    fn get_sign_state_async(
+        &self,
+        session: &Session,
+    ) -> impl Future<Output = Result<SignState, cxsign_error::Error>> + Send {
+        let (sign, session) = (self.clone(), session.clone());
+        cxsign_utils::unblock(move || sign.get_sign_state(&session))
+    }
+    /// 参见 [`SignTrait::pre_sign`].
+    fn pre_sign_async(
+        &self,
+        session: &Session,
+    ) -> impl Future<Output = Result<PreSignResult, cxsign_error::Error>> + Send {
+        let (sign, session) = (self.clone(), session.clone());
+        cxsign_utils::unblock(move || sign.pre_sign(&session))
+    }
+    /// 参见 [`SignTrait::sign`].
+    fn sign_async(
+        &self,
+        session: &Session,
+        pre_sign_result: PreSignResult,
+    ) -> impl Future<Output = Result<SignResult, cxsign_error::Error>> + Send {
+        let (sign, session) = (self.clone(), session.clone());
+        cxsign_utils::unblock(move || sign.sign(&session, pre_sign_result))
+    }
+    /// 参见 [`SignTrait::pre_sign_and_sign`].
+    fn pre_sign_and_sign_async(
+        &self,
+        session: &Session,
+    ) -> impl Future<Output = Result<SignResult, cxsign_error::Error>> + Send {
+        let (sign, session) = (self.clone(), session.clone());
+        cxsign_utils::unblock(move || sign.pre_sign_and_sign(&session))
+    }
+}
+
+impl<T: SignTrait + Clone + Send + 'static> AsyncSignTrait for T {}
@@ -1,3 +1,5 @@
+#[cfg(feature = "async")]
+mod async_sign;
 mod gesture;
 mod location;
 mod normal;
@@ -6,6 +8,8 @@ mod qrcode;
 mod raw;
 mod signcode;
 
+#[cfg(feature = "async")]
+pub use async_sign::*;
 pub use gesture::*;
 pub use location::*;
 pub use normal::*;
@@ -17,7 +21,6 @@ use std::ops::Add;
 
 use cxsign_types::{Course, Dioption, LocationWithRange};
 use cxsign_user::Session;
-use serde::Deserialize;
 
 pub type CaptchaId = String;
 
@@ -73,23 +76,11 @@ pub trait SignTrait: Ord {
         self.as_inner().status_code == 1
             && std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH.add(time))
-                .unwrap()
-                < two_hours
+                .map_or(true, |d| d < two_hours)
     }
     /// 获取签到后状态。参见返回类型 [`SignState`].
     fn get_sign_state(&self, session: &Session) -> Result<SignState, cxsign_error::Error> {
-        let r = crate::protocol::get_attend_info(session, &self.as_inner().active_id)?;
-        #[derive(Deserialize)]
-        struct Status {
-            status: i64,
-        }
-        #[derive(Deserialize)]
-        struct Data {
-            data: Status,
-        }
-        let Data {
-            data: Status { status },
-        } = r.into_json().unwrap();
+        let status = crate::protocol::get_attend_status(session, &self.as_inner().active_id)?;
         Ok(status.into())
     }
     /// 通过签到结果的字符串判断签到结果如何。
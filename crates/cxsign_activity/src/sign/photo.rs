@@ -31,12 +31,11 @@ impl SignTrait for PhotoSign {
             PreSignResult::Susses => Ok(SignResult::Susses),
             _ => {
                 let photo = self.photo.as_ref().unwrap();
-                let r = protocol::photo_sign(
+                protocol::photo_sign(
                     session,
                     self.raw_sign.active_id.as_str(),
                     photo.get_object_id(),
-                )?;
-                Ok(self.guess_sign_result_by_text(&r.into_string().unwrap()))
+                )
             }
         }
     }
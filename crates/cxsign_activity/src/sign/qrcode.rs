@@ -57,7 +57,7 @@ impl SignTrait for QrCodeSign {
         let enc = self.enc.as_deref().unwrap_or("");
         let raw = self.as_inner();
         let active_id = raw.active_id.as_str();
-        let uid = session.get_uid();
+        let uid = &session.get_uid();
         let response_of_presign = protocol::pre_sign_for_qrcode_sign(
             session,
             raw.course.clone(),
@@ -3,10 +3,11 @@ use crate::sign::{
     GestureSign, LocationSign, NormalSign, PhotoSign, PreSignResult, QrCodeSign, Sign, SignDetail,
     SignResult, SignTrait, SigncodeSign,
 };
+use cxsign_error::ResponseExt;
 use cxsign_types::{Course, Dioption, Location, LocationWithRange};
 use cxsign_user::Session;
 use cxsign_utils::get_width_str_should_be;
-use log::{debug, error, info, trace, warn};
+use log::{debug, info, trace, warn};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
@@ -27,7 +28,7 @@ impl SignTrait for RawSign {
     }
     fn pre_sign(&self, session: &Session) -> Result<PreSignResult, cxsign_error::Error> {
         let active_id = self.active_id.as_str();
-        let uid = session.get_uid();
+        let uid = &session.get_uid();
         let response_of_pre_sign =
             protocol::pre_sign(session, self.course.clone(), active_id, uid)?;
         info!("用户[{}]预签到已请求。", session.get_stu_name());
@@ -40,10 +41,7 @@ impl SignTrait for RawSign {
     ) -> Result<SignResult, cxsign_error::Error> {
         match pre_sign_result {
             PreSignResult::Susses => Ok(SignResult::Susses),
-            _ => {
-                let r = protocol::general_sign(session, self.active_id.as_str())?;
-                Ok(self.guess_sign_result_by_text(&r.into_string().unwrap()))
-            }
+            _ => protocol::general_sign(session, self.active_id.as_str()),
         }
     }
 }
@@ -78,8 +76,7 @@ impl RawSign {
             result: i64,
         }
         let CheckR { result } = crate::protocol::check_signcode(session, active_id, signcode)?
-            .into_json()
-            .unwrap();
+            .read_json("checkSignCode")?;
         Ok(result == 1)
     }
     pub(crate) fn get_sign_detail(
@@ -100,7 +97,7 @@ impl RawSign {
             is_photo_sign,
             is_refresh_qrcode,
             sign_code,
-        } = r.into_json().unwrap();
+        } = r.read_json("signDetail")?;
         Ok(SignDetail {
             is_photo: is_photo_sign > 0,
             is_refresh_qrcode: is_refresh_qrcode > 0,
@@ -116,12 +113,16 @@ impl RawSign {
 impl RawSign {
     pub fn to_sign(self, session: &Session) -> Sign {
         if let Ok(sign_detail) = RawSign::get_sign_detail(self.active_id.as_str(), session) {
-            let r#else = |e| {
-                error!("{}", self.other_id);
-                error!("{}", self.course.get_name());
-                panic!("{e}")
+            let Ok(other_id) = self.other_id.parse::<u8>() else {
+                warn!(
+                    "无法解析课程[{}]中签到[{}]的类型：{}.",
+                    self.course.get_name(),
+                    self.name,
+                    self.other_id
+                );
+                return Sign::Unknown(self);
             };
-            match self.other_id.parse::<u8>().unwrap_or_else(r#else) {
+            match other_id {
                 0 => {
                     if sign_detail.is_photo {
                         Sign::Photo(PhotoSign {
@@ -210,11 +211,11 @@ impl RawSign {
         session: &Session,
         response_of_presign: ureq::Response,
     ) -> Result<PreSignResult, cxsign_error::Error> {
-        let html = response_of_presign.into_string().unwrap();
+        let html = response_of_presign.read_string("preSign")?;
         trace!("预签到请求结果：{html}");
         if let Some(start_of_statuscontent_h1) = html.find("id=\"statuscontent\"") {
             let html = &html[start_of_statuscontent_h1 + 19..];
-            let end_of_statuscontent_h1 = html.find("</").unwrap();
+            let end_of_statuscontent_h1 = html.find("</").unwrap_or(html.len());
             let content_of_statuscontent_h1 = html[0..end_of_statuscontent_h1].trim();
             debug!("content_of_statuscontent_h1: {content_of_statuscontent_h1:?}.");
             if content_of_statuscontent_h1.contains("签到成功") {
@@ -225,24 +226,29 @@ impl RawSign {
         if let Some(location) = LocationWithRange::find_in_html(&html) {
             captcha_id_and_location.push_second(location);
         }
-        if let Some(start_of_captcha_id) = html.find("captchaId: '") {
-            let id = &html[start_of_captcha_id + 12..start_of_captcha_id + 12 + 32];
+        if let Some(start_of_captcha_id) = html.find("captchaId: '")
+            && let Some(id) = html.get(start_of_captcha_id + 12..start_of_captcha_id + 12 + 32)
+        {
             debug!("captcha_id: {id}");
             captcha_id_and_location.push_first(id.to_string());
         }
         let response_of_analysis = protocol::analysis(session, active_id)?;
-        let data = response_of_analysis.into_string().unwrap();
+        let data = response_of_analysis.read_string("analysis")?;
         let code = {
-            let start_of_code = data.find("code='+'").unwrap() + 8;
-            let data = &data[start_of_code..data.len()];
-            let end_of_code = data.find('\'').unwrap();
+            let Some(start_of_code) = data.find("code='+'") else {
+                return Err(cxsign_error::Error::unexpected_response("analysis", &data));
+            };
+            let data = &data[start_of_code + 8..data.len()];
+            let Some(end_of_code) = data.find('\'') else {
+                return Err(cxsign_error::Error::unexpected_response("analysis", data));
+            };
             &data[0..end_of_code]
         };
         debug!("code: {code:?}");
         let _response_of_analysis2 = protocol::analysis2(session, code)?;
         debug!(
             "analysis 结果：{}",
-            _response_of_analysis2.into_string().unwrap()
+            _response_of_analysis2.read_string("analysis2")?
         );
         std::thread::sleep(std::time::Duration::from_millis(500));
         Ok(PreSignResult::Data(captcha_id_and_location))
@@ -256,8 +262,7 @@ impl RawSign {
         signcode: &str,
     ) -> Result<SignResult, cxsign_error::Error> {
         if Self::check_signcode(session, &self.active_id, signcode)? {
-            let r = protocol::signcode_sign(session, self.active_id.as_str(), signcode)?;
-            Ok(self.guess_sign_result_by_text(&r.into_string().unwrap()))
+            protocol::signcode_sign(session, self.active_id.as_str(), signcode)
         } else {
             Ok(SignResult::Fail {
                 msg: "签到码或手势不正确".into(),
@@ -358,3 +363,40 @@ impl RawSign {
     //     Ok(())
     // }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::sign::{PreSignResult, RawSign, SignTrait};
+    use cxsign_transport::record::Replayer;
+    use cxsign_types::{Course, Dioption};
+    use cxsign_user::Session;
+    use std::sync::Arc;
+
+    // 模拟服务器的往返测试：测试数据由 `cxsign_mock` 的响应录制而成，只检查录制与严格回放能否衔接，
+    // 不能发现真实页面的变化。
+    #[test]
+    fn analysis_after_mock_recorded_pre_sign() {
+        // 严格回放，`analysis2` 的请求参数须与录制时一致，即 `code` 须被正确提取。
+        let replayer = Replayer::load(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/fixtures/mock/location_pre_sign"
+        ))
+        .unwrap()
+        .strict();
+        let session = Session::from_transport(Arc::new(replayer), "13800000000").unwrap();
+        let sign = RawSign {
+            start_time_mills: 0,
+            active_id: "3000".to_owned(),
+            name: "位置签到".to_owned(),
+            course: Course::new(200, 100, "测试教师", "", "测试课程"),
+            other_id: "4".to_owned(),
+            status_code: 1,
+        };
+        let Ok(PreSignResult::Data(Dioption::Both(captcha_id, location))) = sign.pre_sign(&session)
+        else {
+            panic!("预签到结果应包含验证码 id 和位置。")
+        };
+        assert_eq!(captcha_id, "MockCaptchaId0000000000000000000");
+        assert_eq!(location.get_range(), 50);
+    }
+}
@@ -1,12 +1,14 @@
 use crate::protocol;
 use crate::sign::{CaptchaId, SignResult, SignTrait};
 use cxsign_captcha::protocol::CAPTCHA_ID;
+use cxsign_transport::Transport;
 use cxsign_types::{Location, LocationWithRange};
 use cxsign_user::Session;
 use log::{debug, warn};
 
 pub fn secondary_verification(
-    agent: &ureq::Agent,
+    agent: &dyn Transport,
+    active_id: &str,
     url: String,
     captcha_id: &Option<CaptchaId>,
 ) -> Result<SignResult, cxsign_error::Error> {
@@ -17,12 +19,8 @@ pub fn secondary_verification(
         CAPTCHA_ID
     };
     let url_param = cxsign_captcha::utils::captcha_solver(agent, captcha_id)?;
-    let r = {
-        let url = url + "&validate=" + &url_param;
-        let r = protocol::ureq_get(agent, &url)?;
-        guess_sign_result_by_text(&r.into_string().unwrap())
-    };
-    Ok(r)
+    let url = url + "&validate=" + &url_param;
+    protocol::submit_sign(agent, active_id, &url)
 }
 
 pub fn guess_sign_result_by_text(text: &str) -> SignResult {
@@ -68,8 +66,7 @@ pub(crate) fn sign_unchecked_with_location<T: SignTrait>(
     }
     for location in locations {
         let url = url_getter(&location);
-        let r = protocol::ureq_get(session, &url)?;
-        match sign.guess_sign_result_by_text(&r.into_string().unwrap()) {
+        match protocol::submit_sign(session, &sign.as_inner().active_id, &url)? {
             SignResult::Susses => return Ok(SignResult::Susses),
             SignResult::Fail { msg } => {
                 if msg.starts_with("validate") {
@@ -81,7 +78,12 @@ pub(crate) fn sign_unchecked_with_location<T: SignTrait>(
                     } else {
                         url
                     };
-                    return secondary_verification(session, url, &captcha_id);
+                    return secondary_verification(
+                        session,
+                        &sign.as_inner().active_id,
+                        url,
+                        &captcha_id,
+                    );
                 } else if msg.contains("位置") || msg.contains("Location") || msg.contains("范围")
                 {
                     continue;
@@ -1,33 +1,35 @@
+use cxsign_transport::endpoint::{Endpoint, Host};
+use cxsign_transport::{Transport, TransportExt};
 use std::fmt::Display;
 
 pub static CAPTCHA_ID: &str = "Qt9FIw9o4pwRjOyqM6yizZBh682qN2TU";
 // 获取滑块。
-static GET_CAPTCHA: &str = "https://captcha.chaoxing.com/captcha/get/verification/image";
+static GET_CAPTCHA: Endpoint = Endpoint::new(Host::Captcha, "/captcha/get/verification/image");
 // 滑块验证。
-static CHECK_CAPTCHA: &str = "https://captcha.chaoxing.com/captcha/check/verification/result";
+static CHECK_CAPTCHA: Endpoint = Endpoint::new(Host::Captcha, "/captcha/check/verification/result");
 // 获取服务器时间。
-static GET_SERVER_TIME: &str = "https://captcha.chaoxing.com/captcha/get/conf";
+static GET_SERVER_TIME: Endpoint = Endpoint::new(Host::Captcha, "/captcha/get/conf");
 // Doesn't matter.
 static CALLBACK_NAME: &str = "jQuery_114514_1919810";
 
 pub fn get_server_time(
-    agent: &ureq::Agent,
+    agent: &dyn Transport,
     captcha_id: &str,
     time_stamp_mills: impl Display + Copy,
-) -> Result<ureq::Response, Box<ureq::Error>> {
+) -> Result<ureq::Response, cxsign_error::Error> {
     let url = format!(
         "{GET_SERVER_TIME}?callback={CALLBACK_NAME}&captchaId={captcha_id}&_={time_stamp_mills}"
     );
-    Ok(agent.get(&url).call()?)
+    agent.get(&url).call()
 }
 
 pub fn get_captcha(
-    agent: &ureq::Agent,
+    agent: &dyn Transport,
     captcha_id: &str,
     captcha_key: &str,
     tmp_token: &str,
     time_stamp_mills: impl Display + Copy,
-) -> Result<ureq::Response, Box<ureq::Error>> {
+) -> Result<ureq::Response, cxsign_error::Error> {
     let url = format!(
         "{GET_CAPTCHA}?{}&{}&{}&{}&{}&{}&{}&_={time_stamp_mills}",
         format_args!("callback={}", CALLBACK_NAME),
@@ -38,16 +40,16 @@ pub fn get_captcha(
         "version=1.1.16",
         "referer=https%3A%2F%2Fmobilelearn.chaoxing.com",
     );
-    Ok(agent.get(&url).call()?)
+    agent.get(&url).call()
 }
 
 pub fn check_captcha(
-    agent: &ureq::Agent,
+    agent: &dyn Transport,
     captcha_id: &str,
     x: impl Display + Copy,
     token: &str,
     time_stamp_mills: impl Display + Copy,
-) -> Result<ureq::Response, Box<ureq::Error>> {
+) -> Result<ureq::Response, cxsign_error::Error> {
     let url = format!(
         "{CHECK_CAPTCHA}?{}&{}&{}&{}&{}&{}&{}&{}&_={time_stamp_mills}",
         format_args!("callback={CALLBACK_NAME}",),
@@ -62,5 +64,5 @@ pub fn check_captcha(
     let get = agent
         .get(&url)
         .set("Referer", "https://mobilelearn.chaoxing.com");
-    Ok(get.call()?)
+    get.call()
 }
@@ -1,3 +1,5 @@
+use cxsign_error::{Error, ResponseExt};
+use cxsign_transport::Transport;
 use log::{debug, warn};
 use serde::Deserialize;
 
@@ -7,13 +9,18 @@ use crate::protocol::{check_captcha, get_captcha, get_server_time};
 // Doesn't matter.
 static CALLBACK_NAME: &str = "jQuery_114514_1919810";
 
-pub fn trim_response_to_json<'a, T>(text: &'a str) -> Result<T, ureq::serde_json::Error>
+/// 去除 JSONP 响应的回调函数包装并解析其中的 JSON.
+pub fn trim_response_to_json<'a, T>(text: &'a str) -> Result<T, Error>
 where
     T: ureq::serde::de::Deserialize<'a>,
 {
-    let s = &text[CALLBACK_NAME.len() + 1..text.len() - 1];
+    let s = text
+        .strip_prefix(CALLBACK_NAME)
+        .and_then(|s| s.strip_prefix('('))
+        .and_then(|s| s.strip_suffix(')'))
+        .ok_or_else(|| Error::unexpected_response("captcha", text))?;
     debug!("{s}");
-    ureq::serde_json::from_str(s)
+    ureq::serde_json::from_str(s).map_err(|_| Error::unexpected_response("captcha", text))
 }
 
 pub fn generate_secrets(
@@ -31,10 +38,10 @@ pub fn generate_secrets(
 }
 
 pub fn auto_solve_captcha(
-    agent: &ureq::Agent,
+    agent: &dyn Transport,
     captcha_id: &str,
     time: u128,
-) -> Result<ValidateResult, Box<ureq::Error>> {
+) -> Result<ValidateResult, Error> {
     let (key, tmp_token) = generate_secrets(captcha_id, time, "slide");
     let r = get_captcha(agent, captcha_id, &key, &tmp_token, time + 1)?;
     #[derive(Deserialize)]
@@ -56,33 +63,29 @@ pub fn auto_solve_captcha(
             shade_image_url,
             cutout_image_url,
         },
-    } = trim_response_to_json(&r.into_string().unwrap()).unwrap();
+    } = trim_response_to_json(&r.read_string("captcha")?)?;
     debug!("滑块图片 url：{}, {}", shade_image_url, cutout_image_url);
-    let agent = ureq::Agent::new();
-    let small_img = cxsign_imageproc::download_image(&agent, &cutout_image_url)?;
-    let big_img = cxsign_imageproc::download_image(&agent, &shade_image_url)?;
+    let small_img = cxsign_imageproc::download_image(agent, &cutout_image_url)?;
+    let big_img = cxsign_imageproc::download_image(agent, &shade_image_url)?;
     let max_x = cxsign_imageproc::find_sub_image(&big_img, &small_img);
     debug!("本地滑块结果：{max_x}");
-    let r = check_captcha(&agent, captcha_id, max_x, &token, time + 2)?;
-    let v: ValidateResult = trim_response_to_json(&r.into_string().unwrap()).unwrap();
+    let r = check_captcha(agent, captcha_id, max_x, &token, time + 2)?;
+    let v: ValidateResult = trim_response_to_json(&r.read_string("captcha")?)?;
     debug!("滑块结果：{v:?}");
     Ok(v)
 }
 
-pub fn captcha_solver(
-    agent: &ureq::Agent,
-    captcha_id: &str,
-) -> Result<String, cxsign_error::Error> {
+pub fn captcha_solver(agent: &dyn Transport, captcha_id: &str) -> Result<String, Error> {
     let time = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
+        .unwrap_or_default()
         .as_millis();
     let r = get_server_time(agent, captcha_id, time)?;
     #[derive(Deserialize)]
     struct Tmp {
         t: u128,
     }
-    let Tmp { t } = trim_response_to_json(r.into_string().unwrap().as_str()).unwrap();
+    let Tmp { t } = trim_response_to_json(&r.read_string("captcha")?)?;
     // 事不过三。
     for i in 0..3 {
         if let Some(c) = auto_solve_captcha(agent, captcha_id, t + i)?.get_validate_info() {
@@ -91,7 +94,7 @@ pub fn captcha_solver(
             warn!("滑块验证失败，即将重试。")
         }
     }
-    Err(cxsign_error::Error::CaptchaEmptyError)
+    Err(Error::CaptchaEmptyError)
 }
 
 #[derive(Deserialize, Debug)]
@@ -106,10 +109,15 @@ impl ValidateResult {
         struct Tmp {
             validate: String,
         }
-        self.extra_data.as_ref().map(|s| {
+        self.extra_data.as_ref().and_then(|s| {
             debug!("{s}");
-            let Tmp { validate } = ureq::serde_json::from_str(s).unwrap();
-            validate
+            match ureq::serde_json::from_str::<Tmp>(s) {
+                Ok(Tmp { validate }) => Some(validate),
+                Err(e) => {
+                    warn!("无法解析滑块验证结果：{e}.");
+                    None
+                }
+            }
         })
     }
 }
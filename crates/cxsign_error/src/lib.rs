@@ -1,13 +1,61 @@
+mod login;
+mod response;
+
+pub use login::*;
+pub use response::*;
 use thiserror::Error;
 
+/// 错误信息中保留的响应内容的最大字符数。
+const MAX_BODY_CHARS: usize = 256;
+
 #[derive(Error, Debug)]
 pub enum Error {
-    #[error("登录失败！")]
+    #[deprecated(note = "使用 `LoginRejected`、`MissingPassword` 等具体的错误类型。")]
+    #[error("登录失败！{0}")]
     LoginError(String),
-    #[error(transparent)]
-    AgentError(#[from] Box<ureq::Error>),
+    /// 服务器拒绝登录。
+    #[error("登录失败！{0}")]
+    LoginRejected(LoginOutcome),
+    /// 网络传输错误，如无法连接、连接超时等。
+    #[error("网络请求失败：{0}")]
+    TransportError(Box<ureq::Transport>),
+    /// 读取响应内容时的网络错误，如连接在响应内容读取完之前中断。
+    #[error("接口 `{endpoint}` 的响应读取失败：{source}")]
+    ResponseReadError {
+        /// 接口名称。
+        endpoint: &'static str,
+        source: std::io::Error,
+    },
+    /// 服务器返回了表示错误的状态码。
+    #[error("服务器返回了错误的状态码 {status}（{url}）！")]
+    HttpStatusError { status: u16, url: String },
+    /// 服务器的响应不符合预期，例如接口格式发生了变化或会话已过期。
+    #[error("接口 `{endpoint}` 的响应不符合预期！响应内容：{body:?}")]
+    UnexpectedResponseError {
+        /// 接口名称。
+        endpoint: &'static str,
+        /// 截断后的响应内容。
+        body: String,
+    },
     #[error(transparent)]
     IoError(#[from] std::io::Error),
+    /// 数据库错误。
+    #[error("数据库操作失败：{0}")]
+    StorageError(#[from] sqlite::Error),
+    /// 没有可用于登录的密码，如密码为空或没有保存密码。
+    #[error("没有密码！")]
+    MissingPassword,
+    /// 会话已失效并已重新登录，但请求不能安全地重复发送（参见 `Endpoint::is_idempotent`），因此没有重新发送。
+    ///
+    /// 会话失效时服务器返回的是登录页，请求并未生效，调用者可以确认后再次发送。
+    #[error("会话已失效并已重新登录，请求未重新发送")]
+    SessionRenewed,
+    /// 会话已失效，且账号没有保存密码（如通过导入 Cookies 添加的账号），无法重新登录。
+    #[error("会话已过期，请重新导入 Cookies")]
+    SessionExpired,
+    /// 用户中止了操作，如取消二维码登录。
+    #[error("操作已取消：{0}")]
+    Cancelled(String),
     #[error("`enc` 为空！")]
     EncError(String),
     #[error("无法获取位置信息！")]
@@ -16,6 +64,86 @@ pub enum Error {
     CaptchaEmptyError,
     #[error("设置位置预处理错误！")]
     SetLocationPreprocessorError,
-    #[error("数据解析失败！")]
-    ParseError(String)
+    #[error("数据解析失败！{0}")]
+    ParseError(String),
+    /// 会话配置有误，如代理地址或证书无法解析。
+    #[error("会话配置有误！{0}")]
+    ConfigError(String),
+    /// 命令行中的操作失败，如没有指定的账号或部分账号签到失败。
+    #[error("{0}")]
+    CommandFailed(String),
+}
+
+impl Error {
+    /// 构造 [`Error::UnexpectedResponseError`], 响应内容过长时会被截断。
+    pub fn unexpected_response(endpoint: &'static str, body: &str) -> Self {
+        let body = if body.chars().count() > MAX_BODY_CHARS {
+            body.chars().take(MAX_BODY_CHARS).collect::<String>() + "..."
+        } else {
+            body.to_owned()
+        };
+        Error::UnexpectedResponseError { endpoint, body }
+    }
+}
+
+impl From<ureq::Error> for Error {
+    fn from(e: ureq::Error) -> Self {
+        match e {
+            ureq::Error::Status(status, response) => Error::HttpStatusError {
+                status,
+                url: response.get_url().to_owned(),
+            },
+            ureq::Error::Transport(transport) => Error::TransportError(Box::new(transport)),
+        }
+    }
+}
+
+impl From<Box<ureq::Error>> for Error {
+    fn from(e: Box<ureq::Error>) -> Self {
+        Error::from(*e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_json, Error, ResponseExt, MAX_BODY_CHARS};
+
+    #[test]
+    fn unexpected_response_truncates_body() {
+        let body = "签".repeat(MAX_BODY_CHARS * 2);
+        let Error::UnexpectedResponseError { endpoint, body } =
+            Error::unexpected_response("test", &body)
+        else {
+            unreachable!()
+        };
+        assert_eq!(endpoint, "test");
+        assert_eq!(body.chars().count(), MAX_BODY_CHARS + 3);
+    }
+
+    #[test]
+    fn parse_json_reports_endpoint() {
+        let r = parse_json::<i64>("test", "<html></html>");
+        assert!(matches!(
+            r,
+            Err(Error::UnexpectedResponseError {
+                endpoint: "test",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn read_errors_report_endpoint() {
+        // 响应内容在 `Content-Length` 之前中断。
+        let response = "HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\n{}"
+            .parse::<ureq::Response>()
+            .unwrap();
+        assert!(matches!(
+            response.read_string("test"),
+            Err(Error::ResponseReadError {
+                endpoint: "test",
+                ..
+            })
+        ));
+    }
 }
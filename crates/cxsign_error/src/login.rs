@@ -0,0 +1,81 @@
+/// 服务器拒绝登录的原因，由登录接口返回的提示信息判断，均保留了原始的提示信息。
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LoginOutcome {
+    /// 用户名或密码错误，重试没有意义。
+    #[error("用户名或密码错误：{0}")]
+    WrongPassword(String),
+    /// 账号已被锁定或冻结。
+    #[error("账号已被锁定：{0}")]
+    AccountLocked(String),
+    /// 需要验证码或其他验证。
+    #[error("需要进行验证：{0}")]
+    VerificationRequired(String),
+    /// 账号禁止在其他设备上登录。
+    #[error("禁止在其他设备登录：{0}")]
+    OtherDeviceForbidden(String),
+    /// 无法识别的原因。
+    #[error("{0}")]
+    Unknown(String),
+}
+
+impl LoginOutcome {
+    /// 根据提示信息判断原因。
+    pub fn from_message(message: &str) -> Self {
+        let message = message.to_owned();
+        let contains_any = |keywords: &[&str]| keywords.iter().any(|k| message.contains(k));
+        // 密码错误次数过多导致的锁定也会提到密码，因此先判断锁定。
+        if contains_any(&["锁定", "冻结", "封禁", "禁用"]) {
+            LoginOutcome::AccountLocked(message)
+        } else if contains_any(&["其他设备", "其它设备", "异地", "其他终端"]) {
+            LoginOutcome::OtherDeviceForbidden(message)
+        } else if contains_any(&["验证码", "验证", "滑块"]) {
+            LoginOutcome::VerificationRequired(message)
+        } else if contains_any(&["密码错误", "密码不正确", "用户名或密码", "账号或密码"])
+        {
+            LoginOutcome::WrongPassword(message)
+        } else {
+            LoginOutcome::Unknown(message)
+        }
+    }
+    /// 原始的提示信息。
+    pub fn message(&self) -> &str {
+        match self {
+            LoginOutcome::WrongPassword(message)
+            | LoginOutcome::AccountLocked(message)
+            | LoginOutcome::VerificationRequired(message)
+            | LoginOutcome::OtherDeviceForbidden(message)
+            | LoginOutcome::Unknown(message) => message,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::LoginOutcome;
+
+    type IsExpected = fn(&LoginOutcome) -> bool;
+
+    #[test]
+    fn classify_login_messages() {
+        let cases: [(&str, IsExpected); 5] = [
+            ("用户名或密码错误", |o| {
+                matches!(o, LoginOutcome::WrongPassword(_))
+            }),
+            ("密码错误次数过多，账号已被锁定", |o| {
+                matches!(o, LoginOutcome::AccountLocked(_))
+            }),
+            ("请输入验证码", |o| {
+                matches!(o, LoginOutcome::VerificationRequired(_))
+            }),
+            ("该账号禁止在其他设备登录", |o| {
+                matches!(o, LoginOutcome::OtherDeviceForbidden(_))
+            }),
+            ("系统繁忙", |o| matches!(o, LoginOutcome::Unknown(_))),
+        ];
+        for (message, is_expected) in cases {
+            let outcome = LoginOutcome::from_message(message);
+            assert!(is_expected(&outcome), "{message}: {outcome:?}");
+            assert_eq!(outcome.message(), message);
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use crate::Error;
+use serde::de::DeserializeOwned;
+
+/// 读取响应内容的辅助特型，读取或解析失败时返回 [`Error`] 而不是 panic.
+pub trait ResponseExt {
+    /// 以字符串形式读取响应内容。读取过程中的 I/O 错误属于网络错误，返回 [`Error::ResponseReadError`].
+    fn read_string(self, endpoint: &'static str) -> Result<String, Error>;
+    /// 将响应内容解析为 JSON. 解析失败时返回 [`Error::UnexpectedResponseError`].
+    fn read_json<T: DeserializeOwned>(self, endpoint: &'static str) -> Result<T, Error>;
+}
+
+impl ResponseExt for ureq::Response {
+    fn read_string(self, endpoint: &'static str) -> Result<String, Error> {
+        self.into_string()
+            .map_err(|source| Error::ResponseReadError { endpoint, source })
+    }
+
+    fn read_json<T: DeserializeOwned>(self, endpoint: &'static str) -> Result<T, Error> {
+        let text = self.read_string(endpoint)?;
+        parse_json(endpoint, &text)
+    }
+}
+
+/// 将文本解析为 JSON. 解析失败时返回 [`Error::UnexpectedResponseError`].
+pub fn parse_json<T: DeserializeOwned>(endpoint: &'static str, text: &str) -> Result<T, Error> {
+    serde_json::from_str(text).map_err(|_| Error::unexpected_response(endpoint, text))
+}
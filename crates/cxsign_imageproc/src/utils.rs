@@ -1,3 +1,4 @@
+use cxsign_transport::{Transport, TransportExt};
 use image::{
     DynamicImage, GenericImage, GrayImage, ImageBuffer, Luma, LumaA, Pixel, Primitive, Rgba,
 };
@@ -93,21 +94,19 @@ where
 }
 
 pub fn download_image(
-    agent: &ureq::Agent,
+    agent: &dyn Transport,
     image_url: &str,
-) -> Result<DynamicImage, Box<ureq::Error>> {
+) -> Result<DynamicImage, cxsign_error::Error> {
     let mut v = Vec::new();
     agent
         .get(image_url)
         .call()?
         .into_reader()
-        .read_to_end(&mut v)
-        .unwrap();
+        .read_to_end(&mut v)?;
     let img = image::ImageReader::new(std::io::Cursor::new(v))
-        .with_guessed_format()
-        .unwrap()
+        .with_guessed_format()?
         .decode()
-        .unwrap();
+        .map_err(|e| cxsign_error::Error::ParseError(format!("无法解码图片：{e}.")))?;
     Ok(img)
 }
 
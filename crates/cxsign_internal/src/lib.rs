@@ -2,12 +2,18 @@ use cxsign_activity::sign;
 pub use cxsign_activity::{Activity, OtherActivity};
 pub use cxsign_error::*;
 use cxsign_signner as signner;
-pub use cxsign_store::UnameAndEncPwdPair;
+pub use cxsign_store::{SessionsReport, UnameAndEncPwdPair};
 pub use cxsign_types::{
-    Course, Location, LocationAndAliasesPair, LocationPreprocessorTrait, LocationWithRange, Photo,
+    Course, CoursesReport, Location, LocationAndAliasesPair, LocationPreprocessorTrait,
+    LocationWithRange, Photo,
+};
+pub use cxsign_user::{
+    from_netscape, inspect_cookies, session_expires_at, to_netscape, CookieInfo, ReloginError,
+    Session, SessionConfig, SessionRecord, SessionStorage, UserCookies,
 };
-pub use cxsign_user::{Session, UserCookies};
 pub use sign::*;
+#[cfg(feature = "async")]
+pub use signner::AsyncSignnerTrait;
 pub use signner::{
     DefaultGestureOrSigncodeSignner, DefaultLocationSignner, DefaultNormalOrRawSignner,
     DefaultPhotoSignner, DefaultQrCodeSignner, SignnerTrait,
@@ -23,18 +29,30 @@ pub mod protocol {
 }
 
 pub mod store {
-    pub use cxsign_store::{DataBase, DataBaseTableTrait};
+    pub use cxsign_store::crypto;
+    pub use cxsign_store::{
+        Backup, BackupManifest, DataBase, DataBaseTableTrait, DataFormat, RestoreDiff, RestoreMode,
+        TableDiff,
+    };
     pub mod tables {
-        pub use cxsign_store::{AccountTable, AliasTable, ExcludeTable};
+        pub use cxsign_store::{
+            AccountTable, AliasTable, ExcludeTable, SessionConfigTable, SessionTable,
+        };
         pub use cxsign_types::LocationTable;
     }
 }
+pub mod transport {
+    pub use cxsign_transport::*;
+}
 pub mod utils {
     pub use crate::signner::utils::*;
     pub use cxsign_captcha::utils::*;
     pub use cxsign_dir::*;
     pub use cxsign_imageproc::*;
-    pub use cxsign_login::{des_enc, load_json, login_enc};
+    pub use cxsign_login::{
+        build_agent, des_enc, load_json, login_enc, login_qr, login_sms, search_institutions,
+        Institution, QrLoginEvent,
+    };
     pub use cxsign_types::{
         do_location_preprocessor, set_boxed_location_preprocessor, set_location_preprocessor,
     };
@@ -0,0 +1,82 @@
+use crate::{build_agent_with, login_enc_with, protocol, LoginR, SessionConfig};
+use cookie_store::CookieStore;
+use cxsign_error::{Error, ResponseExt};
+use cxsign_transport::Transport;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use ureq::Agent;
+
+/// 机构（学校），使用机构账号（学号）登录时需要指定。
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Institution {
+    pub fid: i64,
+    pub name: String,
+}
+
+impl Display for Institution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}（fid: {}）", self.name, self.fid)
+    }
+}
+
+/// 按名称搜索机构。
+pub fn search_institutions(
+    transport: &dyn Transport,
+    keyword: &str,
+) -> Result<Vec<Institution>, Error> {
+    #[derive(Deserialize)]
+    struct SearchR {
+        #[serde(default)]
+        froms: Vec<InstitutionR>,
+    }
+    #[derive(Deserialize)]
+    struct InstitutionR {
+        id: i64,
+        name: String,
+    }
+    let r: SearchR = protocol::search_institutions(transport, keyword)?.read_json("searchUnis")?;
+    Ok(r.froms
+        .into_iter()
+        .map(|i| Institution {
+            fid: i.id,
+            name: i.name,
+        })
+        .collect())
+}
+
+/// 使用机构账号（学号）登录，参见 [`login_unit_with`].
+pub fn login_unit_with_config(
+    fid: i64,
+    stu_number: &str,
+    enc_passwd: &str,
+    config: &SessionConfig,
+) -> Result<Agent, Error> {
+    let client = build_agent_with(CookieStore::new(None), config)?;
+    login_unit_with(&client, fid, stu_number, enc_passwd)?;
+    Ok(client)
+}
+
+/// 通过给定的 [`Transport`] 使用机构 `fid` 下的学号登录，登录所得的 Cookies 保存在该 [`Transport`] 中。
+pub fn login_unit_with(
+    transport: &dyn Transport,
+    fid: i64,
+    stu_number: &str,
+    enc_passwd: &str,
+) -> Result<(), Error> {
+    protocol::login_unit(transport, fid, stu_number, enc_passwd)?
+        .read_json::<LoginR>("unitlogin")?
+        .into_result(stu_number)
+}
+
+/// 登录。指定了机构 `fid` 时使用机构账号（学号）登录，否则使用手机号登录。
+pub fn login_account_with(
+    transport: &dyn Transport,
+    fid: Option<i64>,
+    account: &str,
+    enc_passwd: &str,
+) -> Result<(), Error> {
+    match fid {
+        Some(fid) => login_unit_with(transport, fid, account, enc_passwd),
+        None => login_enc_with(transport, account, enc_passwd),
+    }
+}
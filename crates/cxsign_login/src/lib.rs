@@ -1,65 +1,125 @@
-use cxsign_error::Error;
+use cookie_store::CookieStore;
+pub use cxsign_error::LoginOutcome;
+use cxsign_error::{Error, ResponseExt};
+use cxsign_transport::Transport;
 use log::warn;
 use std::path::Path;
 use ureq::{Agent, AgentBuilder};
 
+mod config;
+mod institution;
 pub mod protocol;
+mod qrcode;
+mod sms;
 mod utils;
 
+pub use config::*;
+pub use institution::*;
+pub use qrcode::*;
+pub use sms::*;
 pub use utils::*;
 static UA: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 16_0_3 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Mobile/15E148 (schild:eaf4fb193ec970c0a9775e2a27b0232b) (device:iPhone11,2) Language/zh-Hans com.ssreader.ChaoXingStudy/ChaoXingStudy_3_6.0.2_ios_phone_202209281930_99 (@Kalimdor)_1665876591620212942";
 
-pub fn login_enc(account: &str, enc_passwd: &str) -> Result<Agent, Error> {
-    let cookie_store = cookie_store::CookieStore::new(None);
-    let client = AgentBuilder::new()
+/// 使用给定的 Cookies 和默认的 [`SessionConfig`] 构造 [`Agent`].
+pub fn build_agent(cookie_store: CookieStore) -> Result<Agent, Error> {
+    build_agent_with(cookie_store, &SessionConfig::default())
+}
+
+/// 使用给定的 Cookies 和 [`SessionConfig`] 构造 [`Agent`].
+pub fn build_agent_with(cookie_store: CookieStore, config: &SessionConfig) -> Result<Agent, Error> {
+    let builder = AgentBuilder::new()
         .user_agent(UA)
-        .cookie_store(cookie_store)
-        .build();
-    let response = protocol::login_enc(&client, account, enc_passwd)?;
-    /// TODO: 存疑
-    #[derive(serde::Deserialize)]
-    struct LoginR {
-        url: Option<String>,
-        msg1: Option<String>,
-        msg2: Option<String>,
-        status: bool,
-    }
-    let LoginR {
-        status,
-        url,
-        msg1,
-        msg2,
-    } = response.into_json().unwrap();
-    let mut mes = Vec::new();
-    if let Some(url) = url {
-        mes.push(url);
-    }
-    if let Some(msg1) = msg1 {
-        mes.push(msg1);
-    }
-    if let Some(msg2) = msg2 {
-        mes.push(msg2);
-    }
-    if !status {
-        for mes in &mes {
-            warn!("{mes:?}");
+        .cookie_store(cookie_store);
+    Ok(config.apply(builder)?.build())
+}
+
+pub fn login_enc(account: &str, enc_passwd: &str) -> Result<Agent, Error> {
+    login_enc_with_config(account, enc_passwd, &SessionConfig::default())
+}
+
+pub fn login_enc_with_config(
+    account: &str,
+    enc_passwd: &str,
+    config: &SessionConfig,
+) -> Result<Agent, Error> {
+    let client = build_agent_with(CookieStore::new(None), config)?;
+    login_enc_with(&client, account, enc_passwd)?;
+    Ok(client)
+}
+
+/// 通过给定的 [`Transport`] 登录，登录所得的 Cookies 保存在该 [`Transport`] 中。
+///
+/// 服务器拒绝登录时返回 [`Error::LoginRejected`], 其中的 [`LoginOutcome`] 给出了原因。
+pub fn login_enc_with(
+    transport: &dyn Transport,
+    account: &str,
+    enc_passwd: &str,
+) -> Result<(), Error> {
+    login_enc_with_validate(transport, account, enc_passwd, None, "")
+}
+
+/// 同 [`login_enc_with`], 但附带账号所属机构的 `fid` 和通过验证码后得到的验证信息 `validate`,
+/// 后者用于登录失败且原因为 [`LoginOutcome::VerificationRequired`] 之后的重试。
+pub fn login_enc_with_validate(
+    transport: &dyn Transport,
+    account: &str,
+    enc_passwd: &str,
+    fid: Option<i64>,
+    validate: &str,
+) -> Result<(), Error> {
+    let response = protocol::login_enc(transport, account, enc_passwd, fid, validate)?;
+    response
+        .read_json::<LoginR>("fanyalogin")?
+        .into_result(account)
+}
+
+/// 各登录接口的响应。
+#[derive(serde::Deserialize)]
+pub(crate) struct LoginR {
+    msg1: Option<String>,
+    msg2: Option<String>,
+    mes: Option<String>,
+    status: bool,
+}
+
+impl LoginR {
+    /// 登录失败时返回 [`Error::LoginRejected`].
+    pub(crate) fn into_result(self, account: &str) -> Result<(), Error> {
+        let LoginR {
+            msg1,
+            msg2,
+            mes,
+            status,
+        } = self;
+        if !status {
+            let message = [msg1, msg2, mes]
+                .into_iter()
+                .flatten()
+                .filter(|m| !m.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            let outcome = LoginOutcome::from_message(&message);
+            warn!("用户[{account}]登录失败：{outcome}.");
+            return Err(Error::LoginRejected(outcome));
         }
-        return Err(Error::LoginError(format!("{mes:?}")));
+        Ok(())
     }
-    Ok(client)
 }
 
-pub fn load_json<P: AsRef<Path>>(cookies_file: P) -> Agent {
+pub fn load_json<P: AsRef<Path>>(cookies_file: P) -> Result<Agent, Error> {
+    load_json_with_config(cookies_file, &SessionConfig::default())
+}
+
+pub fn load_json_with_config<P: AsRef<Path>>(
+    cookies_file: P,
+    config: &SessionConfig,
+) -> Result<Agent, Error> {
     let cookie_store = {
-        let file = std::fs::File::open(cookies_file)
-            .map(std::io::BufReader::new)
-            .unwrap();
-        cookie_store::CookieStore::load_json(file).unwrap()
+        let file = std::fs::File::open(cookies_file).map(std::io::BufReader::new)?;
+        CookieStore::load_json(file)
+            .map_err(|e| Error::ParseError(format!("Cookies 解析失败：{e}.")))?
     };
-    AgentBuilder::new()
-        .user_agent(UA)
-        .cookie_store(cookie_store)
-        .build()
+    build_agent_with(cookie_store, config)
 }
 
 #[cfg(test)]
@@ -1,20 +1,150 @@
-use ureq::{Agent, Response};
+use cxsign_transport::endpoint::{Endpoint, Host};
+use cxsign_transport::{retry, Transport, TransportExt};
+use ureq::Response;
 
 // 登录页
-static LOGIN_PAGE: &str =
-    "https://passport2.chaoxing.com/mlogin?fid=&newversion=true&refer=http%3A%2F%2Fi.chaoxing.com";
+static LOGIN_PAGE: Endpoint = Endpoint::new(
+    Host::Passport2,
+    "/mlogin?fid=&newversion=true&refer=http%3A%2F%2Fi.chaoxing.com",
+);
 
-pub fn login_page(client: &Agent) -> Result<Response, Box<ureq::Error>> {
-    Ok(client.get(LOGIN_PAGE).call()?)
+pub fn login_page(client: &dyn Transport) -> Result<Response, cxsign_error::Error> {
+    client.get(&LOGIN_PAGE.url()).call()
 }
 
 // 非明文密码登录
-static LOGIN_ENC: &str = "https://passport2.chaoxing.com/fanyalogin";
+static LOGIN_ENC: Endpoint = Endpoint::new(Host::Passport2, "/fanyalogin");
 
-pub fn login_enc(client: &Agent, uname: &str, pwd_enc: &str) -> Result<Response, Box<ureq::Error>> {
-    Ok(client
-        .post(LOGIN_ENC)
+/// 请求参数中的 fid, 未指定机构时为 `-1`.
+fn fid_param(fid: Option<i64>) -> i64 {
+    fid.unwrap_or(-1)
+}
+
+/// `validate` 为通过验证码后得到的验证信息，不需要验证时为空。
+pub fn login_enc(
+    client: &dyn Transport,
+    uname: &str,
+    pwd_enc: &str,
+    fid: Option<i64>,
+    validate: &str,
+) -> Result<Response, cxsign_error::Error> {
+    client
+        .post(&LOGIN_ENC.url())
+        .set("Content-Type", "application/x-www-form-urlencoded")
+        .set("X-Requested-With", "XMLHttpRequest")
+        .send_string(&format!("uname={uname}&password={pwd_enc}&fid={}&t=true&refer=https%253A%252F%252Fi.chaoxing.com&forbidotherlogin=0&validate={}", fid_param(fid), percent_encoding::utf8_percent_encode(validate, percent_encoding::NON_ALPHANUMERIC)))
+}
+
+// 发送短信验证码
+static SEND_SMS_CODE: Endpoint = Endpoint::new(Host::Passport2, "/num/phonecode");
+
+pub fn send_sms_code(client: &dyn Transport, phone: &str) -> Result<Response, cxsign_error::Error> {
+    client
+        .post(&SEND_SMS_CODE.url())
+        .set("Content-Type", "application/x-www-form-urlencoded")
+        .set("X-Requested-With", "XMLHttpRequest")
+        .send_string(&format!("phone={phone}&needcode=false"))
+}
+
+// 短信验证码登录
+static LOGIN_SMS: Endpoint = Endpoint::new(Host::Passport2, "/num/login");
+
+pub fn login_sms(
+    client: &dyn Transport,
+    phone: &str,
+    fid: Option<i64>,
+    code: &str,
+) -> Result<Response, cxsign_error::Error> {
+    client
+        .post(&LOGIN_SMS.url())
+        .set("Content-Type", "application/x-www-form-urlencoded")
+        .set("X-Requested-With", "XMLHttpRequest")
+        .send_string(&format!(
+            "phone={phone}&code={}&fid={}&refer=https%253A%252F%252Fi.chaoxing.com",
+            percent_encoding::utf8_percent_encode(code, percent_encoding::NON_ALPHANUMERIC),
+            fid_param(fid)
+        ))
+}
+
+// 二维码登录页，其中包含二维码的 `uuid` 和 `enc`
+static QR_LOGIN_PAGE: Endpoint = Endpoint::new(
+    Host::Passport2,
+    "/login?newversion=true&refer=http%3A%2F%2Fi.chaoxing.com",
+);
+
+pub fn qr_login_page(client: &dyn Transport) -> Result<Response, cxsign_error::Error> {
+    client.get(&QR_LOGIN_PAGE.url()).call()
+}
+
+// 登录二维码图片
+static QR_IMAGE: Endpoint = Endpoint::new(Host::Passport2, "/createqr");
+
+pub fn qr_image_url(uuid: &str, fid: Option<i64>) -> String {
+    format!("{QR_IMAGE}?uuid={uuid}&fid={}", fid_param(fid))
+}
+
+// 二维码扫描后，手机端打开的地址，即二维码的内容
+static QR_AUTH: Endpoint = Endpoint::new(Host::Passport2, "/toauthlogin");
+
+pub fn qr_content(uuid: &str, enc: &str) -> String {
+    format!("{QR_AUTH}?uuid={uuid}&enc={enc}&xxtrefer=&clientid=&type=0&mobiletip=")
+}
+
+// 查询二维码的扫描状态
+static QR_AUTH_STATUS: Endpoint = Endpoint::new(Host::Passport2, "/getauthstatus").idempotent();
+
+pub fn qr_auth_status(
+    client: &dyn Transport,
+    uuid: &str,
+    enc: &str,
+) -> Result<Response, cxsign_error::Error> {
+    retry::call(&QR_AUTH_STATUS, || {
+        client
+            .post(&QR_AUTH_STATUS.url())
+            .endpoint(&QR_AUTH_STATUS)
+            .set("Content-Type", "application/x-www-form-urlencoded")
+            .set("X-Requested-With", "XMLHttpRequest")
+            .send_string(&format!("enc={enc}&uuid={uuid}"))
+    })
+}
+
+// 搜索机构
+static SEARCH_INSTITUTIONS: Endpoint =
+    Endpoint::new(Host::Passport2, "/org/searchUnis").idempotent();
+
+pub fn search_institutions(
+    client: &dyn Transport,
+    keyword: &str,
+) -> Result<Response, cxsign_error::Error> {
+    let body = format!(
+        "filter={}&product=44&type=",
+        percent_encoding::utf8_percent_encode(keyword, percent_encoding::NON_ALPHANUMERIC)
+    );
+    retry::call(&SEARCH_INSTITUTIONS, || {
+        client
+            .post(&SEARCH_INSTITUTIONS.url())
+            .endpoint(&SEARCH_INSTITUTIONS)
+            .set("Content-Type", "application/x-www-form-urlencoded")
+            .set("X-Requested-With", "XMLHttpRequest")
+            .send_string(&body)
+    })
+}
+
+// 机构账号（学号）登录
+static LOGIN_UNIT: Endpoint = Endpoint::new(Host::Passport2, "/unitlogin");
+
+pub fn login_unit(
+    client: &dyn Transport,
+    fid: i64,
+    stu_number: &str,
+    pwd_enc: &str,
+) -> Result<Response, cxsign_error::Error> {
+    client
+        .post(&LOGIN_UNIT.url())
         .set("Content-Type", "application/x-www-form-urlencoded")
         .set("X-Requested-With", "XMLHttpRequest")
-        .send_string(&format!("uname={uname}&password={pwd_enc}&fid=-1&t=true&refer=https%253A%252F%252Fi.chaoxing.com&forbidotherlogin=0&validate="))?)
+        .send_string(&format!(
+            "fid={fid}&uname={}&numcode=&password={pwd_enc}&refer=https%253A%252F%252Fi.chaoxing.com&t=true&hidecompletephone=0&doubleFactorLogin=0&independentId=0",
+            percent_encoding::utf8_percent_encode(stu_number, percent_encoding::NON_ALPHANUMERIC)
+        ))
 }
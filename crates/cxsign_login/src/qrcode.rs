@@ -0,0 +1,156 @@
+use crate::{build_agent_with, protocol, SessionConfig};
+use cookie_store::CookieStore;
+use cxsign_error::{Error, LoginOutcome, ResponseExt};
+use cxsign_transport::Transport;
+use log::{debug, info};
+use std::ops::ControlFlow;
+use std::time::Duration;
+use ureq::Agent;
+
+/// 默认的扫描状态查询间隔。
+pub const QR_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 二维码登录过程中的事件，参见 [`login_qr_with`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QrLoginEvent {
+    /// 获取到了新的二维码，需要展示给用户。
+    ///
+    /// `content` 为二维码的内容，可以自行生成二维码；`image_url` 为服务器生成的二维码图片的地址。
+    Show { content: String, image_url: String },
+    /// 尚未扫描，每次查询后触发，可用于实现超时。
+    Waiting,
+    /// 已扫描，等待用户在手机上确认。
+    Scanned { nickname: String },
+    /// 二维码已失效。回调返回 [`ControlFlow::Continue`] 时会获取新的二维码。
+    Expired,
+}
+
+/// 二维码的扫描状态。
+enum QrStatus {
+    Confirmed,
+    Waiting,
+    Scanned(String),
+    Expired,
+}
+
+/// 使用二维码登录，参见 [`login_qr_with`].
+pub fn login_qr(
+    fid: Option<i64>,
+    config: &SessionConfig,
+    on_event: &mut dyn FnMut(QrLoginEvent) -> ControlFlow<()>,
+) -> Result<Agent, Error> {
+    let client = build_agent_with(CookieStore::new(None), config)?;
+    login_qr_with(&client, fid, QR_POLL_INTERVAL, on_event)?;
+    Ok(client)
+}
+
+/// 通过给定的 [`Transport`] 使用二维码登录，登录所得的 Cookies 保存在该 [`Transport`] 中。
+///
+/// 获取二维码后每隔 `poll_interval` 查询一次扫描状态，直到用户在手机上确认登录。
+/// 过程中的每个事件都会通过 `on_event` 通知调用者，回调返回 [`ControlFlow::Break`] 时登录中止。
+/// `fid` 为账号所属机构的 fid, 未知时为 `None`.
+pub fn login_qr_with(
+    transport: &dyn Transport,
+    fid: Option<i64>,
+    poll_interval: Duration,
+    on_event: &mut dyn FnMut(QrLoginEvent) -> ControlFlow<()>,
+) -> Result<(), Error> {
+    let cancelled = || Error::Cancelled("二维码登录".to_owned());
+    loop {
+        let (uuid, enc) = get_qr_code(transport)?;
+        let event = QrLoginEvent::Show {
+            content: protocol::qr_content(&uuid, &enc),
+            image_url: protocol::qr_image_url(&uuid, fid),
+        };
+        if on_event(event).is_break() {
+            return Err(cancelled());
+        }
+        loop {
+            std::thread::sleep(poll_interval);
+            let event = match get_qr_status(transport, &uuid, &enc)? {
+                QrStatus::Confirmed => {
+                    info!("二维码登录成功。");
+                    return Ok(());
+                }
+                QrStatus::Waiting => QrLoginEvent::Waiting,
+                QrStatus::Scanned(nickname) => QrLoginEvent::Scanned { nickname },
+                QrStatus::Expired => QrLoginEvent::Expired,
+            };
+            let expired = event == QrLoginEvent::Expired;
+            if on_event(event).is_break() {
+                return Err(cancelled());
+            }
+            if expired {
+                break;
+            }
+        }
+    }
+}
+
+/// 从登录页中获取二维码的 `uuid` 和 `enc`.
+fn get_qr_code(transport: &dyn Transport) -> Result<(String, String), Error> {
+    let html = protocol::qr_login_page(transport)?.read_string("login")?;
+    match (
+        find_input_value(&html, "uuid"),
+        find_input_value(&html, "enc"),
+    ) {
+        (Some(uuid), Some(enc)) => Ok((uuid.to_owned(), enc.to_owned())),
+        _ => Err(Error::unexpected_response("login", &html)),
+    }
+}
+
+fn get_qr_status(transport: &dyn Transport, uuid: &str, enc: &str) -> Result<QrStatus, Error> {
+    #[derive(serde::Deserialize)]
+    struct StatusR {
+        status: bool,
+        #[serde(rename = "type")]
+        type_: Option<String>,
+        nickname: Option<String>,
+        mes: Option<String>,
+    }
+    let StatusR {
+        status,
+        type_,
+        nickname,
+        mes,
+    } = protocol::qr_auth_status(transport, uuid, enc)?.read_json("getauthstatus")?;
+    debug!("二维码状态：{type_:?}, {mes:?}.");
+    if status {
+        return Ok(QrStatus::Confirmed);
+    }
+    Ok(match type_.as_deref() {
+        Some("3") => QrStatus::Waiting,
+        Some("4") => QrStatus::Scanned(nickname.unwrap_or_default()),
+        Some("1") | Some("2") | Some("6") => QrStatus::Expired,
+        _ => {
+            return Err(Error::LoginRejected(LoginOutcome::from_message(
+                &mes.unwrap_or_default(),
+            )))
+        }
+    })
+}
+
+/// 获取 `id` 为 `id` 的 `<input>` 元素的 `value`.
+fn find_input_value<'a>(html: &'a str, id: &str) -> Option<&'a str> {
+    let start = html.find(&format!("id=\"{id}\""))?;
+    let tag_end = start + html[start..].find('>')?;
+    let tag = &html[..tag_end];
+    let tag_start = tag.rfind('<')?;
+    let tag = &html[tag_start..tag_end];
+    let value = tag.find("value=\"")? + "value=\"".len();
+    let len = tag[value..].find('"')?;
+    Some(&html[tag_start + value..tag_start + value + len])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::qrcode::find_input_value;
+
+    #[test]
+    fn find_qr_input_values() {
+        let html = r#"<input type="hidden" value="abc-123" id="uuid"/><input id="enc" value="E"/>"#;
+        assert_eq!(find_input_value(html, "uuid"), Some("abc-123"));
+        assert_eq!(find_input_value(html, "enc"), Some("E"));
+        assert_eq!(find_input_value(html, "fid"), None);
+    }
+}
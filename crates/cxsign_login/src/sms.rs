@@ -0,0 +1,47 @@
+use crate::{build_agent_with, protocol, LoginR, SessionConfig};
+use cookie_store::CookieStore;
+use cxsign_error::{Error, LoginOutcome, ResponseExt};
+use cxsign_transport::Transport;
+use log::info;
+use ureq::Agent;
+
+/// 使用短信验证码登录，参见 [`login_sms_with`].
+pub fn login_sms(
+    phone: &str,
+    fid: Option<i64>,
+    config: &SessionConfig,
+    code: &mut dyn FnMut() -> Result<String, Error>,
+) -> Result<Agent, Error> {
+    let client = build_agent_with(CookieStore::new(None), config)?;
+    login_sms_with(&client, phone, fid, code)?;
+    Ok(client)
+}
+
+/// 通过给定的 [`Transport`] 使用短信验证码登录，登录所得的 Cookies 保存在该 [`Transport`] 中。
+///
+/// 验证码发送成功后调用 `code` 获取用户收到的验证码，CLI 中可以提示用户输入，服务中则可以等待其他途径提交。
+/// `code` 返回错误时登录中止。`fid` 为账号所属机构的 fid, 未知时为 `None`.
+pub fn login_sms_with(
+    transport: &dyn Transport,
+    phone: &str,
+    fid: Option<i64>,
+    code: &mut dyn FnMut() -> Result<String, Error>,
+) -> Result<(), Error> {
+    #[derive(serde::Deserialize)]
+    struct SendR {
+        status: bool,
+        mes: Option<String>,
+    }
+    let SendR { status, mes } =
+        protocol::send_sms_code(transport, phone)?.read_json("phonecode")?;
+    if !status {
+        return Err(Error::LoginRejected(LoginOutcome::from_message(
+            &mes.unwrap_or_default(),
+        )));
+    }
+    info!("验证码已发送至[{phone}].");
+    let code = code()?;
+    protocol::login_sms(transport, phone, fid, code.trim())?
+        .read_json::<LoginR>("num/login")?
+        .into_result(phone)
+}
@@ -1,43 +1,61 @@
+use cxsign_error::Error;
 use des::{
     cipher::{generic_array::GenericArray, BlockEncrypt as _, KeyInit as _},
     Des,
 };
 
-pub fn des_enc(text: &str) -> String {
-    fn pkcs7(text: &str) -> Vec<[u8; 8]> {
-        assert!(text.len() > 7);
-        assert!(text.len() < 17);
-        let mut r = Vec::new();
-        let pwd = text.as_bytes();
-        let len = pwd.len();
-        let batch = len / 8;
-        let m = len % 8;
-        for i in 0..batch {
-            let mut a = [0u8; 8];
-            a.copy_from_slice(&pwd[i * 8..8 + i * 8]);
-            r.push(a);
-        }
-        let mut b = [0u8; 8];
-        for i in 0..m {
-            b[i] = pwd[8 * batch + i];
-        }
-        for item in b.iter_mut().skip(m) {
-            *item = (8 - m) as u8;
-        }
-        r.push(b);
-        // #[cfg(debug_assertions)]
-        // debug!("{r:?}");
+/// 登录时对密码的编码：DES-ECB, PKCS#7 填充，结果为十六进制字符串。
+///
+/// 密码按 UTF-8 字节编码，长度不限，密码为空时返回错误。
+pub fn des_enc(text: &str) -> Result<String, Error> {
+    /// 按 8 字节分块并进行 PKCS#7 填充。长度恰为 8 的倍数时，末尾补一整块 `8`.
+    fn pkcs7(bytes: &[u8]) -> Vec<[u8; 8]> {
+        let mut r = bytes
+            .chunks_exact(8)
+            .map(|chunk| <[u8; 8]>::try_from(chunk).unwrap())
+            .collect::<Vec<_>>();
+        let rest = bytes.chunks_exact(8).remainder();
+        let mut last = [(8 - rest.len()) as u8; 8];
+        last[..rest.len()].copy_from_slice(rest);
+        r.push(last);
         r
     }
+    if text.is_empty() {
+        return Err(Error::MissingPassword);
+    }
     let key = b"u2oh6Vu^".to_owned();
     let key = GenericArray::from(key);
     let des = Des::new(&key);
     let mut data_block_enc = Vec::new();
-    for block in pkcs7(text) {
+    for block in pkcs7(text.as_bytes()) {
         let mut block = GenericArray::from(block);
         des.encrypt_block(&mut block);
-        let mut block = block.to_vec();
-        data_block_enc.append(&mut block);
+        data_block_enc.extend_from_slice(&block);
+    }
+    Ok(hex::encode(data_block_enc))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::des_enc;
+    use cxsign_error::Error;
+
+    #[test]
+    fn des_enc_known_answers() {
+        // 由 `openssl enc -des-ecb -K 75326f683656755e -nosalt` 计算。
+        let cases = [
+            ("a", "9524e6bb60f41487"),
+            ("12345678", "b263c7c0590ad18df8073c058dc600ab"),
+            ("password123", "9a97671096b3b45677039b75d66be6dc"),
+            ("密码passwd", "51589bcab70f1ea96424e7ac4bf0b34f"),
+            (
+                "abcdefghijklmnopqrstuvwxyz0123456789",
+                "8b8f399a8b129aefb7f07d497dce198be1ace7adf058af9322585885a9d771be8210d23cee352ae6",
+            ),
+        ];
+        for (text, enc) in cases {
+            assert_eq!(des_enc(text).unwrap(), enc, "{text}");
+        }
+        assert!(matches!(des_enc(""), Err(Error::MissingPassword)));
     }
-    hex::encode(data_block_enc)
 }
@@ -0,0 +1,112 @@
+//! 滑块验证。
+//!
+//! 背景图为确定性的噪声纹理，滑块为背景图中 [`CUT_X`] 处的一块矩形，四周透明，
+//! 因此 `cxsign_imageproc::find_sub_image` 能够准确地找到缺口位置。
+
+use crate::handler::Context;
+use crate::{MockResponse, State};
+use image::{DynamicImage, ImageFormat, Rgb, RgbImage, Rgba, RgbaImage};
+use serde_json::{json, Value};
+use std::io::Cursor;
+
+const WIDTH: u32 = 160;
+const HEIGHT: u32 = 50;
+const CUT_WIDTH: u32 = 30;
+/// 滑块图片中透明边框的宽度。
+const MARGIN: u32 = 5;
+/// 缺口在背景图中的横坐标。
+const CUT_X: u32 = 97;
+/// 允许的误差。
+const TOLERANCE: f64 = 4.0;
+
+fn jsonp(ctx: &Context, value: &Value) -> MockResponse {
+    let callback = ctx.param("callback");
+    let callback = if callback.is_empty() {
+        "callback"
+    } else {
+        callback
+    };
+    MockResponse::ok(&format!("{callback}({value})"))
+}
+
+pub(crate) fn conf(ctx: &Context) -> MockResponse {
+    let t = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    jsonp(ctx, &json!({"t": t, "captchaId": ctx.param("captchaId")}))
+}
+
+pub(crate) fn image(state: &mut State, ctx: &Context) -> MockResponse {
+    let token = format!("mock-captcha-token-{}", state.next_serial());
+    state.captcha_tokens.insert(token.clone());
+    let origin = ctx.url.origin().ascii_serialization();
+    jsonp(
+        ctx,
+        &json!({
+            "token": token,
+            "imageVerificationVo": {
+                "shadeImage": format!("{origin}/mock/shade.png"),
+                "cutoutImage": format!("{origin}/mock/cutout.png"),
+            },
+        }),
+    )
+}
+
+pub(crate) fn check(state: &mut State, ctx: &Context) -> MockResponse {
+    let x = serde_json::from_str::<Value>(ctx.param("textClickArr"))
+        .ok()
+        .and_then(|v| v[0]["x"].as_f64());
+    let passed = state.captcha_tokens.remove(ctx.param("token"))
+        && x.is_some_and(|x| (x - (CUT_X - MARGIN) as f64).abs() <= TOLERANCE);
+    if passed {
+        let validate = format!("validate_mock_{}", state.next_serial());
+        state.validates.insert(validate.clone());
+        let extra_data = json!({ "validate": validate }).to_string();
+        jsonp(
+            ctx,
+            &json!({"error": 0, "result": true, "extraData": extra_data}),
+        )
+    } else {
+        jsonp(
+            ctx,
+            &json!({"error": 0, "result": false, "extraData": null}),
+        )
+    }
+}
+
+/// 背景图中 `(x, y)` 处的灰度。
+fn noise(x: u32, y: u32) -> u8 {
+    let mut h = (x as u64) << 32 | y as u64;
+    h = h.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    h ^= h >> 29;
+    h = h.wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    h ^= h >> 32;
+    h as u8
+}
+
+fn png(image: DynamicImage) -> MockResponse {
+    let mut bytes = Vec::new();
+    match image.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png) {
+        Ok(()) => MockResponse::bytes("image/png", bytes),
+        Err(e) => MockResponse::new(500, &e.to_string()),
+    }
+}
+
+pub(crate) fn shade_image() -> MockResponse {
+    png(RgbImage::from_fn(WIDTH, HEIGHT, |x, y| Rgb([noise(x, y); 3])).into())
+}
+
+pub(crate) fn cutout_image() -> MockResponse {
+    let piece = MARGIN..MARGIN + CUT_WIDTH;
+    let rows = MARGIN..HEIGHT - MARGIN;
+    png(RgbaImage::from_fn(CUT_WIDTH + 2 * MARGIN, HEIGHT, |x, y| {
+        if piece.contains(&x) && rows.contains(&y) {
+            let v = noise(x - MARGIN + CUT_X, y);
+            Rgba([v, v, v, 255])
+        } else {
+            Rgba([0, 0, 0, 0])
+        }
+    })
+    .into())
+}
@@ -0,0 +1,463 @@
+use crate::{
+    captcha, MockAccount, MockActivity, MockQrLogin, MockResponse, MockSignKind, State,
+    MOCK_CAPTCHA_ID,
+};
+use cxsign_transport::{Body, Request};
+use serde_json::json;
+use std::collections::HashMap;
+use url::Url;
+
+/// 会话令牌所在的 Cookie.
+static SESSION_COOKIE: &str = "vc3";
+/// 登录所得 Cookies 的有效期（秒），与真实服务器一样为 30 天。
+pub(crate) static SESSION_MAX_AGE: i64 = 30 * 24 * 3600;
+/// 需要二次验证时随签到结果返回的 `enc2`.
+static ENC2: &str = "MOCKENC2";
+/// 预签到后 `analysis` 接口给出的 `code`.
+static ANALYSIS_CODE: &str = "MOCKCODE";
+
+/// 解析后的请求。
+pub(crate) struct Context {
+    pub(crate) url: Url,
+    pub(crate) params: HashMap<String, String>,
+    cookies: HashMap<String, String>,
+}
+
+impl Context {
+    fn new(request: &Request) -> Option<Self> {
+        let url = Url::parse(&request.url).ok()?;
+        let mut params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        if let Body::Text(text) = &request.body
+            && request
+                .header("Content-Type")
+                .is_some_and(|t| t.starts_with("application/x-www-form-urlencoded"))
+        {
+            params.extend(url::form_urlencoded::parse(text.as_bytes()).into_owned());
+        }
+        let cookies = request
+            .header("Cookie")
+            .map(|cookies| {
+                cookies
+                    .split(';')
+                    .filter_map(|c| c.trim().split_once('='))
+                    .map(|(name, value)| (name.to_owned(), value.to_owned()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Some(Self {
+            url,
+            params,
+            cookies,
+        })
+    }
+    pub(crate) fn param(&self, name: &str) -> &str {
+        self.params
+            .get(name)
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+    fn active_id(&self, name: &str) -> Option<i64> {
+        self.param(name).parse().ok()
+    }
+}
+
+pub(crate) fn handle(state: &mut State, request: &Request) -> MockResponse {
+    let Some(ctx) = Context::new(request) else {
+        return MockResponse::new(400, "Bad Request");
+    };
+    let path = ctx.url.path().to_owned();
+    state.record_hit(&path);
+    match path.as_str() {
+        "/mlogin" => return login_page(),
+        "/fanyalogin" => return login(state, &ctx),
+        "/num/phonecode" => return send_sms_code(state, &ctx),
+        "/num/login" => return login_sms(state, &ctx),
+        "/login" => return qr_login_page(state),
+        "/getauthstatus" => return qr_auth_status(state, &ctx),
+        "/org/searchUnis" => return search_institutions(state, &ctx),
+        "/unitlogin" => return login_unit(state, &ctx),
+        "/captcha/get/conf" => return captcha::conf(&ctx),
+        "/captcha/get/verification/image" => return captcha::image(state, &ctx),
+        "/captcha/check/verification/result" => return captcha::check(state, &ctx),
+        "/mock/shade.png" => return captcha::shade_image(),
+        "/mock/cutout.png" => return captcha::cutout_image(),
+        _ => (),
+    }
+    // 以下接口均需要登录。
+    let Some(account) = ctx
+        .cookies
+        .get(SESSION_COOKIE)
+        .and_then(|token| state.sessions.get(token))
+        .and_then(|uid| state.scenario.accounts.iter().find(|a| &a.uid == uid))
+        .cloned()
+    else {
+        return login_page();
+    };
+    match path.as_str() {
+        "/mooc/accountManage" => MockResponse::ok(&format!(
+            r#"<p class="personalName"><span id="messageName" class="colorBlue">{}</span></p>"#,
+            account.name
+        )),
+        "/mycourse/backclazzdata" => courses(state),
+        "/v2/apis/active/student/activelist" => active_list(state, &ctx, &account),
+        "/newsign/signDetail" => sign_detail(state, &ctx),
+        "/v2/apis/sign/getLocationLog" => location_log(state, &ctx),
+        "/newsign/preSign" => pre_sign(state, &ctx, &account),
+        "/pptSign/analysis" => MockResponse::ok(&format!(
+            "var url = '/pptSign/analysis2?DB_STRATEGY=RANDOM&code='+'{ANALYSIS_CODE}';"
+        )),
+        "/pptSign/analysis2" => MockResponse::ok("success"),
+        "/pptSign/stuSignajax" => MockResponse::ok(&sign(state, &ctx, &account)),
+        "/v2/apis/sign/getAttendInfo" => {
+            let signed = ctx
+                .active_id("activeId")
+                .is_some_and(|id| state.signed.contains(&(account.uid.clone(), id)));
+            MockResponse::json(&json!({"result": 1, "data": {"status": signed as i64}}))
+        }
+        "/widget/sign/pcStuSignController/checkSignCode" => {
+            let ok = find_activity(state, ctx.active_id("activeId"))
+                .and_then(|a| expected_signcode(&a.kind))
+                .is_some_and(|c| c == ctx.param("signCode"));
+            MockResponse::json(&json!({"result": ok as i64}))
+        }
+        "/" => MockResponse::ok(&format!(
+            "<script>var enc =\"MOCKPANENC{}\"; var _rootdir = \"mock-root-dir\";</script>",
+            account.uid
+        )),
+        "/opt/listres" => {
+            let list = state
+                .scenario
+                .pan_files
+                .iter()
+                .map(|(name, object_id)| json!({"name": name, "objectId": object_id}))
+                .collect::<Vec<_>>();
+            MockResponse::json(&json!({"result": true, "list": list}))
+        }
+        "/api/token/uservalid" => {
+            MockResponse::json(&json!({"result": true, "_token": "MOCKPANTOKEN"}))
+        }
+        "/upload" => {
+            let object_id = format!("mock-object-upload-{}", state.next_serial());
+            state.uploaded.push(object_id.clone());
+            MockResponse::json(&json!({"result": true, "msg": "success", "objectId": object_id}))
+        }
+        _ => MockResponse::not_found(),
+    }
+}
+
+fn login_page() -> MockResponse {
+    MockResponse::ok("<html><head><title>用户登录</title></head><body></body></html>")
+}
+
+fn login(state: &mut State, ctx: &Context) -> MockResponse {
+    let Some(account) = state
+        .scenario
+        .accounts
+        .iter()
+        .find(|a| a.uname == ctx.param("uname") && a.enc_passwd == ctx.param("password"))
+        .cloned()
+    else {
+        return MockResponse::json(&json!({"msg2": "用户名或密码错误", "status": false}));
+    };
+    if let Some(message) = &account.rejection {
+        return MockResponse::json(&json!({"msg2": message, "status": false}));
+    }
+    login_success(state, ctx, &account)
+}
+
+/// 登录成功，设置会话 Cookies.
+fn login_success(state: &mut State, ctx: &Context, account: &MockAccount) -> MockResponse {
+    let token = format!("mock-session-{}", state.next_serial());
+    state.sessions.insert(token.clone(), account.uid.clone());
+    let domain = match ctx.url.host_str() {
+        Some(host) if host.ends_with("chaoxing.com") => "; Domain=chaoxing.com",
+        _ => "",
+    };
+    let set_cookie = |name: &str, value: &str| {
+        format!("{name}={value}; Path=/; Max-Age={SESSION_MAX_AGE}{domain}")
+    };
+    MockResponse::json(&json!({"url": "https%3A%2F%2Fi.chaoxing.com", "status": true}))
+        .with_header("Set-Cookie", &set_cookie("_uid", &account.uid))
+        .with_header("Set-Cookie", &set_cookie("fid", &account.fid))
+        .with_header("Set-Cookie", &set_cookie(SESSION_COOKIE, &token))
+}
+
+fn search_institutions(state: &State, ctx: &Context) -> MockResponse {
+    let froms = state
+        .scenario
+        .institutions
+        .iter()
+        .filter(|(_, name)| name.contains(ctx.param("filter")))
+        .map(|(fid, name)| json!({"id": fid, "name": name}))
+        .collect::<Vec<_>>();
+    MockResponse::json(&json!({"result": true, "froms": froms}))
+}
+
+fn login_unit(state: &mut State, ctx: &Context) -> MockResponse {
+    let Some(account) = state
+        .scenario
+        .accounts
+        .iter()
+        .find(|a| {
+            a.fid == ctx.param("fid")
+                && a.stu_number.as_deref() == Some(ctx.param("uname"))
+                && a.enc_passwd == ctx.param("password")
+        })
+        .cloned()
+    else {
+        return MockResponse::json(&json!({"status": false, "mes": "用户名或密码错误"}));
+    };
+    login_success(state, ctx, &account)
+}
+
+fn send_sms_code(state: &mut State, ctx: &Context) -> MockResponse {
+    let phone = ctx.param("phone");
+    if !state.scenario.accounts.iter().any(|a| a.uname == phone) {
+        return MockResponse::json(&json!({"status": false, "mes": "该手机号未注册"}));
+    }
+    let code = format!("{:06}", 100000 + state.next_serial());
+    state.sms_codes.insert(phone.to_owned(), code);
+    MockResponse::json(&json!({"status": true, "mes": "验证码已发送"}))
+}
+
+fn login_sms(state: &mut State, ctx: &Context) -> MockResponse {
+    let phone = ctx.param("phone");
+    if state.sms_codes.get(phone).map(String::as_str) != Some(ctx.param("code")) {
+        return MockResponse::json(&json!({"status": false, "mes": "验证码错误"}));
+    }
+    state.sms_codes.remove(phone);
+    let account = state
+        .scenario
+        .accounts
+        .iter()
+        .find(|a| a.uname == phone)
+        .cloned()
+        .expect("发送验证码时已检查账号");
+    login_success(state, ctx, &account)
+}
+
+fn qr_login_page(state: &mut State) -> MockResponse {
+    let uuid = format!("mock-qr-{}", state.next_serial());
+    state.qr_logins.insert(uuid.clone(), MockQrLogin::Waiting);
+    MockResponse::ok(&format!(
+        r#"<html><head><title>用户登录</title></head><body><input type="hidden" id="uuid" value="{uuid}"/><input type="hidden" id="enc" value="{}"/></body></html>"#,
+        qr_enc(&uuid)
+    ))
+}
+
+fn qr_enc(uuid: &str) -> String {
+    format!("ENC-{uuid}")
+}
+
+fn qr_auth_status(state: &mut State, ctx: &Context) -> MockResponse {
+    let uuid = ctx.param("uuid");
+    if ctx.param("enc") != qr_enc(uuid) {
+        return MockResponse::json(&json!({"status": false, "type": "1", "mes": "二维码已失效"}));
+    }
+    let Some(qr) = state.qr_logins.get(uuid).cloned() else {
+        return MockResponse::json(&json!({"status": false, "type": "1", "mes": "二维码已失效"}));
+    };
+    let find = |uid: &str| {
+        state
+            .scenario
+            .accounts
+            .iter()
+            .find(|a| a.uid == uid)
+            .cloned()
+            .expect("扫码时已检查账号")
+    };
+    match qr {
+        MockQrLogin::Waiting => {
+            MockResponse::json(&json!({"status": false, "type": "3", "mes": "未登录"}))
+        }
+        MockQrLogin::Scanned(uid) => MockResponse::json(
+            &json!({"status": false, "type": "4", "mes": "已扫描", "nickname": find(&uid).name}),
+        ),
+        MockQrLogin::Expired => {
+            MockResponse::json(&json!({"status": false, "type": "2", "mes": "二维码已失效"}))
+        }
+        MockQrLogin::Confirmed(uid) => {
+            let account = find(&uid);
+            state.qr_logins.remove(uuid);
+            login_success(state, ctx, &account)
+        }
+    }
+}
+
+fn courses(state: &State) -> MockResponse {
+    let channel_list = state
+        .scenario
+        .courses
+        .iter()
+        .map(|c| {
+            json!({
+                "key": c.class_id,
+                "content": {"course": {"data": [{
+                    "id": c.id,
+                    "teacherfactor": c.teacher,
+                    "imageurl": "",
+                    "name": c.name,
+                }]}},
+            })
+        })
+        .collect::<Vec<_>>();
+    MockResponse::json(&json!({"result": 1, "channelList": channel_list}))
+}
+
+fn find_activity(state: &State, id: Option<i64>) -> Option<&MockActivity> {
+    let id = id?;
+    state.scenario.activities.iter().find(|a| a.id == id)
+}
+
+fn expected_signcode(kind: &MockSignKind) -> Option<&str> {
+    match kind {
+        MockSignKind::Gesture { gesture } => Some(gesture),
+        MockSignKind::Signcode { signcode } => Some(signcode),
+        _ => None,
+    }
+}
+
+fn active_list(state: &State, ctx: &Context, account: &MockAccount) -> MockResponse {
+    // 机构账号需要提供正确的 fid.
+    if account.stu_number.is_some() && ctx.param("fid") != account.fid {
+        return MockResponse::new(403, "Forbidden");
+    }
+    let course_id = ctx.param("courseId").parse::<i64>().ok();
+    let list = state
+        .scenario
+        .activities
+        .iter()
+        .filter(|a| Some(a.course_id) == course_id)
+        .map(|a| {
+            json!({
+                "nameOne": a.name,
+                "id": a.id,
+                "otherId": a.kind.other_id(),
+                "status": a.status,
+                "startTime": a.start_time_mills,
+            })
+        })
+        .collect::<Vec<_>>();
+    MockResponse::json(&json!({"result": 1, "data": {"activeList": list}}))
+}
+
+fn sign_detail(state: &State, ctx: &Context) -> MockResponse {
+    let Some(activity) = find_activity(state, ctx.active_id("activePrimaryId")) else {
+        return MockResponse::json(&json!({"result": 0, "msg": "活动不存在"}));
+    };
+    let (is_photo, is_refresh, sign_code) = match &activity.kind {
+        MockSignKind::Photo => (1, 0, None),
+        MockSignKind::QrCode { refresh } => (0, *refresh as i64, Some("MOCKC")),
+        _ => (0, 0, None),
+    };
+    MockResponse::json(&json!({
+        "ifPhoto": is_photo,
+        "ifRefreshEwm": is_refresh,
+        "signCode": sign_code,
+    }))
+}
+
+fn location_log(state: &State, ctx: &Context) -> MockResponse {
+    let course_id = ctx.param("courseId").parse::<i64>().ok();
+    let data = state
+        .scenario
+        .activities
+        .iter()
+        .filter(|a| Some(a.course_id) == course_id)
+        .filter_map(|a| match &a.kind {
+            MockSignKind::Location { location } if location.published => Some(json!({
+                "activeid": a.id,
+                "address": location.addr,
+                "longitude": location.lon,
+                "latitude": location.lat,
+                "locationrange": location.range.to_string(),
+            })),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    MockResponse::json(&json!({"result": 1, "data": data}))
+}
+
+fn needs_validate(state: &State, activity: &MockActivity) -> bool {
+    state.scenario.validate_required
+        && matches!(
+            activity.kind,
+            MockSignKind::Location { .. } | MockSignKind::QrCode { .. }
+        )
+}
+
+fn pre_sign(state: &State, ctx: &Context, account: &MockAccount) -> MockResponse {
+    let Some(activity) = find_activity(state, ctx.active_id("activePrimaryId")) else {
+        return MockResponse::ok(r#"<h1 id="statuscontent">活动不存在</h1>"#);
+    };
+    if state.signed.contains(&(account.uid.clone(), activity.id)) {
+        return MockResponse::ok(r#"<h1 id="statuscontent">签到成功</h1>"#);
+    }
+    let mut html = format!("<title>{}</title>", activity.name);
+    if let MockSignKind::Location { location } = &activity.kind
+        && location.published
+    {
+        html += &format!(
+            r#"<input type="hidden" id="locationText" value="{}"/><input type="hidden" id="locationLongitude" value="{}"/><input type="hidden" id="locationLatitude" value="{}"/><input type="hidden" id="locationRange" value="{}米"/>"#,
+            location.addr, location.lon, location.lat, location.range
+        );
+    }
+    if needs_validate(state, activity) {
+        html += &format!("<script>var config = {{ captchaId: '{MOCK_CAPTCHA_ID}' }};</script>");
+    }
+    MockResponse::ok(&html)
+}
+
+/// 签到接口，返回值与真实接口的文本相同。
+fn sign(state: &mut State, ctx: &Context, account: &MockAccount) -> String {
+    let Some(activity) = find_activity(state, ctx.active_id("activeId")).cloned() else {
+        return "非法请求".to_owned();
+    };
+    let key = (account.uid.clone(), activity.id);
+    if state.signed.contains(&key) {
+        return "您已签到过了".to_owned();
+    }
+    match &activity.kind {
+        MockSignKind::Normal => (),
+        MockSignKind::Photo => {
+            let object_id = ctx.param("objectId");
+            let known = state
+                .scenario
+                .pan_files
+                .iter()
+                .any(|(_, id)| id == object_id)
+                || state.uploaded.iter().any(|id| id == object_id);
+            if !known {
+                return "请先拍照".to_owned();
+            }
+        }
+        MockSignKind::Gesture { .. } | MockSignKind::Signcode { .. } => {
+            if expected_signcode(&activity.kind) != Some(ctx.param("signCode")) {
+                return "签到码错误".to_owned();
+            }
+        }
+        MockSignKind::QrCode { .. } => {
+            if ctx.param("enc") != state.enc {
+                return String::new();
+            }
+        }
+        MockSignKind::Location { location } => {
+            let lon = ctx.param("longitude").parse::<f64>();
+            let lat = ctx.param("latitude").parse::<f64>();
+            let (Ok(lon), Ok(lat)) = (lon, lat) else {
+                return "位置信息无效".to_owned();
+            };
+            if !location.contains(lon, lat) {
+                return "您不在签到位置范围内，请重新签到".to_owned();
+            }
+        }
+    }
+    if needs_validate(state, &activity) {
+        let validated = ctx.param("enc2") == ENC2 && state.validates.remove(ctx.param("validate"));
+        if !validated {
+            return format!("validate_{ENC2}");
+        }
+    }
+    state.signed.insert(key);
+    "success".to_owned()
+}
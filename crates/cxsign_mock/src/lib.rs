@@ -0,0 +1,290 @@
+//! 超星接口的替身服务器，用于离线测试。
+//!
+//! [`MockServer`] 按照 [`Scenario`] 中描述的账号、课程和签到活动应答各个接口，
+//! 可以通过 [`MockServer::transport`] 以 [`Transport`](cxsign_transport::Transport) 的形式直接注入 `Session`,
+//! 也可以通过 [`MockServer::spawn`] 在本地端口上监听，再配合 [`endpoint`](cxsign_transport::endpoint)
+//! 中的主机覆盖让整个程序使用它。
+//!
+//! 模拟服务器只按请求路径分发，忽略主机部分。
+#![feature(let_chains)]
+
+mod captcha;
+mod handler;
+mod scenario;
+mod server;
+#[cfg(test)]
+mod tests;
+mod transport;
+
+pub use scenario::*;
+pub use server::*;
+pub use transport::*;
+
+use cxsign_error::Error;
+use cxsign_transport::Request;
+use log::debug;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// 模拟服务器在预签到页面中给出的滑块 ID.
+pub static MOCK_CAPTCHA_ID: &str = "MockCaptchaId0000000000000000000";
+
+/// 模拟服务器的响应。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl MockResponse {
+    pub fn new(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+    pub fn ok(body: &str) -> Self {
+        Self::new(200, body).with_header("Content-Type", "text/html;charset=UTF-8")
+    }
+    pub fn json(value: &serde_json::Value) -> Self {
+        Self::new(200, &value.to_string()).with_header("Content-Type", "application/json")
+    }
+    pub fn bytes(content_type: &str, body: Vec<u8>) -> Self {
+        Self {
+            status: 200,
+            headers: vec![("Content-Type".to_owned(), content_type.to_owned())],
+            body,
+        }
+    }
+    pub fn not_found() -> Self {
+        Self::new(404, "Not Found")
+    }
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+    /// 以文本形式获取响应内容。
+    pub fn text(&self) -> std::borrow::Cow<'_, str> {
+        String::from_utf8_lossy(&self.body)
+    }
+    /// 序列化为 HTTP/1.1 响应报文。
+    pub fn to_http_bytes(&self) -> Vec<u8> {
+        let reason = http::StatusCode::from_u16(self.status)
+            .ok()
+            .and_then(|s| s.canonical_reason())
+            .unwrap_or("Unknown");
+        let mut s = format!("HTTP/1.1 {} {reason}\r\n", self.status);
+        for (name, value) in &self.headers {
+            s += &format!("{name}: {value}\r\n");
+        }
+        s += &format!(
+            "Content-Length: {}\r\nConnection: close\r\n\r\n",
+            self.body.len()
+        );
+        let mut bytes = s.into_bytes();
+        bytes.extend_from_slice(&self.body);
+        bytes
+    }
+    /// 转换为 [`ureq::Response`].
+    pub fn into_response(self) -> Result<ureq::Response, Error> {
+        let mut builder = http::Response::builder().status(self.status);
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        let response = builder
+            .body(self.body)
+            .map_err(|e| Error::ParseError(format!("无法构造响应：{e}.")))?;
+        Ok(response.into())
+    }
+}
+
+/// 登录二维码的状态。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum MockQrLogin {
+    Waiting,
+    Scanned(String),
+    Confirmed(String),
+    Expired,
+}
+
+/// 模拟服务器的内部状态。
+#[derive(Debug)]
+pub(crate) struct State {
+    pub(crate) scenario: Scenario,
+    /// 二维码签到当前的 `enc`.
+    pub(crate) enc: String,
+    enc_serial: u32,
+    /// 会话令牌到 uid 的映射。
+    pub(crate) sessions: HashMap<String, String>,
+    /// 已签到的 `(uid, 活动 ID)`.
+    pub(crate) signed: HashSet<(String, i64)>,
+    /// 已发放且尚未使用的滑块令牌。
+    pub(crate) captcha_tokens: HashSet<String>,
+    /// 已发放且尚未使用的滑块验证结果。
+    pub(crate) validates: HashSet<String>,
+    pub(crate) uploaded: Vec<String>,
+    /// 手机号到已发送且尚未使用的短信验证码的映射。
+    pub(crate) sms_codes: HashMap<String, String>,
+    /// 登录二维码的 `uuid` 到其状态的映射。
+    pub(crate) qr_logins: HashMap<String, MockQrLogin>,
+    hits: HashMap<String, usize>,
+    /// 路径到接下来需要直接返回 503 的请求数，这些请求不会被处理。
+    failures: HashMap<String, usize>,
+    /// 路径到接下来处理后仍返回 503 的请求数，模拟响应丢失。
+    lost_responses: HashMap<String, usize>,
+    serial: u64,
+}
+
+impl State {
+    fn new(scenario: Scenario) -> Self {
+        Self {
+            scenario,
+            enc: Self::enc_of(0),
+            enc_serial: 0,
+            sessions: HashMap::new(),
+            signed: HashSet::new(),
+            captcha_tokens: HashSet::new(),
+            validates: HashSet::new(),
+            uploaded: Vec::new(),
+            sms_codes: HashMap::new(),
+            qr_logins: HashMap::new(),
+            hits: HashMap::new(),
+            failures: HashMap::new(),
+            lost_responses: HashMap::new(),
+            serial: 0,
+        }
+    }
+    fn enc_of(serial: u32) -> String {
+        format!("MOCKENC{serial:025X}")
+    }
+    /// 生成一个自增的标识，用于令牌、objectId 等。
+    pub(crate) fn next_serial(&mut self) -> u64 {
+        self.serial += 1;
+        self.serial
+    }
+    fn record_hit(&mut self, path: &str) {
+        *self.hits.entry(path.to_owned()).or_default() += 1;
+    }
+    fn take_fault(faults: &mut HashMap<String, usize>, path: &str) -> bool {
+        if let Some(count) = faults.get_mut(path)
+            && *count > 0
+        {
+            *count -= 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// 模拟服务器。
+#[derive(Debug)]
+pub struct MockServer {
+    state: Mutex<State>,
+}
+
+impl MockServer {
+    pub fn new(scenario: Scenario) -> Arc<Self> {
+        Arc::new(Self {
+            state: Mutex::new(State::new(scenario)),
+        })
+    }
+    pub(crate) fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+    /// 创建一个直接调用本服务器、拥有独立 Cookies 的 [`MockTransport`].
+    pub fn transport(self: &Arc<Self>) -> MockTransport {
+        MockTransport::new(Arc::clone(self))
+    }
+    /// 处理请求。
+    pub fn handle(&self, request: &Request) -> MockResponse {
+        let mut state = self.lock();
+        let path = url::Url::parse(&request.url)
+            .map(|url| url.path().to_owned())
+            .unwrap_or_default();
+        let response = if State::take_fault(&mut state.failures, &path) {
+            state.record_hit(&path);
+            MockResponse::new(503, "Service Unavailable")
+        } else {
+            let response = handler::handle(&mut state, request);
+            if State::take_fault(&mut state.lost_responses, &path) {
+                MockResponse::new(503, "Service Unavailable")
+            } else {
+                response
+            }
+        };
+        debug!("{} {} -> {}", request.method, request.url, response.status);
+        response
+    }
+    /// 修改场景，如新增签到活动等。
+    pub fn update_scenario(&self, f: impl FnOnce(&mut Scenario)) {
+        f(&mut self.lock().scenario)
+    }
+    /// 二维码签到当前有效的 `enc`.
+    pub fn current_enc(&self) -> String {
+        self.lock().enc.clone()
+    }
+    /// 刷新二维码，之前的 `enc` 随即失效。返回新的 `enc`.
+    pub fn refresh_enc(&self) -> String {
+        let mut state = self.lock();
+        state.enc_serial += 1;
+        state.enc = State::enc_of(state.enc_serial);
+        state.enc.clone()
+    }
+    /// 用户是否已经完成了某个签到。
+    pub fn is_signed(&self, uid: &str, active_id: i64) -> bool {
+        self.lock().signed.contains(&(uid.to_owned(), active_id))
+    }
+    /// 已完成的签到数。
+    pub fn signed_count(&self) -> usize {
+        self.lock().signed.len()
+    }
+    /// 最近发送给 `phone` 的短信验证码，模拟用户查看短信。
+    pub fn sms_code(&self, phone: &str) -> Option<String> {
+        self.lock().sms_codes.get(phone).cloned()
+    }
+    /// 模拟账号 `uname` 在手机上扫描了登录二维码 `uuid`, `confirm` 为是否同时确认登录。
+    pub fn scan_login_qr(&self, uuid: &str, uname: &str, confirm: bool) {
+        let mut state = self.lock();
+        let Some(uid) = state
+            .scenario
+            .accounts
+            .iter()
+            .find(|a| a.uname == uname)
+            .map(|a| a.uid.clone())
+        else {
+            return;
+        };
+        if let Some(qr) = state.qr_logins.get_mut(uuid) {
+            *qr = if confirm {
+                MockQrLogin::Confirmed(uid)
+            } else {
+                MockQrLogin::Scanned(uid)
+            };
+        }
+    }
+    /// 使登录二维码 `uuid` 失效。
+    pub fn expire_login_qr(&self, uuid: &str) {
+        if let Some(qr) = self.lock().qr_logins.get_mut(uuid) {
+            *qr = MockQrLogin::Expired;
+        }
+    }
+    /// 使所有已登录的会话失效，模拟 Cookies 过期。
+    pub fn expire_sessions(&self) {
+        self.lock().sessions.clear()
+    }
+    /// 接下来对 `path` 的 `count` 次请求直接返回 503, 不做处理。
+    pub fn fail_requests(&self, path: &str, count: usize) {
+        self.lock().failures.insert(path.to_owned(), count);
+    }
+    /// 接下来对 `path` 的 `count` 次请求照常处理，但返回 503, 模拟请求已生效而响应丢失。
+    pub fn lose_responses(&self, path: &str, count: usize) {
+        self.lock().lost_responses.insert(path.to_owned(), count);
+    }
+    /// 某个路径被请求的次数。
+    pub fn hits(&self, path: &str) -> usize {
+        self.lock().hits.get(path).copied().unwrap_or(0)
+    }
+}
@@ -0,0 +1,251 @@
+/// 模拟账号。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockAccount {
+    pub uname: String,
+    /// 登录时需要提供的加密后的密码，模拟服务器只做字符串比较。
+    pub enc_passwd: String,
+    pub uid: String,
+    pub fid: String,
+    pub name: String,
+    /// 设置后，即使密码正确也拒绝登录，并返回该提示信息。
+    pub rejection: Option<String>,
+    /// 机构账号的学号，设置后需要使用机构账号登录。
+    pub stu_number: Option<String>,
+}
+
+impl MockAccount {
+    pub fn new(uname: &str, enc_passwd: &str, uid: &str, name: &str) -> Self {
+        Self {
+            uname: uname.to_owned(),
+            enc_passwd: enc_passwd.to_owned(),
+            uid: uid.to_owned(),
+            fid: "1000".to_owned(),
+            name: name.to_owned(),
+            rejection: None,
+            stu_number: None,
+        }
+    }
+    /// 设为机构 `fid` 下学号为 `stu_number` 的机构账号。
+    pub fn in_institution(mut self, fid: i64, stu_number: &str) -> Self {
+        self.fid = fid.to_string();
+        self.stu_number = Some(stu_number.to_owned());
+        self
+    }
+    /// 拒绝该账号登录，参见 [`MockAccount::rejection`].
+    pub fn rejected(mut self, message: &str) -> Self {
+        self.rejection = Some(message.to_owned());
+        self
+    }
+}
+
+/// 模拟课程，所有账号均加入了所有课程。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockCourse {
+    pub id: i64,
+    pub class_id: i64,
+    pub name: String,
+    pub teacher: String,
+}
+
+impl MockCourse {
+    pub fn new(id: i64, class_id: i64, name: &str, teacher: &str) -> Self {
+        Self {
+            id,
+            class_id,
+            name: name.to_owned(),
+            teacher: teacher.to_owned(),
+        }
+    }
+}
+
+/// 签到位置及范围。
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockLocation {
+    pub addr: String,
+    pub lon: f64,
+    pub lat: f64,
+    /// 范围，单位为米。
+    pub range: u32,
+    /// 是否可以通过 `getLocationLog` 获取到该位置。
+    pub published: bool,
+}
+
+impl MockLocation {
+    pub fn new(addr: &str, lon: f64, lat: f64, range: u32) -> Self {
+        Self {
+            addr: addr.to_owned(),
+            lon,
+            lat,
+            range,
+            published: true,
+        }
+    }
+    /// 与给定经纬度之间的距离，单位为米。
+    pub fn distance_to(&self, lon: f64, lat: f64) -> f64 {
+        const R: f64 = 6371393.0;
+        let (lat1, lat2) = (self.lat.to_radians(), lat.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lon = (lon - self.lon).to_radians();
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * R * a.sqrt().asin()
+    }
+    pub fn contains(&self, lon: f64, lat: f64) -> bool {
+        self.distance_to(lon, lat) <= self.range as f64
+    }
+}
+
+/// 签到类型。
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockSignKind {
+    Normal,
+    Photo,
+    /// 二维码签到，`refresh` 为真时二维码会刷新，参见 [`MockServer::refresh_enc`](crate::MockServer::refresh_enc).
+    QrCode {
+        refresh: bool,
+    },
+    Gesture {
+        gesture: String,
+    },
+    Location {
+        location: MockLocation,
+    },
+    Signcode {
+        signcode: String,
+    },
+}
+
+impl MockSignKind {
+    pub fn other_id(&self) -> &'static str {
+        match self {
+            MockSignKind::Normal | MockSignKind::Photo => "0",
+            MockSignKind::QrCode { .. } => "2",
+            MockSignKind::Gesture { .. } => "3",
+            MockSignKind::Location { .. } => "4",
+            MockSignKind::Signcode { .. } => "5",
+        }
+    }
+}
+
+/// 签到活动。
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockActivity {
+    pub id: i64,
+    pub course_id: i64,
+    pub name: String,
+    pub kind: MockSignKind,
+    /// 1 表示进行中。
+    pub status: i32,
+    pub start_time_mills: u64,
+}
+
+impl MockActivity {
+    /// 创建一个一分钟前开始、正在进行中的签到。
+    pub fn new(id: i64, course_id: i64, name: &str, kind: MockSignKind) -> Self {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        Self {
+            id,
+            course_id,
+            name: name.to_owned(),
+            kind,
+            status: 1,
+            start_time_mills: now.saturating_sub(60_000),
+        }
+    }
+}
+
+/// 模拟服务器的初始数据及行为。
+#[derive(Debug, Clone, Default)]
+pub struct Scenario {
+    pub accounts: Vec<MockAccount>,
+    pub courses: Vec<MockCourse>,
+    pub activities: Vec<MockActivity>,
+    /// 云盘中的文件，`(文件名, objectId)`.
+    pub pan_files: Vec<(String, String)>,
+    /// 为真时位置签到和二维码签到需要先通过滑块验证。
+    pub validate_required: bool,
+    /// 可以搜索到的机构，`(fid, 名称)`.
+    pub institutions: Vec<(i64, String)>,
+}
+
+impl Scenario {
+    pub const UNAME: &'static str = "13800000000";
+    pub const ENC_PASSWD: &'static str = "mock-enc-passwd";
+    pub const COURSE_ID: i64 = 200;
+    pub const ACTIVE_ID: i64 = 3000;
+
+    /// 一个账号、一门课程，没有签到活动。
+    pub fn basic() -> Self {
+        Self::default()
+            .with_account(MockAccount::new(
+                Self::UNAME,
+                Self::ENC_PASSWD,
+                "10001",
+                "测试用户",
+            ))
+            .with_course(MockCourse::new(
+                Self::COURSE_ID,
+                100,
+                "测试课程",
+                "测试教师",
+            ))
+            .with_pan_file("1.png", "mock-object-1")
+    }
+    /// 两个账号和一个会刷新的二维码签到。
+    pub fn qrcode_with_refresh() -> Self {
+        Self::basic()
+            .with_account(MockAccount::new(
+                "13800000001",
+                Self::ENC_PASSWD,
+                "10002",
+                "测试用户二",
+            ))
+            .with_activity(MockActivity::new(
+                Self::ACTIVE_ID,
+                Self::COURSE_ID,
+                "二维码签到",
+                MockSignKind::QrCode { refresh: true },
+            ))
+    }
+    /// 一个范围为 50 米的位置签到。
+    pub fn location_out_of_range() -> Self {
+        Self::basic().with_activity(MockActivity::new(
+            Self::ACTIVE_ID,
+            Self::COURSE_ID,
+            "位置签到",
+            MockSignKind::Location {
+                location: MockLocation::new("测试地点", 116.397128, 39.916527, 50),
+            },
+        ))
+    }
+    /// 需要通过滑块验证的位置签到。
+    pub fn validate_required() -> Self {
+        Self::location_out_of_range().require_validate()
+    }
+    pub fn with_institution(mut self, fid: i64, name: &str) -> Self {
+        self.institutions.push((fid, name.to_owned()));
+        self
+    }
+    pub fn with_account(mut self, account: MockAccount) -> Self {
+        self.accounts.push(account);
+        self
+    }
+    pub fn with_course(mut self, course: MockCourse) -> Self {
+        self.courses.push(course);
+        self
+    }
+    pub fn with_activity(mut self, activity: MockActivity) -> Self {
+        self.activities.push(activity);
+        self
+    }
+    pub fn with_pan_file(mut self, name: &str, object_id: &str) -> Self {
+        self.pan_files.push((name.to_owned(), object_id.to_owned()));
+        self
+    }
+    pub fn require_validate(mut self) -> Self {
+        self.validate_required = true;
+        self
+    }
+}
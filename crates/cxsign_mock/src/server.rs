@@ -0,0 +1,119 @@
+use crate::MockServer;
+use cxsign_transport::endpoint::{set_host, Host};
+use cxsign_transport::{Body, Method, Request};
+use log::warn;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+/// 在本地端口上监听的模拟服务器，离开作用域时停止。
+///
+/// 每个连接只处理一个请求，响应后即关闭连接。
+#[derive(Debug)]
+pub struct MockHttpServer {
+    addr: SocketAddr,
+    stopped: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MockHttpServer {
+    pub fn get_addr(&self) -> SocketAddr {
+        self.addr
+    }
+    /// 服务器地址，如 `http://127.0.0.1:8080`.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+    /// 通过接口地址注册表将所有主机指向本服务器。
+    pub fn point_hosts_here(&self) {
+        for host in Host::ALL {
+            set_host(host, &self.url());
+        }
+    }
+}
+
+impl Drop for MockHttpServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // 唤醒阻塞中的 `accept`.
+        let _ = TcpStream::connect(self.addr);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl MockServer {
+    /// 在 `127.0.0.1` 的随机端口上监听。
+    pub fn spawn(self: &Arc<Self>) -> std::io::Result<MockHttpServer> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let stopped = Arc::new(AtomicBool::new(false));
+        let server = Arc::clone(self);
+        let stopped_ = Arc::clone(&stopped);
+        let handle = std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stopped_.load(Ordering::SeqCst) {
+                    break;
+                }
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                if let Err(e) = serve(&server, addr, stream) {
+                    warn!("模拟服务器处理请求失败：{e}.");
+                }
+            }
+        });
+        Ok(MockHttpServer {
+            addr,
+            stopped,
+            handle: Some(handle),
+        })
+    }
+}
+
+fn serve(server: &MockServer, addr: SocketAddr, mut stream: TcpStream) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Ok(());
+    };
+    let method = if method.eq_ignore_ascii_case("POST") {
+        Method::Post
+    } else {
+        Method::Get
+    };
+    let mut request = Request::new(method, &format!("http://{addr}{target}"));
+    loop {
+        line.clear();
+        reader.read_line(&mut line)?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            request
+                .headers
+                .push((name.trim().to_owned(), value.trim().to_owned()));
+        }
+    }
+    let len = request
+        .header("Content-Length")
+        .and_then(|len| len.parse::<usize>().ok())
+        .unwrap_or(0);
+    if len > 0 {
+        let mut body = vec![0; len];
+        reader.read_exact(&mut body)?;
+        request.body = match String::from_utf8(body) {
+            Ok(text) => Body::Text(text),
+            Err(e) => Body::Bytes(e.into_bytes()),
+        };
+    }
+    let response = server.handle(&request);
+    stream.write_all(&response.to_http_bytes())?;
+    stream.flush()
+}
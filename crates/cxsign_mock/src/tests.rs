@@ -0,0 +1,866 @@
+use crate::{
+    MockAccount, MockActivity, MockLocation, MockServer, MockSignKind, MockTransport, Scenario,
+};
+use cxsign_activity::sign::{AsyncSignTrait, Sign, SignTrait};
+use cxsign_activity::Activity;
+use cxsign_dir::Dir;
+use cxsign_error::{Error, LoginOutcome};
+use cxsign_login::QrLoginEvent;
+use cxsign_signner::{
+    AsyncSignnerTrait, DefaultGestureOrSigncodeSignner, DefaultLocationSignner,
+    DefaultPhotoSignner, DefaultQrCodeSignner, SignnerTrait,
+};
+use cxsign_store::crypto::KeySource;
+use cxsign_store::{AccountTable, DataBase, ExcludeTable, SessionTable};
+use cxsign_transport::Transport;
+use cxsign_types::{Course, LocationTable};
+use cxsign_user::{ReloginError, Session};
+use std::ops::ControlFlow;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn temp_db() -> DataBase {
+    let db = DataBase::in_memory().unwrap();
+    db.add_table::<LocationTable>().unwrap();
+    db
+}
+
+fn login(server: &Arc<MockServer>, uname: &str) -> Session {
+    Session::relogin_with(Arc::new(server.transport()), uname, Scenario::ENC_PASSWD).unwrap()
+}
+
+/// 获取唯一的有效签到。
+fn the_sign(db: &DataBase, sessions: &[Session]) -> Sign {
+    let (valid, _, _) = Activity::get_all_activities(
+        db.add_table::<ExcludeTable>().unwrap(),
+        sessions.iter(),
+        false,
+    )
+    .unwrap();
+    assert_eq!(valid.len(), 1);
+    valid.into_keys().next().unwrap().to_sign(&sessions[0])
+}
+
+#[test]
+fn login_and_get_courses() {
+    let server = MockServer::new(Scenario::basic());
+    let transport = Arc::new(server.transport());
+    assert!(matches!(
+        Session::relogin_with(transport, Scenario::UNAME, "wrong"),
+        Err(Error::LoginRejected(LoginOutcome::WrongPassword(_)))
+    ));
+    let session = login(&server, Scenario::UNAME);
+    assert_eq!(session.get_stu_name(), "测试用户");
+    assert_eq!(session.get_uid(), "10001");
+    let courses = Course::get_session_courses(&session).unwrap();
+    assert_eq!(courses.len(), 1);
+    assert_eq!(courses[0].get_id(), Scenario::COURSE_ID);
+    assert_eq!(courses[0].get_name(), "测试课程");
+    server.expire_sessions();
+    assert!(matches!(
+        Course::get_session_courses(&session),
+        Err(Error::UnexpectedResponseError { .. })
+    ));
+}
+
+#[test]
+fn login_rejections_are_typed() {
+    let server = MockServer::new(
+        Scenario::basic().with_account(
+            MockAccount::new("13800000002", Scenario::ENC_PASSWD, "10003", "锁定用户")
+                .rejected("密码错误次数过多，账号已被锁定"),
+        ),
+    );
+    let Err(Error::LoginRejected(outcome)) = Session::relogin_with(
+        Arc::new(server.transport()),
+        "13800000002",
+        Scenario::ENC_PASSWD,
+    ) else {
+        panic!("应当拒绝登录！");
+    };
+    assert_eq!(
+        outcome,
+        LoginOutcome::AccountLocked("密码错误次数过多，账号已被锁定".to_owned())
+    );
+}
+
+#[test]
+fn relogin_when_session_expired() {
+    let server = MockServer::new(Scenario::basic());
+    let db = temp_db();
+    let table = db.add_table::<SessionTable>().unwrap();
+    // 没有 Cookies 时在加载阶段即重新登录。
+    let session = Session::from_transport_with_relogin(
+        Arc::new(server.transport()),
+        Scenario::UNAME,
+        Scenario::ENC_PASSWD,
+        None,
+        Some(table.storage().unwrap()),
+    )
+    .unwrap();
+    assert_eq!(session.get_stu_name(), "测试用户");
+    assert_eq!(session.get_uid(), "10001");
+    assert_eq!(server.hits("/fanyalogin"), 1);
+    let record = table.get_session_record(Scenario::UNAME).unwrap().unwrap();
+    assert_eq!(record.uid, "10001");
+    // 请求过程中会话失效时重新登录并重发请求。
+    server.expire_sessions();
+    let courses = Course::get_session_courses(&session).unwrap();
+    assert_eq!(courses.len(), 1);
+    assert_eq!(server.hits("/fanyalogin"), 2);
+    assert_eq!(server.hits("/mycourse/backclazzdata"), 2);
+    let refreshed = table.get_session_record(Scenario::UNAME).unwrap().unwrap();
+    assert_ne!(refreshed.cookies, record.cookies);
+    // 密码错误时报告原因。
+    server.expire_sessions();
+    assert!(matches!(
+        Session::from_transport_with_relogin(
+            Arc::new(server.transport()),
+            Scenario::UNAME,
+            "wrong",
+            None,
+            None,
+        ),
+        Err(ReloginError::Rejected(LoginOutcome::WrongPassword(_)))
+    ));
+}
+
+#[test]
+fn session_from_parts_is_offline() {
+    let server = MockServer::new(Scenario::basic());
+    let cookie_store = login(&server, Scenario::UNAME).cookie_store();
+    assert_eq!(server.hits("/mooc/accountManage"), 1);
+    let mut session = Session::from_parts(
+        Arc::new(MockTransport::with_cookie_store(
+            Arc::clone(&server),
+            cookie_store,
+        )),
+        Scenario::UNAME,
+        "旧的姓名",
+    );
+    assert_eq!(server.hits("/mooc/accountManage"), 1);
+    assert_eq!(session.get_stu_name(), "旧的姓名");
+    assert_eq!(session.get_uid(), "10001");
+    assert_eq!(session.get_validated_at(), 0);
+    assert_eq!(Course::get_session_courses(&session).unwrap().len(), 1);
+    assert_eq!(session.refresh_stu_name().unwrap(), "测试用户");
+    assert_eq!(server.hits("/mooc/accountManage"), 2);
+    assert_ne!(session.get_validated_at(), 0);
+}
+
+#[test]
+fn stale_sessions_are_validated_on_load() {
+    let server = MockServer::new(Scenario::basic());
+    let db = temp_db();
+    let table = db.add_table::<SessionTable>().unwrap();
+    let load = |enc_passwd: &str| {
+        Session::load_or_relogin_with(
+            table.storage().unwrap(),
+            Scenario::UNAME,
+            enc_passwd,
+            None,
+            |cookie_store| {
+                Ok(Arc::new(MockTransport::with_cookie_store(
+                    Arc::clone(&server),
+                    cookie_store,
+                )))
+            },
+        )
+    };
+    let mut record = login(&server, Scenario::UNAME).to_record().unwrap();
+    record.validated_at = 0;
+    table.save_session_record(&record).unwrap();
+    // 太久没有确认过的会话在加载时确认，无法重新登录时报告原因。
+    server.expire_sessions();
+    assert!(matches!(
+        load("wrong"),
+        Err(ReloginError::Rejected(LoginOutcome::WrongPassword(_)))
+    ));
+    let session = load(Scenario::ENC_PASSWD).unwrap();
+    assert_eq!(server.hits("/fanyalogin"), 3);
+    assert_ne!(session.get_validated_at(), 0);
+    let saved = table.get_session_record(Scenario::UNAME).unwrap().unwrap();
+    assert_eq!(saved.validated_at, session.get_validated_at());
+    // 刚确认过的会话直接加载，uid 在重新登录后更新。
+    record.cookies = "[]".to_owned();
+    record.validated_at = session.get_validated_at();
+    table.save_session_record(&record).unwrap();
+    let hits = server.hits("/mooc/accountManage");
+    let session = load(Scenario::ENC_PASSWD).unwrap();
+    assert_eq!(server.hits("/mooc/accountManage"), hits);
+    assert_eq!(session.get_uid(), "");
+    assert_eq!(Course::get_session_courses(&session).unwrap().len(), 1);
+    assert_eq!(session.get_uid(), "10001");
+}
+
+#[test]
+fn relogin_keeps_fid_and_does_not_replay_signs() {
+    let server = MockServer::new(Scenario::basic().with_activity(MockActivity::new(
+        Scenario::ACTIVE_ID,
+        Scenario::COURSE_ID,
+        "普通签到",
+        MockSignKind::Normal,
+    )));
+    let db = temp_db();
+    let table = db.add_table::<SessionTable>().unwrap();
+    let session = Session::from_transport_with_relogin(
+        Arc::new(server.transport()),
+        Scenario::UNAME,
+        Scenario::ENC_PASSWD,
+        None,
+        Some(table.storage().unwrap()),
+    )
+    .unwrap();
+    let mut record = session.to_record().unwrap();
+    record.fid = "3000".to_owned();
+    table.save_session_record(&record).unwrap();
+    // 会话失效后提交签到：重新登录后不直接重发，确认未签到后才再次提交。
+    server.expire_sessions();
+    let active_id = Scenario::ACTIVE_ID.to_string();
+    let result = cxsign_activity::protocol::general_sign(&session, &active_id).unwrap();
+    assert!(result.is_susses());
+    assert_eq!(server.hits("/fanyalogin"), 2);
+    assert_eq!(server.hits("/v2/apis/sign/getAttendInfo"), 1);
+    assert_eq!(server.hits("/pptSign/stuSignajax"), 2);
+    // 重新登录后保存的会话保留账号所属机构的 fid.
+    let saved = table.get_session_record(Scenario::UNAME).unwrap().unwrap();
+    assert_eq!(saved.fid, "3000");
+    assert_ne!(saved.cookies, record.cookies);
+}
+
+#[test]
+fn cookies_only_sessions_are_not_relogged_in() {
+    let server = MockServer::new(Scenario::basic());
+    let db = temp_db();
+    let table = db.add_table::<SessionTable>().unwrap();
+    let load = |uname: &str| {
+        Session::load_cookies_only_with(table.storage().unwrap(), uname, None, |cookie_store| {
+            Ok(Arc::new(MockTransport::with_cookie_store(
+                Arc::clone(&server),
+                cookie_store,
+            )))
+        })
+    };
+    assert!(matches!(load(Scenario::UNAME), Err(ReloginError::Expired)));
+    let mut record = login(&server, Scenario::UNAME).to_record().unwrap();
+    record.validated_at = 0;
+    table.save_session_record(&record).unwrap();
+    let session = load(Scenario::UNAME).unwrap();
+    assert_eq!(session.get_stu_name(), "测试用户");
+    // 会话失效后不会使用空密码重新登录。
+    server.expire_sessions();
+    assert!(matches!(
+        Course::get_session_courses(&session),
+        Err(Error::SessionExpired)
+    ));
+    table.save_session_record(&record).unwrap();
+    assert!(matches!(load(Scenario::UNAME), Err(ReloginError::Expired)));
+    assert_eq!(server.hits("/fanyalogin"), 1);
+}
+
+#[test]
+fn cookies_round_trip_through_netscape_format() {
+    let server = MockServer::new(Scenario::basic());
+    let session = login(&server, Scenario::UNAME);
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let expires_at = session.get_expires_at().unwrap();
+    assert!((expires_at - now - crate::handler::SESSION_MAX_AGE).abs() <= 5);
+    let cookies = session.get_cookies();
+    let uid = cookies.iter().find(|cookie| cookie.name == "_uid").unwrap();
+    assert_eq!(uid.value, "10001");
+    assert_eq!(uid.path, "/");
+    assert!(uid.include_subdomains);
+
+    let text = cxsign_user::to_netscape(&session.cookie_store());
+    assert!(text.contains("\t_uid\t10001"));
+    let cookie_store = cxsign_user::from_netscape(&text).unwrap();
+    let mut imported = Session::from_parts(
+        Arc::new(MockTransport::with_cookie_store(
+            Arc::clone(&server),
+            cookie_store,
+        )),
+        Scenario::UNAME,
+        "",
+    );
+    assert_eq!(imported.get_uid(), "10001");
+    assert_eq!(imported.refresh_stu_name().unwrap(), "测试用户");
+    assert!((imported.get_expires_at().unwrap() - expires_at).abs() <= 5);
+}
+
+#[test]
+fn login_with_sms_code() {
+    let server = MockServer::new(Scenario::basic());
+    let transport = Arc::new(server.transport());
+    assert!(matches!(
+        cxsign_login::login_sms_with(transport.as_ref(), Scenario::UNAME, None, &mut || Ok(
+            "000000".to_owned()
+        )),
+        Err(Error::LoginRejected(_))
+    ));
+    cxsign_login::login_sms_with(transport.as_ref(), Scenario::UNAME, None, &mut || {
+        Ok(server.sms_code(Scenario::UNAME).unwrap())
+    })
+    .unwrap();
+    let session = Session::from_transport(transport, Scenario::UNAME).unwrap();
+    assert_eq!(session.get_uid(), "10001");
+    assert_eq!(Course::get_session_courses(&session).unwrap().len(), 1);
+}
+
+#[test]
+fn login_with_qr_code() {
+    let server = MockServer::new(Scenario::basic());
+    let transport = Arc::new(server.transport());
+    let mut events = Vec::new();
+    let mut on_event = |event: QrLoginEvent| {
+        // 第一个二维码失效，扫描第二个二维码并在下一次查询后确认。
+        match &event {
+            QrLoginEvent::Show { content, .. } => {
+                let uuid = content.split("uuid=").nth(1).unwrap().split('&').next();
+                let uuid = uuid.unwrap().to_owned();
+                if events.is_empty() {
+                    server.expire_login_qr(&uuid);
+                } else {
+                    server.scan_login_qr(&uuid, Scenario::UNAME, false);
+                }
+            }
+            QrLoginEvent::Scanned { .. } => {
+                let Some(QrLoginEvent::Show { content, .. }) = events.last() else {
+                    unreachable!()
+                };
+                let uuid = content.split("uuid=").nth(1).unwrap().split('&').next();
+                server.scan_login_qr(uuid.unwrap(), Scenario::UNAME, true);
+            }
+            _ => (),
+        }
+        events.push(event);
+        ControlFlow::Continue(())
+    };
+    cxsign_login::login_qr_with(transport.as_ref(), None, Duration::ZERO, &mut on_event).unwrap();
+    assert!(matches!(
+        events.as_slice(),
+        [
+            QrLoginEvent::Show { .. },
+            QrLoginEvent::Expired,
+            QrLoginEvent::Show { .. },
+            QrLoginEvent::Scanned { nickname },
+        ] if nickname == "测试用户"
+    ));
+    let session = Session::from_transport(transport, Scenario::UNAME).unwrap();
+    assert_eq!(session.get_uid(), "10001");
+    // 回调可以中止登录。
+    let transport = server.transport();
+    assert!(
+        cxsign_login::login_qr_with(&transport, None, Duration::ZERO, &mut |_| {
+            ControlFlow::Break(())
+        })
+        .is_err()
+    );
+}
+
+#[test]
+fn login_by_institution_and_student_number() {
+    let server = MockServer::new(
+        Scenario::basic()
+            .with_institution(2000, "模拟大学")
+            .with_institution(3000, "模拟学院")
+            .with_account(
+                MockAccount::new("13800000003", Scenario::ENC_PASSWD, "10004", "机构用户")
+                    .in_institution(2000, "20240001"),
+            )
+            .with_activity(MockActivity::new(
+                Scenario::ACTIVE_ID,
+                Scenario::COURSE_ID,
+                "普通签到",
+                MockSignKind::Normal,
+            )),
+    );
+    let transport = server.transport();
+    let institutions = cxsign_login::search_institutions(&transport, "大学").unwrap();
+    assert_eq!(institutions.len(), 1);
+    assert_eq!(institutions[0].fid, 2000);
+    // 机构账号不能使用手机号的方式登录。
+    assert!(Session::relogin_with(Arc::new(transport), "20240001", Scenario::ENC_PASSWD).is_err());
+    let session = Session::relogin_account_with(
+        Arc::new(server.transport()),
+        "20240001",
+        Scenario::ENC_PASSWD,
+        Some(institutions[0].fid),
+    )
+    .unwrap();
+    assert_eq!(session.get_uid(), "10004");
+    assert_eq!(session.get_fid(), "2000");
+    // 查询活动时使用账号所属机构的 fid.
+    let db = temp_db();
+    let (valid, _, _) = Activity::get_all_activities(
+        db.add_table::<ExcludeTable>().unwrap(),
+        [session].iter(),
+        false,
+    )
+    .unwrap();
+    assert_eq!(valid.len(), 1);
+    // fid 与账号一同保存。
+    let table = db.add_table::<AccountTable>().unwrap();
+    table
+        .add_account_or(
+            "20240001",
+            Scenario::ENC_PASSWD,
+            "机构用户",
+            AccountTable::update_account,
+        )
+        .unwrap();
+    assert_eq!(table.get_fid("20240001").unwrap(), None);
+    table.set_fid("20240001", Some(2000)).unwrap();
+    assert_eq!(table.get_fid("20240001").unwrap(), Some(2000));
+}
+
+#[test]
+fn persisted_fid_overrides_cookie_fid() {
+    let server = MockServer::new(
+        Scenario::basic()
+            .with_account(
+                MockAccount::new("13800000003", Scenario::ENC_PASSWD, "10004", "机构用户")
+                    .in_institution(2000, "20240001"),
+            )
+            .with_activity(MockActivity::new(
+                Scenario::ACTIVE_ID,
+                Scenario::COURSE_ID,
+                "普通签到",
+                MockSignKind::Normal,
+            )),
+    );
+    let session = Session::relogin_account_with(
+        Arc::new(server.transport()),
+        "20240001",
+        Scenario::ENC_PASSWD,
+        Some(2000),
+    )
+    .unwrap();
+    // Cookies 中的 fid 与随账号保存的 fid 不同。
+    let mut cookie_store = session.cookie_store();
+    let url = url::Url::parse("https://passport2.chaoxing.com/").unwrap();
+    cookie_store
+        .parse("fid=1000; Path=/; Domain=chaoxing.com", &url)
+        .unwrap();
+    let mut record = session.to_record().unwrap();
+    record.cookies =
+        cxsign_user::SessionRecord::new("20240001", "机构用户", &cookie_store, record.validated_at)
+            .unwrap()
+            .cookies;
+    let db = temp_db();
+    let table = db.add_table::<SessionTable>().unwrap();
+    table.save_session_record(&record).unwrap();
+    let session = Session::load_or_relogin_with(
+        table.storage().unwrap(),
+        "20240001",
+        Scenario::ENC_PASSWD,
+        Some(2000),
+        |cookie_store| {
+            Ok(Arc::new(MockTransport::with_cookie_store(
+                Arc::clone(&server),
+                cookie_store,
+            )))
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        cxsign_user::UserCookies::new(session.get_transport().as_ref()).get_fid(),
+        "1000"
+    );
+    assert_eq!(session.get_fid(), "2000");
+    assert_eq!(session.to_record().unwrap().fid, "2000");
+    // 查询活动时使用随账号保存的 fid, 使用 Cookies 中的 fid 时服务器会拒绝。
+    let (valid, _, _) = Activity::get_all_activities(
+        db.add_table::<ExcludeTable>().unwrap(),
+        [session].iter(),
+        false,
+    )
+    .unwrap();
+    assert_eq!(valid.len(), 1);
+}
+
+#[test]
+fn concurrent_bootstrap_reports_failures() {
+    let server = MockServer::new(Scenario::qrcode_with_refresh());
+    let db = temp_db();
+    let table = db.add_table::<AccountTable>().unwrap();
+    let sessions = db.add_table::<SessionTable>().unwrap();
+    for uname in [Scenario::UNAME, "13800000001"] {
+        let session = login(&server, uname);
+        sessions
+            .save_session_record(&session.to_record().unwrap())
+            .unwrap();
+        table
+            .add_account_or(
+                uname,
+                Scenario::ENC_PASSWD,
+                session.get_stu_name(),
+                AccountTable::update_account,
+            )
+            .unwrap();
+    }
+    // 会话均从数据库中加载，不发送请求。
+    let report = table
+        .load_sessions_by_accounts_str(&format!("{},13800000001,1", Scenario::UNAME))
+        .unwrap();
+    assert_eq!(report.sessions.len(), 2);
+    assert!(report.failures.is_empty());
+    assert_eq!(report.missing, vec!["1".to_owned()]);
+    assert!(!report.is_complete());
+    // 第一个账号的会话失效，其课程获取失败，不影响另一个账号。
+    let first = login(&server, Scenario::UNAME);
+    server.expire_sessions();
+    let second = login(&server, "13800000001");
+    let report = Course::fetch_courses([first, second].iter());
+    assert_eq!(report.failures.len(), 1);
+    assert_eq!(report.failures[0].0.get_uid(), "10001");
+    assert!(matches!(
+        report.failures[0].1,
+        Error::UnexpectedResponseError { .. }
+    ));
+    let (_, sessions) = report.courses.into_iter().next().unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].get_uid(), "10002");
+}
+
+#[test]
+fn migrate_json_cookies() {
+    let server = MockServer::new(Scenario::basic());
+    let dir = std::env::temp_dir().join(format!("cxsign_mock_migrate_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let dir = Dir::from(dir);
+    let db = DataBase::new(dir.clone()).unwrap();
+    let session = login(&server, Scenario::UNAME);
+    session.store_json(&dir).unwrap();
+    db.add_table::<AccountTable>()
+        .unwrap()
+        .add_account_or(
+            Scenario::UNAME,
+            Scenario::ENC_PASSWD,
+            session.get_stu_name(),
+            AccountTable::update_account,
+        )
+        .unwrap();
+    let cookies_file = dir.get_json_file_path(Scenario::UNAME);
+    assert!(cookies_file.exists());
+    // 模拟还没有会话表的旧版本数据库，重新打开时会创建会话表并迁移。
+    let passphrase = KeySource::Passphrase("口令".to_owned());
+    db.rekey(Some(&passphrase)).unwrap();
+    db.execute(
+        "DROP TABLE session; DELETE FROM schema_version \
+        WHERE table_name IN ('session','session_json_files');",
+    )
+    .unwrap();
+    drop(db);
+    // 数据库已加密，解锁前无法迁移，文件保留到解锁后。
+    let db = DataBase::new(dir.clone()).unwrap();
+    assert!(cookies_file.exists());
+    drop(db);
+    let db = DataBase::new(dir.clone()).unwrap();
+    assert!(cookies_file.exists());
+    db.unlock(&passphrase).unwrap();
+    let record = db
+        .add_table::<SessionTable>()
+        .unwrap()
+        .get_session_record(Scenario::UNAME)
+        .unwrap()
+        .unwrap();
+    assert!(!cookies_file.exists());
+    assert_eq!(record.stu_name, "测试用户");
+    assert_eq!(record.uid, "10001");
+    // 迁移后的 Cookies 仍然有效，无需重新登录。
+    let transport = MockTransport::with_cookie_store(server, record.cookie_store().unwrap());
+    let server = Arc::clone(transport.get_server());
+    let session = Session::from_transport_with_relogin(
+        Arc::new(transport),
+        Scenario::UNAME,
+        Scenario::ENC_PASSWD,
+        None,
+        None,
+    )
+    .unwrap();
+    assert_eq!(session.get_uid(), "10001");
+    assert_eq!(server.hits("/fanyalogin"), 1);
+}
+
+#[test]
+fn qrcode_sign_with_refresh() {
+    let server = MockServer::new(Scenario::qrcode_with_refresh());
+    let db = temp_db();
+    let sessions = [
+        login(&server, Scenario::UNAME),
+        login(&server, "13800000001"),
+    ];
+    let Sign::QrCode(mut sign) = the_sign(&db, &sessions) else {
+        panic!("应为二维码签到。")
+    };
+    assert!(sign.is_refresh());
+    let stale_enc = Some(server.current_enc());
+    server.refresh_enc();
+    let results = DefaultQrCodeSignner::new(
+        &db,
+        &None,
+        &None,
+        &stale_enc,
+        #[cfg(any(target_os = "linux", target_os = "windows", target_os = "macos"))]
+        false,
+    )
+    .sign(&mut sign, sessions.iter())
+    .unwrap();
+    assert!(results.values().all(|r| !r.is_susses()));
+    let enc = Some(server.current_enc());
+    let results = DefaultQrCodeSignner::new(
+        &db,
+        &None,
+        &None,
+        &enc,
+        #[cfg(any(target_os = "linux", target_os = "windows", target_os = "macos"))]
+        false,
+    )
+    .sign(&mut sign, sessions.iter())
+    .unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results.values().all(|r| r.is_susses()));
+    assert!(server.is_signed("10001", Scenario::ACTIVE_ID));
+    assert!(server.is_signed("10002", Scenario::ACTIVE_ID));
+}
+
+#[test]
+fn location_out_of_range_falls_back_to_preset() {
+    let server = MockServer::new(Scenario::location_out_of_range());
+    let db = temp_db();
+    let sessions = [login(&server, Scenario::UNAME)];
+    let Sign::Location(mut sign) = the_sign(&db, &sessions) else {
+        panic!("应为位置签到。")
+    };
+    let far_away = Some("别处,116.0,39.0,1108".to_owned());
+    let results = DefaultLocationSignner::new(&db, &far_away)
+        .sign(&mut sign, sessions.iter())
+        .unwrap();
+    assert!(results[&sessions[0]].is_susses());
+    // 先以给定位置签到失败，再以预设位置签到成功。
+    assert_eq!(server.hits("/pptSign/stuSignajax"), 2);
+    assert!(server.is_signed("10001", Scenario::ACTIVE_ID));
+}
+
+#[test]
+fn location_out_of_range_without_preset() {
+    let mut scenario = Scenario::location_out_of_range();
+    if let MockSignKind::Location { location } = &mut scenario.activities[0].kind {
+        location.published = false;
+    }
+    let server = MockServer::new(scenario);
+    let db = temp_db();
+    let sessions = [login(&server, Scenario::UNAME)];
+    let Sign::Location(mut sign) = the_sign(&db, &sessions) else {
+        panic!("应为位置签到。")
+    };
+    let far_away = Some("别处,116.0,39.0,1108".to_owned());
+    let results = DefaultLocationSignner::new(&db, &far_away)
+        .sign(&mut sign, sessions.iter())
+        .unwrap();
+    assert!(!results[&sessions[0]].is_susses());
+    assert!(!server.is_signed("10001", Scenario::ACTIVE_ID));
+}
+
+#[test]
+fn validate_required() {
+    let server = MockServer::new(Scenario::validate_required());
+    let db = temp_db();
+    let sessions = [login(&server, Scenario::UNAME)];
+    let Sign::Location(mut sign) = the_sign(&db, &sessions) else {
+        panic!("应为位置签到。")
+    };
+    let results = DefaultLocationSignner::new(&db, &None)
+        .sign(&mut sign, sessions.iter())
+        .unwrap();
+    assert!(results[&sessions[0]].is_susses());
+    assert_eq!(server.hits("/captcha/check/verification/result"), 1);
+    assert!(server.is_signed("10001", Scenario::ACTIVE_ID));
+}
+
+#[test]
+fn photo_sign_uses_cloud_file() {
+    let scenario = Scenario::basic().with_activity(crate::MockActivity::new(
+        Scenario::ACTIVE_ID,
+        Scenario::COURSE_ID,
+        "拍照签到",
+        MockSignKind::Photo,
+    ));
+    let server = MockServer::new(scenario);
+    let db = temp_db();
+    let sessions = [login(&server, Scenario::UNAME)];
+    let Sign::Photo(mut sign) = the_sign(&db, &sessions) else {
+        panic!("应为拍照签到。")
+    };
+    let results = DefaultPhotoSignner::new(&None::<PathBuf>)
+        .sign(&mut sign, sessions.iter())
+        .unwrap();
+    assert!(results[&sessions[0]].is_susses());
+    assert!(server.is_signed("10001", Scenario::ACTIVE_ID));
+}
+
+#[test]
+fn signcode_sign() {
+    let scenario = Scenario::basic().with_activity(crate::MockActivity::new(
+        Scenario::ACTIVE_ID,
+        Scenario::COURSE_ID,
+        "签到码签到",
+        MockSignKind::Signcode {
+            signcode: "1234".to_owned(),
+        },
+    ));
+    let server = MockServer::new(scenario);
+    let db = temp_db();
+    let sessions = [login(&server, Scenario::UNAME)];
+    let Sign::Signcode(mut sign) = the_sign(&db, &sessions) else {
+        panic!("应为签到码签到。")
+    };
+    let results = DefaultGestureOrSigncodeSignner::new("4321")
+        .sign(&mut sign, sessions.iter())
+        .unwrap();
+    assert!(!results[&sessions[0]].is_susses());
+    let results = DefaultGestureOrSigncodeSignner::new("1234")
+        .sign(&mut sign, sessions.iter())
+        .unwrap();
+    assert!(results[&sessions[0]].is_susses());
+    assert!(sign
+        .get_sign_state(&sessions[0])
+        .is_ok_and(|s| s as i64 == 1));
+}
+
+#[test]
+fn retry_transient_failures() {
+    let scenario = Scenario::basic()
+        .with_activity(crate::MockActivity::new(
+            Scenario::ACTIVE_ID,
+            Scenario::COURSE_ID,
+            "签到码签到",
+            MockSignKind::Signcode {
+                signcode: "1234".to_owned(),
+            },
+        ))
+        .with_account(crate::MockAccount::new(
+            "13800000001",
+            Scenario::ENC_PASSWD,
+            "10002",
+            "测试用户二",
+        ));
+    let server = MockServer::new(scenario);
+    let db = temp_db();
+    let sessions = [login(&server, Scenario::UNAME)];
+    // 查询接口可以直接重试。
+    server.fail_requests("/v2/apis/active/student/activelist", 2);
+    let Sign::Signcode(mut sign) = the_sign(&db, &sessions) else {
+        panic!("应为签到码签到。")
+    };
+    assert_eq!(server.hits("/v2/apis/active/student/activelist"), 3);
+    // 签到请求已生效但响应丢失时，确认签到状态后不再重复提交。
+    server.lose_responses("/pptSign/stuSignajax", 1);
+    let results = DefaultGestureOrSigncodeSignner::new("1234")
+        .sign(&mut sign, sessions.iter())
+        .unwrap();
+    assert!(results[&sessions[0]].is_susses());
+    assert_eq!(server.hits("/pptSign/stuSignajax"), 1);
+    assert!(server.hits("/v2/apis/sign/getAttendInfo") >= 1);
+    // 签到请求未生效时，确认签到状态后重新提交。
+    let sessions = [login(&server, "13800000001")];
+    server.fail_requests("/pptSign/stuSignajax", 1);
+    let results = DefaultGestureOrSigncodeSignner::new("1234")
+        .sign(&mut sign, sessions.iter())
+        .unwrap();
+    assert!(results[&sessions[0]].is_susses());
+    assert!(server.is_signed("10002", Scenario::ACTIVE_ID));
+    assert_eq!(server.hits("/pptSign/stuSignajax"), 3);
+}
+
+/// 在当前线程中运行 [`Future`](std::future::Future), 测试异步接口不依赖特定的运行时。
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    struct Unparker(std::thread::Thread);
+    impl std::task::Wake for Unparker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark()
+        }
+    }
+    let waker = Arc::new(Unparker(std::thread::current())).into();
+    let mut cx = std::task::Context::from_waker(&waker);
+    let mut future = std::pin::pin!(future);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            std::task::Poll::Ready(output) => return output,
+            std::task::Poll::Pending => std::thread::park(),
+        }
+    }
+}
+
+#[test]
+fn async_qrcode_sign() {
+    let server = MockServer::new(Scenario::qrcode_with_refresh());
+    let db = temp_db();
+    block_on(async {
+        let mut sessions = Vec::new();
+        for uname in [Scenario::UNAME, "13800000001"] {
+            let transport = Arc::new(server.transport());
+            let session = Session::relogin_with_async(transport, uname, Scenario::ENC_PASSWD);
+            sessions.push(session.await.unwrap());
+        }
+        let courses = Course::get_courses_async(sessions.iter()).await.unwrap();
+        assert_eq!(courses.values().next().map(Vec::len), Some(2));
+        let table = db.add_table::<ExcludeTable>().unwrap();
+        let (valid, _, _) = Activity::get_all_activities_async(table, sessions.iter(), false)
+            .await
+            .unwrap();
+        let Sign::QrCode(mut sign) = valid.into_keys().next().unwrap().to_sign(&sessions[0]) else {
+            panic!("应为二维码签到。")
+        };
+        let enc = Some(server.current_enc());
+        let results = DefaultQrCodeSignner::new(
+            &db,
+            &None,
+            &None,
+            &enc,
+            #[cfg(any(target_os = "linux", target_os = "windows", target_os = "macos"))]
+            false,
+        )
+        .sign_async(&mut sign, sessions.iter())
+        .await
+        .unwrap();
+        assert!(results.values().all(|r| r.is_susses()));
+        let state = sign.get_sign_state_async(&sessions[1]).await.unwrap();
+        assert_eq!(state as i64, 1);
+    });
+    assert_eq!(server.signed_count(), 2);
+}
+
+#[test]
+fn over_http() {
+    let server = MockServer::new(Scenario::location_out_of_range());
+    let http = server.spawn().unwrap();
+    let location = MockLocation::new("测试地点", 116.397128, 39.916527, 50);
+    assert!(location.contains(116.397, 39.9166));
+    assert!(!location.contains(116.0, 39.0));
+    let agent = ureq::Agent::new();
+    let r = agent
+        .post(&format!("{}/fanyalogin", http.url()))
+        .send_form(&[
+            ("uname", Scenario::UNAME),
+            ("password", Scenario::ENC_PASSWD),
+        ])
+        .unwrap();
+    assert!(r.into_string().unwrap().contains("true"));
+    let r = agent
+        .get(&format!("{}/mooc/accountManage", http.url()))
+        .call()
+        .unwrap();
+    // 重新构造的响应保留原地址。
+    let url = r.get_url().to_owned();
+    let (r, body) = cxsign_transport::buffer_response(r).unwrap();
+    assert_eq!(cxsign_transport::response_url(&r), url);
+    assert!(String::from_utf8(body).unwrap().contains("测试用户"));
+    let transport = MockTransport::new(Arc::clone(&server));
+    assert_eq!(transport.get_server().hits("/fanyalogin"), 1);
+}
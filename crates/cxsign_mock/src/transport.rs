@@ -0,0 +1,74 @@
+use crate::MockServer;
+use cookie_store::CookieStore;
+use cxsign_error::Error;
+use cxsign_transport::{Request, Transport};
+use std::sync::{Arc, Mutex};
+use ureq::Response;
+use url::Url;
+
+/// 直接调用 [`MockServer`] 的 [`Transport`], 与 [`ureq::Agent`] 一样自行管理 Cookies.
+#[derive(Debug)]
+pub struct MockTransport {
+    server: Arc<MockServer>,
+    cookie_store: Mutex<CookieStore>,
+}
+
+impl MockTransport {
+    pub fn new(server: Arc<MockServer>) -> Self {
+        Self::with_cookie_store(server, CookieStore::default())
+    }
+    /// 使用已有的 Cookies, 如从文件中加载的 Cookies.
+    pub fn with_cookie_store(server: Arc<MockServer>, cookie_store: CookieStore) -> Self {
+        Self {
+            server,
+            cookie_store: Mutex::new(cookie_store),
+        }
+    }
+    pub fn get_server(&self) -> &Arc<MockServer> {
+        &self.server
+    }
+}
+
+impl Transport for MockTransport {
+    fn call(&self, mut request: Request) -> Result<Response, Error> {
+        let url = Url::parse(&request.url)
+            .map_err(|e| Error::ParseError(format!("无效的地址 `{}`：{e}.", request.url)))?;
+        {
+            let store = self.cookie_store.lock().unwrap_or_else(|e| e.into_inner());
+            let cookies = store
+                .get_request_values(&url)
+                .map(|(name, value)| format!("{name}={value}"))
+                .collect::<Vec<_>>();
+            if !cookies.is_empty() {
+                request
+                    .headers
+                    .push(("Cookie".to_owned(), cookies.join("; ")));
+            }
+        }
+        let response = self.server.handle(&request);
+        {
+            let mut store = self.cookie_store.lock().unwrap_or_else(|e| e.into_inner());
+            for (_, cookie) in response
+                .headers
+                .iter()
+                .filter(|(name, _)| name.eq_ignore_ascii_case("Set-Cookie"))
+            {
+                let _ = store.parse(cookie, &url);
+            }
+        }
+        if response.status >= 400 {
+            return Err(Error::HttpStatusError {
+                status: response.status,
+                url: request.url,
+            });
+        }
+        response.into_response()
+    }
+
+    fn cookie_store(&self) -> CookieStore {
+        self.cookie_store
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
@@ -1,55 +1,61 @@
 use crate::multipart::{Field, PreparedFields};
+use cxsign_transport::endpoint::{Endpoint, Host};
+use cxsign_transport::{Transport, TransportExt};
 use std::fs::File;
 use std::path::Path;
-use ureq::{Agent, Response};
+use ureq::Response;
 
 // 超星网盘页
-static PAN_CHAOXING: &str = "https://pan-yz.chaoxing.com";
+static PAN_CHAOXING: Endpoint = Endpoint::new(Host::PanYz, "");
 
-pub fn pan_chaoxing(client: &Agent) -> Result<Response, Box<ureq::Error>> {
-    Ok(client.get(PAN_CHAOXING).call()?)
+pub fn pan_chaoxing(client: &dyn Transport) -> Result<Response, cxsign_error::Error> {
+    client.get(&PAN_CHAOXING.url()).call()
 }
 
 // 网盘列表
-static PAN_LIST: &str = "https://pan-yz.chaoxing.com/opt/listres";
-
-pub fn pan_list(client: &Agent, parent_id: &str, enc: &str) -> Result<Response, Box<ureq::Error>> {
-    Ok(client
+static PAN_LIST: Endpoint = Endpoint::new(Host::PanYz, "/opt/listres");
+
+pub fn pan_list(
+    client: &dyn Transport,
+    parent_id: &str,
+    enc: &str,
+) -> Result<Response, cxsign_error::Error> {
+    client
         .post(&format!(
             "{PAN_LIST}?puid=0&shareid=0&parentId={parent_id}&page=1&size=50&enc={enc}"
         ))
-        .call()?)
+        .call()
 }
 
 // 获取超星云盘的 token
-static PAN_TOKEN: &str = "https://pan-yz.chaoxing.com/api/token/uservalid";
+static PAN_TOKEN: Endpoint = Endpoint::new(Host::PanYz, "/api/token/uservalid");
 
-pub fn pan_token(client: &Agent) -> Result<Response, Box<ureq::Error>> {
-    Ok(client.get(PAN_TOKEN).call()?)
+pub fn pan_token(client: &dyn Transport) -> Result<Response, cxsign_error::Error> {
+    client.get(&PAN_TOKEN.url()).call()
 }
 
 // 网盘上传接口
-static PAN_UPLOAD: &str = "https://pan-yz.chaoxing.com/upload";
+static PAN_UPLOAD: Endpoint = Endpoint::new(Host::PanYz, "/upload");
 
 pub fn pan_upload(
-    client: &Agent,
+    client: &dyn Transport,
     file: &File,
     uid: &str,
     token: &str,
     file_name: &str,
-) -> Result<Response, Box<ureq::Error>> {
+) -> Result<Response, cxsign_error::Error> {
     let file_ext: &Path = file_name.as_ref();
     let file_ext = file_ext.extension().and_then(|s| s.to_str()).unwrap_or("");
     let mime = mime_guess::from_ext(file_ext).first_or_octet_stream();
     let mut fields = Vec::<Field>::default();
     Field::add_stream(&mut fields, "file", file, Some(file_name), Some(mime));
     Field::add_text(&mut fields, "puid", uid);
-    let multipart = PreparedFields::from_fields(&mut fields).unwrap();
-    Ok(client
+    let multipart = PreparedFields::from_fields(&mut fields)?;
+    client
         .post(&format!("{PAN_UPLOAD}?_from=mobilelearn&_token={token}"))
         .set(
             "Content-Type",
             &format!("multipart/form-data; boundary={}", multipart.get_boundary()),
         )
-        .send(multipart)?)
+        .send(multipart)
 }
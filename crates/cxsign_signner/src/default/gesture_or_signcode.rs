@@ -1,4 +1,6 @@
 use crate::SignnerTrait;
+#[cfg(feature = "async")]
+use cxsign_activity::sign::{AsyncSignTrait, GestureOrSigncodeSignTrait};
 use cxsign_activity::sign::{GestureSign, SignResult, SignTrait, SigncodeSign};
 use cxsign_error::Error;
 use cxsign_user::Session;
@@ -63,3 +65,46 @@ impl SignnerTrait<SigncodeSign> for DefaultGestureOrSigncodeSignner {
         sign.pre_sign_and_sign(session)
     }
 }
+
+#[cfg(feature = "async")]
+impl DefaultGestureOrSigncodeSignner {
+    async fn sign_async_<'a, T, Sessions>(
+        &self,
+        sign: &mut T,
+        sessions: Sessions,
+    ) -> Result<HashMap<&'a Session, SignResult>, Error>
+    where
+        T: GestureOrSigncodeSignTrait + AsyncSignTrait,
+        Sessions: Iterator<Item = &'a Session>,
+    {
+        sign.set_signcode(self.0.clone());
+        let signs = sessions.map(|session| (session, sign.clone()));
+        crate::sign_concurrently(signs, |sign, session| sign.pre_sign_and_sign(session))
+            .await
+            .into_iter()
+            .map(|(session, r)| r.map(|r| (session, r)))
+            .collect()
+    }
+}
+
+#[cfg(feature = "async")]
+impl crate::AsyncSignnerTrait<GestureSign> for DefaultGestureOrSigncodeSignner {
+    async fn sign_async<'a, Sessions: Iterator<Item = &'a Session> + Clone>(
+        &mut self,
+        sign: &mut GestureSign,
+        sessions: Sessions,
+    ) -> Result<HashMap<&'a Session, SignResult>, Error> {
+        self.sign_async_(sign, sessions).await
+    }
+}
+
+#[cfg(feature = "async")]
+impl crate::AsyncSignnerTrait<SigncodeSign> for DefaultGestureOrSigncodeSignner {
+    async fn sign_async<'a, Sessions: Iterator<Item = &'a Session> + Clone>(
+        &mut self,
+        sign: &mut SigncodeSign,
+        sessions: Sessions,
+    ) -> Result<HashMap<&'a Session, SignResult>, Error> {
+        self.sign_async_(sign, sessions).await
+    }
+}
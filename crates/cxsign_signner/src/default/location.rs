@@ -16,6 +16,15 @@ impl<'a> DefaultLocationSignner<'a> {
     pub fn new(db: &'a DataBase, location_str: &'a Option<String>) -> Self {
         Self { db, location_str }
     }
+    fn set_location(&self, sign: &mut LocationSign) -> Result<(), Error> {
+        let location = crate::utils::get_locations(sign, self.db, self.location_str);
+        if location == Location::get_none_location() {
+            error!("未获取到位置信息，请检查位置列表或检查输入。");
+            return Err(Error::LocationError);
+        }
+        sign.set_location(location);
+        Ok(())
+    }
 }
 impl<'a> SignnerTrait<LocationSign> for DefaultLocationSignner<'a> {
     type ExtData<'e> = ();
@@ -25,12 +34,7 @@ impl<'a> SignnerTrait<LocationSign> for DefaultLocationSignner<'a> {
         sign: &mut LocationSign,
         sessions: Sessions,
     ) -> Result<HashMap<&'b Session, SignResult>, Error> {
-        let location = crate::utils::get_locations(sign, self.db, self.location_str);
-        if location == Location::get_none_location() {
-            error!("未获取到位置信息，请检查位置列表或检查输入。");
-            return Err(Error::LocationError);
-        }
-        sign.set_location(location.clone());
+        self.set_location(sign)?;
         let mut map = HashMap::new();
         for session in sessions {
             let r = Self::sign_single(sign, session, ())?;
@@ -43,3 +47,20 @@ impl<'a> SignnerTrait<LocationSign> for DefaultLocationSignner<'a> {
         sign.pre_sign_and_sign(session)
     }
 }
+
+#[cfg(feature = "async")]
+impl<'a> crate::AsyncSignnerTrait<LocationSign> for DefaultLocationSignner<'a> {
+    async fn sign_async<'b, Sessions: Iterator<Item = &'b Session> + Clone>(
+        &mut self,
+        sign: &mut LocationSign,
+        sessions: Sessions,
+    ) -> Result<HashMap<&'b Session, SignResult>, Error> {
+        self.set_location(sign)?;
+        let signs = sessions.map(|session| (session, sign.clone()));
+        crate::sign_concurrently(signs, |sign, session| Self::sign_single(sign, session, ()))
+            .await
+            .into_iter()
+            .map(|(session, r)| r.map(|r| (session, r)))
+            .collect()
+    }
+}
@@ -62,3 +62,38 @@ impl SignnerTrait<RawSign> for DefaultNormalOrRawSignner {
         sign_single_(sign, session)
     }
 }
+
+#[cfg(feature = "async")]
+async fn sign_async_<'a, Sessions: Iterator<Item = &'a Session>>(
+    sign: &RawSign,
+    sessions: Sessions,
+) -> Result<HashMap<&'a Session, SignResult>, Error> {
+    let signs = sessions.map(|session| (session, sign.clone()));
+    crate::sign_concurrently(signs, |sign, session| sign_single_(sign, session))
+        .await
+        .into_iter()
+        .map(|(session, r)| r.map(|r| (session, r)))
+        .collect()
+}
+
+#[cfg(feature = "async")]
+impl crate::AsyncSignnerTrait<NormalSign> for DefaultNormalOrRawSignner {
+    async fn sign_async<'a, Sessions: Iterator<Item = &'a Session> + Clone>(
+        &mut self,
+        sign: &mut NormalSign,
+        sessions: Sessions,
+    ) -> Result<HashMap<&'a Session, SignResult>, Error> {
+        sign_async_(sign.as_inner(), sessions).await
+    }
+}
+
+#[cfg(feature = "async")]
+impl crate::AsyncSignnerTrait<RawSign> for DefaultNormalOrRawSignner {
+    async fn sign_async<'a, Sessions: Iterator<Item = &'a Session> + Clone>(
+        &mut self,
+        sign: &mut RawSign,
+        sessions: Sessions,
+    ) -> Result<HashMap<&'a Session, SignResult>, Error> {
+        sign_async_(sign, sessions).await
+    }
+}
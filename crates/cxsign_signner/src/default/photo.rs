@@ -26,20 +26,16 @@ impl DefaultPhotoSignner {
         };
         Self { path }
     }
-}
-impl SignnerTrait<PhotoSign> for DefaultPhotoSignner {
-    type ExtData<'e> = ();
-
-    fn sign<'a, Sessions: Iterator<Item = &'a Session> + Clone>(
-        &mut self,
-        sign: &mut PhotoSign,
+    /// 获取各用户签到时使用的照片，顺序与 `sessions` 相同。
+    fn get_photos<'a, Sessions: Iterator<Item = &'a Session> + Clone>(
+        path: &Option<PathBuf>,
         sessions: Sessions,
-    ) -> Result<HashMap<&'a Session, SignResult>, Error> {
+    ) -> Result<Vec<Option<Photo>>, Error> {
         let mut pic_map = HashMap::new();
         let mut session_to_index = HashMap::new();
-        if let Some(pic) = self.path.as_ref() {
+        if let Some(pic) = path.as_ref() {
             for session in sessions.clone() {
-                let photo = Photo::get_from_file(session, pic);
+                let photo = Photo::get_from_file(session, pic)?;
                 pic_map.insert(0, photo);
                 for session in sessions.clone() {
                     session_to_index.insert(session, 0);
@@ -48,7 +44,13 @@ impl SignnerTrait<PhotoSign> for DefaultPhotoSignner {
         } else {
             let mut index = 0;
             for session in sessions.clone() {
-                let photo = Photo::default(session);
+                let photo = Photo::default(session).unwrap_or_else(|e| {
+                    warn!(
+                        "用户[{}]获取云盘中的照片失败！错误信息：{e}.",
+                        session.get_stu_name()
+                    );
+                    None
+                });
                 session_to_index.insert(session, index);
                 if let Some(photo) = photo {
                     pic_map.insert(index, photo);
@@ -61,20 +63,33 @@ impl SignnerTrait<PhotoSign> for DefaultPhotoSignner {
                 }
             }
         }
+        Ok(sessions
+            .map(|session| pic_map.get(&session_to_index[session]).cloned())
+            .collect())
+    }
+    fn no_photo(sign: &PhotoSign) -> SignResult {
+        SignResult::Fail {
+            msg: format!("拍照签到[{}]没有获取到有效的照片！", sign.as_inner().name),
+        }
+    }
+}
+impl SignnerTrait<PhotoSign> for DefaultPhotoSignner {
+    type ExtData<'e> = ();
+
+    fn sign<'a, Sessions: Iterator<Item = &'a Session> + Clone>(
+        &mut self,
+        sign: &mut PhotoSign,
+        sessions: Sessions,
+    ) -> Result<HashMap<&'a Session, SignResult>, Error> {
+        let photos = Self::get_photos(&self.path, sessions.clone())?;
         let mut map = HashMap::new();
-        for session in sessions {
-            let index = session_to_index[session];
-            if let Some(photo) = pic_map.get(&index).cloned() {
+        for (session, photo) in sessions.zip(photos) {
+            if let Some(photo) = photo {
                 sign.set_photo(photo);
                 let a = Self::sign_single(sign, session, ())?;
                 map.insert(session, a);
             } else {
-                map.insert(
-                    session,
-                    SignResult::Fail {
-                        msg: format!("拍照签到[{}]没有获取到有效的照片！", sign.as_inner().name),
-                    },
-                );
+                map.insert(session, Self::no_photo(sign));
             }
         }
         Ok(map)
@@ -88,3 +103,38 @@ impl SignnerTrait<PhotoSign> for DefaultPhotoSignner {
         sign.pre_sign_and_sign(session)
     }
 }
+
+#[cfg(feature = "async")]
+impl crate::AsyncSignnerTrait<PhotoSign> for DefaultPhotoSignner {
+    async fn sign_async<'a, Sessions: Iterator<Item = &'a Session> + Clone>(
+        &mut self,
+        sign: &mut PhotoSign,
+        sessions: Sessions,
+    ) -> Result<HashMap<&'a Session, SignResult>, Error> {
+        let (path, sessions_) = (
+            self.path.clone(),
+            sessions.clone().cloned().collect::<Vec<_>>(),
+        );
+        let photos =
+            cxsign_utils::unblock(move || Self::get_photos(&path, sessions_.iter())).await?;
+        let mut map = HashMap::new();
+        let mut signs = Vec::new();
+        for (session, photo) in sessions.zip(photos) {
+            if let Some(photo) = photo {
+                let mut sign = sign.clone();
+                sign.set_photo(photo);
+                signs.push((session, sign));
+            } else {
+                map.insert(session, Self::no_photo(sign));
+            }
+        }
+        for (session, r) in crate::sign_concurrently(signs.into_iter(), |sign, session| {
+            Self::sign_single(sign, session, ())
+        })
+        .await
+        {
+            map.insert(session, r?);
+        }
+        Ok(map)
+    }
+}
@@ -35,16 +35,7 @@ impl<'a> DefaultQrCodeSignner<'a> {
             precisely,
         }
     }
-}
-
-impl<'l> SignnerTrait<QrCodeSign> for DefaultQrCodeSignner<'l> {
-    type ExtData<'e> = ();
-
-    fn sign<'a, Sessions: Iterator<Item = &'a Session> + Clone>(
-        &mut self,
-        sign: &mut QrCodeSign,
-        sessions: Sessions,
-    ) -> Result<HashMap<&'a Session, SignResult>, Error> {
+    fn set_location_and_enc(&self, sign: &mut QrCodeSign) -> Result<(), Error> {
         let location =
             crate::utils::get_locations(sign.as_location_sign_mut(), self.db, self.location_str);
         if location == Location::get_none_location() {
@@ -56,6 +47,19 @@ impl<'l> SignnerTrait<QrCodeSign> for DefaultQrCodeSignner<'l> {
         #[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
         let enc = crate::utils::enc_gen(sign, self.path, self.enc)?;
         sign.set_enc(enc);
+        Ok(())
+    }
+}
+
+impl<'l> SignnerTrait<QrCodeSign> for DefaultQrCodeSignner<'l> {
+    type ExtData<'e> = ();
+
+    fn sign<'a, Sessions: Iterator<Item = &'a Session> + Clone>(
+        &mut self,
+        sign: &mut QrCodeSign,
+        sessions: Sessions,
+    ) -> Result<HashMap<&'a Session, SignResult>, Error> {
+        self.set_location_and_enc(sign)?;
         let mut map = HashMap::new();
         if sign.is_refresh() {
             let sessions = sessions.collect::<Vec<&'a Session>>();
@@ -96,3 +100,29 @@ impl<'l> SignnerTrait<QrCodeSign> for DefaultQrCodeSignner<'l> {
         unsafe { sign.sign_unchecked(session, r) }.map_err(Error::from)
     }
 }
+
+#[cfg(feature = "async")]
+impl<'l> crate::AsyncSignnerTrait<QrCodeSign> for DefaultQrCodeSignner<'l> {
+    async fn sign_async<'a, Sessions: Iterator<Item = &'a Session> + Clone>(
+        &mut self,
+        sign: &mut QrCodeSign,
+        sessions: Sessions,
+    ) -> Result<HashMap<&'a Session, SignResult>, Error> {
+        self.set_location_and_enc(sign)?;
+        let refresh = sign.is_refresh();
+        let signs = sessions.map(|session| (session, sign.clone()));
+        let results =
+            crate::sign_concurrently(signs, |sign, session| Self::sign_single(sign, session, ()))
+                .await;
+        let mut map = HashMap::new();
+        for (session, r) in results {
+            let r = if refresh {
+                r.unwrap_or_else(|e| SignResult::Fail { msg: e.to_string() })
+            } else {
+                r?
+            };
+            map.insert(session, r);
+        }
+        Ok(map)
+    }
+}
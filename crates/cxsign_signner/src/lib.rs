@@ -11,6 +11,8 @@ pub use default::*;
 use std::collections::HashMap;
 
 use cxsign_activity::sign::{SignResult, SignTrait};
+#[cfg(feature = "async")]
+use {cxsign_activity::sign::AsyncSignTrait, std::future::Future};
 
 pub trait SignnerTrait<T: SignTrait> {
     type ExtData<'e>;
@@ -25,3 +27,37 @@ pub trait SignnerTrait<T: SignTrait> {
         extra_data: Self::ExtData<'_>,
     ) -> Result<SignResult, Error>;
 }
+
+/// [`SignnerTrait`] 的异步版本。
+///
+/// 需要读取数据库等本地操作在当前任务中进行，各用户的签到请求则通过 [`cxsign_utils::unblock`] 在线程池中并发进行，
+/// 不依赖特定的异步运行时。
+#[cfg(feature = "async")]
+pub trait AsyncSignnerTrait<T: AsyncSignTrait>: SignnerTrait<T> {
+    fn sign_async<'a, Sessions: Iterator<Item = &'a Session> + Clone>(
+        &mut self,
+        sign: &mut T,
+        sessions: Sessions,
+    ) -> impl Future<Output = Result<HashMap<&'a Session, SignResult>, Error>>;
+}
+
+/// 为每个用户在 [`WorkerPool::blocking`](cxsign_utils::WorkerPool::blocking) 中调用 `sign_single`,
+/// 全部开始后再依次等待结果。同时进行的签到数不超过该线程池的最大并发数，其余的排队等待。
+#[cfg(feature = "async")]
+async fn sign_concurrently<'a, T: AsyncSignTrait>(
+    signs: impl Iterator<Item = (&'a Session, T)>,
+    sign_single: fn(&mut T, &Session) -> Result<SignResult, Error>,
+) -> Vec<(&'a Session, Result<SignResult, Error>)> {
+    let tasks = signs
+        .map(|(session, mut sign)| {
+            let session_ = session.clone();
+            let task = cxsign_utils::unblock(move || sign_single(&mut sign, &session_));
+            (session, task)
+        })
+        .collect::<Vec<_>>();
+    let mut results = Vec::new();
+    for (session, task) in tasks {
+        results.push((session, task.await));
+    }
+    results
+}
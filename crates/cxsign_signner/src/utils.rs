@@ -31,12 +31,17 @@ pub fn get_locations(
             }
 
             let table = LocationTable::from_ref(db);
-            if let Some(location) = table
-                .get_location_list_by_course(sign.as_inner().course.get_id())
-                .first()
-            {
+            let list_by_course = |course_id| {
+                table
+                    .get_location_list_by_course(course_id)
+                    .unwrap_or_else(|e| {
+                        warn!("位置读取失败！错误信息：{e}.");
+                        Vec::new()
+                    })
+            };
+            if let Some(location) = list_by_course(sign.as_inner().course.get_id()).first() {
                 location.clone()
-            } else if let Some(location) = table.get_location_list_by_course(-1).first() {
+            } else if let Some(location) = list_by_course(-1).first() {
                 location.clone()
             } else {
                 Location::get_none_location()
@@ -253,17 +258,24 @@ pub fn location_str_to_location(
     let table = LocationTable::from_ref(db);
     if let Some(ref location_str) = location_str {
         let location_str = location_str.trim();
+        let warn_db_error = |e: Error| warn!("位置读取失败！错误信息：{e}.");
         if let Ok(location) = location_str.parse() {
             Ok(location)
-        } else if let Some(location) = table.get_location_by_alias(location_str) {
+        } else if let Some(location) = table
+            .get_location_by_alias(location_str)
+            .map_err(warn_db_error)
+            .ok()
+            .flatten()
+        {
+            Ok(location)
+        } else if let Ok(location_id) = location_str.parse()
+            && let Some((_, location)) = table
+                .get_location(location_id)
+                .map_err(warn_db_error)
+                .ok()
+                .flatten()
+        {
             Ok(location)
-        } else if let Ok(location_id) = location_str.parse() {
-            if table.has_location(location_id) {
-                let (_, location) = table.get_location(location_id);
-                Ok(location)
-            } else {
-                Err(location_str.to_owned())
-            }
         } else {
             Err(location_str.to_owned())
         }
@@ -0,0 +1,836 @@
+//! 数据库的备份与恢复。
+//!
+//! 备份文件以固定的文件头开始，之后为 zlib 压缩的 JSON 文档，包含清单（[`BackupManifest`]）和数据库的完整副本。
+//! 账号、会话（Cookies）、位置等均保存在数据库中，因此一并包含在内。敏感数据保持原样，
+//! 数据库已加密时，恢复后需要使用备份时的口令或密钥文件解锁。
+
+use crate::crypto::random_bytes;
+use crate::sql::{
+    AccountTable, DataBase, DataBaseSource, DataBaseTableTrait, KeyTable, SCHEMA_VERSION_TABLE_NAME,
+};
+use base64::Engine;
+use cxsign_error::Error;
+use log::info;
+use serde::{Deserialize, Serialize};
+use sqlite::{Connection, OpenFlags, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::OpenOptions;
+use std::ops::Deref;
+use std::path::PathBuf;
+
+/// 备份文件的版本。
+pub const BACKUP_VERSION: i64 = 1;
+/// 备份文件的文件头。
+const MAGIC: &[u8] = b"CXSIGN-BACKUP\n";
+/// 以本地编号为键的数据表：(数据表, 编号列, 引用该编号的数据表, 引用列)。
+///
+/// 合并时按内容而不是编号识别同一行，新增的行重新编号，并相应地修改引用。
+const RENUMBERED_TABLES: &[(&str, &str, &str, &str)] = &[("location", "lid", "alias", "lid")];
+
+/// 备份的清单。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub version: i64,
+    /// 创建备份的程序版本。
+    pub program_version: String,
+    /// 创建时间（Unix 时间戳，秒）。
+    pub created_at: i64,
+    /// 数据库中的敏感数据是否已加密。
+    pub encrypted: bool,
+    /// 各数据表的行数。
+    pub tables: BTreeMap<String, usize>,
+    /// 保存的账号。
+    pub accounts: Vec<String>,
+    /// 数据库副本的大小（字节）。
+    pub database_size: usize,
+    /// 数据库副本的 SHA-256 摘要（十六进制）。
+    pub database_sha256: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BackupDocument {
+    manifest: BackupManifest,
+    /// Base64 编码的数据库副本。
+    database: String,
+}
+
+/// 备份，包括清单和数据库的副本。
+#[derive(Debug, Clone)]
+pub struct Backup {
+    manifest: BackupManifest,
+    database: Vec<u8>,
+}
+
+impl Backup {
+    pub fn manifest(&self) -> &BackupManifest {
+        &self.manifest
+    }
+    /// 编码为备份文件的内容。
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let document = BackupDocument {
+            manifest: self.manifest.clone(),
+            database: base64::engine::general_purpose::STANDARD.encode(&self.database),
+        };
+        let text = serde_json::to_string(&document)
+            .map_err(|e| Error::ParseError(format!("备份序列化失败：{e}")))?;
+        let mut data = MAGIC.to_vec();
+        data.extend(cxsign_utils::zlib_encode(&text));
+        Ok(data)
+    }
+    /// 解析备份文件，并校验清单与数据库副本是否一致。
+    pub fn from_bytes(data: &[u8]) -> Result<Self, Error> {
+        let invalid = |reason: String| Error::ParseError(format!("备份文件无效：{reason}"));
+        let data = data
+            .strip_prefix(MAGIC)
+            .ok_or_else(|| invalid("文件头不正确".to_owned()))?;
+        let text =
+            cxsign_utils::try_zlib_decode(data).map_err(|e| invalid(format!("解压失败：{e}")))?;
+        let document: BackupDocument =
+            serde_json::from_str(&text).map_err(|e| invalid(format!("内容解析失败：{e}")))?;
+        let manifest = document.manifest;
+        if manifest.version > BACKUP_VERSION {
+            return Err(invalid(format!(
+                "备份的版本（{}）高于程序支持的版本（{BACKUP_VERSION}），请更新程序。",
+                manifest.version
+            )));
+        }
+        let database = base64::engine::general_purpose::STANDARD
+            .decode(document.database)
+            .map_err(|e| invalid(format!("数据库副本解码失败：{e}")))?;
+        if database.len() != manifest.database_size || sha256(&database) != manifest.database_sha256
+        {
+            return Err(invalid("数据库副本已损坏".to_owned()));
+        }
+        let backup = Self { manifest, database };
+        // 数据库副本可以打开（结构版本不高于程序支持的版本），且各数据表的行数与清单一致。
+        let db = backup.open_database()?;
+        let counts = count_rows(&db)?;
+        let consistent = counts.iter().all(|(table, count)| {
+            backup
+                .manifest
+                .tables
+                .get(table)
+                .copied()
+                .unwrap_or_default()
+                == *count
+        }) && backup
+            .manifest
+            .tables
+            .keys()
+            .all(|table| counts.contains_key(table));
+        if !consistent {
+            return Err(invalid("数据表的行数与清单不符".to_owned()));
+        }
+        drop(db);
+        Ok(backup)
+    }
+    /// 将数据库副本写入临时文件并打开，打开时会升级其中的数据表。
+    fn open_database(&self) -> Result<BackupDataBase, Error> {
+        let file = TempFile::new()?;
+        std::fs::write(&file.path, &self.database)?;
+        let db = DataBase::open(&file.path)?;
+        Ok(BackupDataBase { db, _file: file })
+    }
+}
+
+/// 空的临时文件，位于新建的随机命名的文件夹中，离开作用域时连同文件夹一并删除。
+///
+/// 文件夹仅当前用户可以访问，且文件夹和文件均要求此前不存在，以免其他用户预先创建同名的文件或链接。
+struct TempFile {
+    dir: PathBuf,
+    path: PathBuf,
+}
+
+impl TempFile {
+    fn new() -> Result<Self, Error> {
+        let mut random = [0; 8];
+        random_bytes(&mut random)?;
+        let dir =
+            std::env::temp_dir().join(format!("cxsign_backup_{:016x}", u64::from_le_bytes(random)));
+        let mut builder = std::fs::DirBuilder::new();
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+        builder.create(&dir)?;
+        let file = Self {
+            path: dir.join("database.db"),
+            dir,
+        };
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&file.path)?;
+        Ok(file)
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// 打开的数据库副本。先关闭数据库再删除文件。
+struct BackupDataBase {
+    db: DataBase,
+    _file: TempFile,
+}
+
+impl Deref for BackupDataBase {
+    type Target = DataBase;
+
+    fn deref(&self) -> &Self::Target {
+        &self.db
+    }
+}
+
+/// 恢复方式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RestoreMode {
+    /// 添加备份中新增的数据，以备份中的数据更新已有的数据，保留其他数据。
+    ///
+    /// 要求备份与当前数据库的加密设置相同。
+    #[default]
+    Merge,
+    /// 以备份替换当前的所有数据，包括加密设置。
+    Replace,
+}
+
+/// 恢复备份时单个数据表的变化。行以键（如账号）或内容表示。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableDiff {
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub removed: Vec<String>,
+    /// 未变化的行数。
+    pub unchanged: usize,
+}
+
+impl TableDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// 恢复备份时数据库的变化，参见 [`DataBase::diff_backup`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RestoreDiff {
+    pub tables: BTreeMap<String, TableDiff>,
+}
+
+impl RestoreDiff {
+    pub fn is_empty(&self) -> bool {
+        self.tables.values().all(TableDiff::is_empty)
+    }
+}
+
+/// 数据表的内容。
+struct TableRows {
+    columns: Vec<String>,
+    rows: Vec<Vec<Value>>,
+}
+
+/// 恢复时对单个数据表的修改。
+struct TablePlan {
+    table: String,
+    /// 当前数据库中没有该数据表时，用于创建的语句和数据表的版本。
+    create: Option<(String, Option<i64>)>,
+    /// 替换模式下先清空数据表。
+    clear: bool,
+    columns: Vec<String>,
+    /// 需要写入的行，键相同的行会被替换。
+    rows: Vec<Vec<Value>>,
+    diff: TableDiff,
+}
+
+impl TablePlan {
+    fn apply(&self, db: &DataBase) -> Result<(), Error> {
+        if let Some((sql, version)) = &self.create {
+            db.execute(sql)?;
+            if let Some(version) = version {
+                db.set_schema_version(&self.table, *version)?;
+            }
+        }
+        if self.clear {
+            db.execute(format!("DELETE FROM {};", self.table))?;
+        }
+        if self.rows.is_empty() {
+            return Ok(());
+        }
+        let mut query = db.prepare(format!(
+            "INSERT OR REPLACE INTO {}({}) values({});",
+            self.table,
+            self.columns.join(","),
+            vec!["?"; self.columns.len()].join(",")
+        ))?;
+        for row in &self.rows {
+            query.reset()?;
+            query.bind(&row[..])?;
+            query.next()?;
+        }
+        Ok(())
+    }
+}
+
+fn sha256(data: &[u8]) -> String {
+    hex::encode(ring::digest::digest(&ring::digest::SHA256, data))
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Integer(i) => i.to_string(),
+        Value::Float(f) => f.to_string(),
+        Value::Binary(b) => hex::encode(b),
+        Value::Null => "NULL".to_owned(),
+    }
+}
+
+fn row_to_string(row: &[Value]) -> String {
+    row.iter()
+        .map(value_to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// 以 `[编号] 内容` 的形式描述重新编号的数据表中的行。
+fn numbered_row_to_string(row: &[Value], id_index: usize) -> String {
+    let content = row
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != id_index)
+        .map(|(_, v)| value_to_string(v))
+        .collect::<Vec<_>>();
+    format!(
+        "[{}] {}",
+        value_to_string(&row[id_index]),
+        content.join(",")
+    )
+}
+
+/// 需要备份的数据表，不包括 SQLite 内部的数据表和版本记录。
+fn user_tables(db: &Connection) -> Result<Vec<String>, Error> {
+    let mut query = db.prepare(format!(
+        "SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%' \
+        AND name!='{SCHEMA_VERSION_TABLE_NAME}' ORDER BY name;"
+    ))?;
+    let mut tables = Vec::new();
+    while let sqlite::State::Row = query.next()? {
+        tables.push(query.read(0)?);
+    }
+    Ok(tables)
+}
+
+fn count_rows(db: &Connection) -> Result<BTreeMap<String, usize>, Error> {
+    let mut counts = BTreeMap::new();
+    for table in user_tables(db)? {
+        let mut query = db.prepare(format!("SELECT count(*) FROM {table};"))?;
+        query.next()?;
+        counts.insert(table, query.read::<i64, _>(0)? as usize);
+    }
+    Ok(counts)
+}
+
+fn read_table(db: &Connection, table: &str) -> Result<TableRows, Error> {
+    let mut query = db.prepare(format!("SELECT * FROM {table};"))?;
+    let columns = query.column_names().to_vec();
+    let mut rows = Vec::new();
+    while let sqlite::State::Row = query.next()? {
+        rows.push(
+            (0..columns.len())
+                .map(|i| query.read::<Value, _>(i))
+                .collect::<Result<Vec<_>, _>>()?,
+        );
+    }
+    Ok(TableRows { columns, rows })
+}
+
+/// 具有唯一约束的单个列，用于识别同一行。
+fn key_column(db: &Connection, table: &str) -> Result<Option<String>, Error> {
+    let mut indexes = db.prepare(format!(
+        "SELECT name FROM pragma_index_list('{table}') WHERE \"unique\"=1;"
+    ))?;
+    while let sqlite::State::Row = indexes.next()? {
+        let index = indexes.read::<String, _>(0)?;
+        let mut columns = db.prepare(format!("SELECT name FROM pragma_index_info('{index}');"))?;
+        let mut names = Vec::new();
+        while let sqlite::State::Row = columns.next()? {
+            names.push(columns.read::<String, _>(0)?);
+        }
+        if let [name] = &names[..] {
+            return Ok(Some(name.clone()));
+        }
+    }
+    Ok(None)
+}
+
+fn create_sql(db: &Connection, table: &str) -> Result<String, Error> {
+    let mut query = db.prepare("SELECT sql FROM sqlite_master WHERE type='table' AND name=?;")?;
+    query.bind((1, table))?;
+    query.next()?;
+    Ok(query.read(0)?)
+}
+
+fn as_id(value: &Value) -> Option<i64> {
+    match value {
+        Value::Integer(id) => Some(*id),
+        _ => None,
+    }
+}
+
+impl DataBase {
+    /// 创建备份。数据库中的敏感数据保持原样，无需解锁。
+    pub fn backup(&self) -> Result<Backup, Error> {
+        let file = TempFile::new()?;
+        // 内存数据库的 VFS 不会写入磁盘，因此在使用默认 VFS 的连接中附加数据库后再导出。
+        let helper = Connection::open_with_flags(
+            ":memory:",
+            OpenFlags::new().with_create().with_read_write().with_uri(),
+        )?;
+        let source = match self.source() {
+            DataBaseSource::File(path) => path.to_string_lossy().into_owned(),
+            DataBaseSource::Memory(name) => name.clone(),
+        };
+        let mut query = helper.prepare("ATTACH DATABASE ? AS src;")?;
+        query.bind((1, source.as_str()))?;
+        query.next()?;
+        drop(query);
+        let mut query = helper.prepare("VACUUM src INTO ?;")?;
+        query.bind((1, file.path.to_string_lossy().as_ref()))?;
+        query.next()?;
+        drop(query);
+        drop(helper);
+        let database = std::fs::read(&file.path)?;
+        // 从副本中统计，以免与其他进程的写入不一致。
+        let snapshot = sqlite::open(&file.path)?;
+        let tables = count_rows(&snapshot)?;
+        let mut accounts = Vec::new();
+        if tables.contains_key(AccountTable::TABLE_NAME) {
+            let mut query =
+                snapshot.prepare(format!("SELECT uname FROM {};", AccountTable::TABLE_NAME))?;
+            while let sqlite::State::Row = query.next()? {
+                accounts.push(query.read::<String, _>(0)?);
+            }
+        }
+        accounts.sort();
+        let encrypted = tables
+            .get(KeyTable::TABLE_NAME)
+            .is_some_and(|count| *count > 0);
+        drop(snapshot);
+        let manifest = BackupManifest {
+            version: BACKUP_VERSION,
+            program_version: env!("CARGO_PKG_VERSION").to_owned(),
+            created_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or_default(),
+            encrypted,
+            tables,
+            accounts,
+            database_size: database.len(),
+            database_sha256: sha256(&database),
+        };
+        Ok(Backup { manifest, database })
+    }
+    /// 列出恢复备份时将要进行的修改，不修改数据库。
+    pub fn diff_backup(&self, backup: &Backup, mode: RestoreMode) -> Result<RestoreDiff, Error> {
+        let backup_db = backup.open_database()?;
+        let plans = self.plan_restore(&backup_db, mode)?;
+        Ok(RestoreDiff {
+            tables: plans
+                .into_iter()
+                .map(|plan| (plan.table, plan.diff))
+                .collect(),
+        })
+    }
+    /// 恢复备份，所有修改在同一事务中进行，返回所做的修改。
+    ///
+    /// 替换模式下加密设置发生变化时，需要使用备份时的口令或密钥文件重新解锁。
+    pub fn restore(&self, backup: &Backup, mode: RestoreMode) -> Result<RestoreDiff, Error> {
+        let backup_db = backup.open_database()?;
+        let plans = self.plan_restore(&backup_db, mode)?;
+        self.transaction(|| {
+            for plan in &plans {
+                plan.apply(self)?;
+            }
+            Ok(())
+        })?;
+        let key_changed = plans
+            .iter()
+            .any(|plan| plan.table == KeyTable::TABLE_NAME && !plan.diff.is_empty());
+        if key_changed {
+            self.set_key(None);
+            info!("加密设置已随备份恢复，请使用备份时的口令或密钥文件解锁。");
+        }
+        Ok(RestoreDiff {
+            tables: plans
+                .into_iter()
+                .map(|plan| (plan.table, plan.diff))
+                .collect(),
+        })
+    }
+    fn plan_restore(
+        &self,
+        backup_db: &DataBase,
+        mode: RestoreMode,
+    ) -> Result<Vec<TablePlan>, Error> {
+        let local_tables = user_tables(self)?;
+        let backup_tables = user_tables(backup_db)?;
+        // 没有密钥表的数据库视为未加密。
+        let key_rows = |db: &Connection, tables: &[String]| -> Result<_, Error> {
+            Ok(if tables.iter().any(|t| t == KeyTable::TABLE_NAME) {
+                read_table(db, KeyTable::TABLE_NAME)?.rows
+            } else {
+                Vec::new()
+            })
+        };
+        if mode == RestoreMode::Merge
+            && key_rows(self, &local_tables)? != key_rows(backup_db, &backup_tables)?
+        {
+            return Err(Error::ConfigError(
+                "备份与当前数据库的加密设置不同，无法合并。请使用替换模式，或先将两者的加密设置调整一致"
+                    .to_owned(),
+            ));
+        }
+        // 重新编号的数据表需要在引用它的数据表之前处理。
+        let mut tables = local_tables
+            .iter()
+            .chain(&backup_tables)
+            .cloned()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        tables.sort_by_key(|table| {
+            (
+                !RENUMBERED_TABLES.iter().any(|(t, ..)| t == table),
+                table.clone(),
+            )
+        });
+        // (数据表, 列) -> (备份中的编号 -> 合并后的编号)
+        let mut id_maps = HashMap::<(&str, &str), HashMap<i64, i64>>::new();
+        let mut plans = Vec::new();
+        for table in tables {
+            if mode == RestoreMode::Merge && table == KeyTable::TABLE_NAME {
+                continue;
+            }
+            let local = if local_tables.contains(&table) {
+                Some(read_table(self, &table)?)
+            } else {
+                None
+            };
+            let Some(backup) = (if backup_tables.contains(&table) {
+                Some(read_table(backup_db, &table)?)
+            } else {
+                None
+            }) else {
+                // 仅在替换模式下删除备份中没有的数据表中的数据。
+                let local = local.expect("数据表至少存在于其中之一。");
+                let diff = TableDiff {
+                    removed: match mode {
+                        RestoreMode::Merge => Vec::new(),
+                        RestoreMode::Replace => {
+                            local.rows.iter().map(|r| row_to_string(r)).collect()
+                        }
+                    },
+                    unchanged: match mode {
+                        RestoreMode::Merge => local.rows.len(),
+                        RestoreMode::Replace => 0,
+                    },
+                    ..Default::default()
+                };
+                plans.push(TablePlan {
+                    clear: mode == RestoreMode::Replace,
+                    table,
+                    create: None,
+                    columns: local.columns,
+                    rows: Vec::new(),
+                    diff,
+                });
+                continue;
+            };
+            let create = if local.is_none() {
+                Some((
+                    create_sql(backup_db, &table)?,
+                    backup_db.get_schema_version(&table)?,
+                ))
+            } else {
+                None
+            };
+            // 将当前数据库中的行按备份中的列排列。
+            let local_rows = match &local {
+                Some(local) => {
+                    let indexes = backup
+                        .columns
+                        .iter()
+                        .map(|column| {
+                            local
+                                .columns
+                                .iter()
+                                .position(|c| c == column)
+                                .ok_or_else(|| {
+                                    Error::ConfigError(format!(
+                                        "数据表 {table} 中没有备份中的列 `{column}`, 请更新程序。"
+                                    ))
+                                })
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    local
+                        .rows
+                        .iter()
+                        .map(|row| indexes.iter().map(|i| row[*i].clone()).collect())
+                        .collect()
+                }
+                None => Vec::new(),
+            };
+            let column_index = |name: &str| backup.columns.iter().position(|c| c == name);
+            let mut backup_rows = backup.rows;
+            let mut plan = TablePlan {
+                clear: mode == RestoreMode::Replace,
+                table: table.clone(),
+                create,
+                columns: backup.columns.clone(),
+                rows: Vec::new(),
+                diff: TableDiff::default(),
+            };
+            if mode == RestoreMode::Merge {
+                // 修改对重新编号的行的引用。
+                for (t, c, referencing, ref_column) in RENUMBERED_TABLES {
+                    let (Some(id_map), Some(i)) =
+                        (id_maps.get(&(*t, *c)), column_index(ref_column))
+                    else {
+                        continue;
+                    };
+                    if *referencing != table {
+                        continue;
+                    }
+                    for row in &mut backup_rows {
+                        if let Some(id) = as_id(&row[i]).and_then(|id| id_map.get(&id)) {
+                            row[i] = Value::Integer(*id);
+                        }
+                    }
+                }
+            }
+            let renumbered = RENUMBERED_TABLES
+                .iter()
+                .find(|(t, ..)| *t == table)
+                .and_then(|(t, c, ..)| column_index(c).map(|i| (*t, *c, i)));
+            match (mode, renumbered) {
+                (RestoreMode::Merge, Some((t, c, id_index))) => {
+                    let content = |row: &[Value]| {
+                        row.iter()
+                            .enumerate()
+                            .filter(|(i, _)| *i != id_index)
+                            .map(|(_, v)| v.clone())
+                            .collect::<Vec<_>>()
+                    };
+                    let mut used_ids = local_rows
+                        .iter()
+                        .filter_map(|row: &Vec<Value>| as_id(&row[id_index]))
+                        .collect::<HashSet<_>>();
+                    let mut id_map = HashMap::new();
+                    let mut next_id = 0;
+                    for mut row in backup_rows {
+                        let backup_id = as_id(&row[id_index]);
+                        let row_content = content(&row);
+                        if let Some(local_row) = local_rows
+                            .iter()
+                            .find(|local| content(local) == row_content)
+                        {
+                            if let (Some(backup_id), Some(local_id)) =
+                                (backup_id, as_id(&local_row[id_index]))
+                            {
+                                id_map.insert(backup_id, local_id);
+                            }
+                            plan.diff.unchanged += 1;
+                            continue;
+                        }
+                        while used_ids.contains(&next_id) {
+                            next_id += 1;
+                        }
+                        used_ids.insert(next_id);
+                        if let Some(backup_id) = backup_id {
+                            id_map.insert(backup_id, next_id);
+                        }
+                        row[id_index] = Value::Integer(next_id);
+                        plan.diff.added.push(numbered_row_to_string(&row, id_index));
+                        plan.rows.push(row);
+                    }
+                    id_maps.insert((t, c), id_map);
+                }
+                _ => {
+                    let key_index = key_column(backup_db, &table)?.and_then(|k| column_index(&k));
+                    let describe = |row: &[Value]| match renumbered {
+                        Some((.., id_index)) => numbered_row_to_string(row, id_index),
+                        None => match key_index {
+                            Some(i) => value_to_string(&row[i]),
+                            None => row_to_string(row),
+                        },
+                    };
+                    let same_key = |a: &[Value], b: &[Value]| match key_index {
+                        Some(i) => a[i] == b[i],
+                        None => a == b,
+                    };
+                    for row in &local_rows {
+                        if mode == RestoreMode::Replace
+                            && !backup_rows.iter().any(|b| same_key(b, row))
+                        {
+                            plan.diff.removed.push(describe(row));
+                        }
+                    }
+                    for row in backup_rows {
+                        match local_rows.iter().find(|local| same_key(local, &row)) {
+                            Some(local) if *local == row => {
+                                plan.diff.unchanged += 1;
+                                // 替换模式下数据表会被清空，需要重新写入。
+                                if mode == RestoreMode::Replace {
+                                    plan.rows.push(row);
+                                }
+                            }
+                            Some(_) => {
+                                plan.diff.updated.push(describe(&row));
+                                plan.rows.push(row);
+                            }
+                            None => {
+                                plan.diff.added.push(describe(&row));
+                                plan.rows.push(row);
+                            }
+                        }
+                    }
+                }
+            }
+            plans.push(plan);
+        }
+        Ok(plans)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crypto::KeySource;
+    use crate::{
+        AccountTable, AliasTable, Backup, DataBase, DataBaseTableTrait, ExcludeTable, KeyTable,
+        RestoreMode,
+    };
+    use cxsign_error::Error;
+
+    const LOCATION_TABLE: &str =
+        "CREATE TABLE location (lid INTEGER UNIQUE NOT NULL,addr TEXT NOT NULL);";
+
+    fn add_account(db: &DataBase, uname: &str, pwd: &str) {
+        db.add_table::<AccountTable>()
+            .unwrap()
+            .add_account_or(uname, pwd, "name", AccountTable::update_account)
+            .unwrap();
+    }
+
+    fn add_location(db: &DataBase, lid: i64, addr: &str, alias: &str) {
+        db.execute(format!(
+            "INSERT INTO location(lid,addr) values({lid},'{addr}');"
+        ))
+        .unwrap();
+        db.add_table::<AliasTable>()
+            .unwrap()
+            .add_alias_or(alias, lid, AliasTable::update_alias)
+            .unwrap();
+    }
+
+    fn source() -> DataBase {
+        let db = DataBase::in_memory().unwrap();
+        add_account(&db, "a", "pwd_a");
+        add_account(&db, "b", "pwd_b");
+        db.add_table::<ExcludeTable>()
+            .unwrap()
+            .add_exclude(1)
+            .unwrap();
+        db.execute(LOCATION_TABLE).unwrap();
+        add_location(&db, 0, "教学楼", "教室");
+        db
+    }
+
+    #[test]
+    fn backup_round_trip_and_merge() {
+        let backup = source().backup().unwrap();
+        assert_eq!(backup.manifest().accounts, ["a", "b"]);
+        assert_eq!(backup.manifest().tables["location"], 1);
+        let data = backup.to_bytes().unwrap();
+        let backup = Backup::from_bytes(&data).unwrap();
+
+        let db = DataBase::in_memory().unwrap();
+        add_account(&db, "a", "old");
+        add_account(&db, "c", "pwd_c");
+        db.execute(LOCATION_TABLE).unwrap();
+        add_location(&db, 0, "图书馆", "书库");
+        let diff = db.diff_backup(&backup, RestoreMode::Merge).unwrap();
+        let accounts = &diff.tables["account"];
+        assert_eq!(
+            (&accounts.added, &accounts.updated),
+            (&vec!["b".to_owned()], &vec!["a".to_owned()])
+        );
+        assert!(accounts.removed.is_empty());
+        // 仅列出修改时不修改数据库。
+        assert!(!db
+            .add_table::<AccountTable>()
+            .unwrap()
+            .has_account("b")
+            .unwrap());
+
+        db.restore(&backup, RestoreMode::Merge).unwrap();
+        let table = db.add_table::<AccountTable>().unwrap();
+        assert_eq!(table.get_account("a").unwrap().unwrap().0.enc_pwd, "pwd_a");
+        assert!(table.has_account("b").unwrap() && table.has_account("c").unwrap());
+        // 备份中的位置重新编号，别名随之修改。
+        let alias_table = db.add_table::<AliasTable>().unwrap();
+        assert_eq!(alias_table.get_location_id("书库").unwrap(), Some(0));
+        assert_eq!(alias_table.get_location_id("教室").unwrap(), Some(1));
+        assert!(db
+            .diff_backup(&backup, RestoreMode::Merge)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn replace_and_validation() {
+        let backup = source().backup().unwrap();
+        let db = DataBase::in_memory().unwrap();
+        add_account(&db, "c", "pwd_c");
+        let diff = db.diff_backup(&backup, RestoreMode::Replace).unwrap();
+        assert_eq!(diff.tables["account"].removed, ["c"]);
+        assert_eq!(diff.tables["location"].added, ["[0] 教学楼"]);
+        db.restore(&backup, RestoreMode::Replace).unwrap();
+        let table = db.add_table::<AccountTable>().unwrap();
+        assert!(!table.has_account("c").unwrap() && table.has_account("a").unwrap());
+        assert_eq!(
+            db.add_table::<ExcludeTable>()
+                .unwrap()
+                .get_excludes()
+                .unwrap(),
+            [1]
+        );
+        assert!(db
+            .diff_backup(&backup, RestoreMode::Replace)
+            .unwrap()
+            .is_empty());
+
+        // 加密设置不同时无法合并。
+        let encrypted = DataBase::in_memory().unwrap();
+        encrypted
+            .rekey(Some(&KeySource::Passphrase("口令".to_owned())))
+            .unwrap();
+        assert!(encrypted.restore(&backup, RestoreMode::Merge).is_err());
+        // 没有密钥表的数据库视为未加密。
+        let without_key = DataBase::in_memory().unwrap();
+        without_key
+            .execute(format!("DROP TABLE {};", KeyTable::TABLE_NAME))
+            .unwrap();
+        assert!(without_key.diff_backup(&backup, RestoreMode::Merge).is_ok());
+        assert!(matches!(
+            without_key.diff_backup(&encrypted.backup().unwrap(), RestoreMode::Merge),
+            Err(Error::ConfigError(_))
+        ));
+
+        let mut data = backup.to_bytes().unwrap();
+        assert!(Backup::from_bytes(&data[1..]).is_err());
+        let last = data.len() - 1;
+        data[last] ^= 0xff;
+        assert!(Backup::from_bytes(&data).is_err());
+    }
+}
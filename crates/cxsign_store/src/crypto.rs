@@ -0,0 +1,142 @@
+//! 数据库中敏感数据（账号密码、Cookies 和含有代理地址的会话配置）的加密。
+//!
+//! 密钥由用户提供的口令或密钥文件经 PBKDF2-HMAC-SHA256 派生，数据使用 AES-256-GCM 加密。
+//! 与登录时对密码的编码（[`cxsign_login::des_enc`]）无关，后者的密钥是公开的，不能起到保护作用。
+
+use base64::Engine;
+use cxsign_error::Error;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::fmt::{Debug, Formatter};
+use std::num::NonZeroU32;
+use std::path::PathBuf;
+
+/// 加密后的数据的前缀，不带该前缀的数据视为明文。
+const PREFIX: &str = "enc:v1:";
+/// 派生密钥时的迭代次数。
+pub const DEFAULT_ITERATIONS: u32 = 100_000;
+/// 用于验证密钥是否正确的明文。
+pub(crate) const CHECK_PLAINTEXT: &str = "cxsign";
+
+/// 密钥来源。
+#[derive(Clone)]
+pub enum KeySource {
+    /// 口令。
+    Passphrase(String),
+    /// 密钥文件，文件的全部内容即为口令。
+    KeyFile(PathBuf),
+}
+
+impl Debug for KeySource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeySource::Passphrase(_) => f.write_str("Passphrase(..)"),
+            KeySource::KeyFile(path) => f.debug_tuple("KeyFile").field(path).finish(),
+        }
+    }
+}
+
+impl KeySource {
+    fn secret(&self) -> Result<Vec<u8>, Error> {
+        let secret = match self {
+            KeySource::Passphrase(passphrase) => passphrase.as_bytes().to_vec(),
+            KeySource::KeyFile(path) => std::fs::read(path)?,
+        };
+        if secret.is_empty() {
+            return Err(Error::ConfigError("口令或密钥文件为空！".to_owned()));
+        }
+        Ok(secret)
+    }
+}
+
+/// 加密数据所用的密钥。
+#[derive(Clone)]
+pub struct StorageKey {
+    key: [u8; 32],
+}
+
+impl Debug for StorageKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("StorageKey(..)")
+    }
+}
+
+impl StorageKey {
+    /// 从密钥来源派生密钥。
+    pub fn derive(source: &KeySource, salt: &[u8], iterations: u32) -> Result<Self, Error> {
+        let iterations = NonZeroU32::new(iterations)
+            .ok_or_else(|| Error::ConfigError("迭代次数不能为 0！".to_owned()))?;
+        let mut key = [0; 32];
+        ring::pbkdf2::derive(
+            ring::pbkdf2::PBKDF2_HMAC_SHA256,
+            iterations,
+            salt,
+            &source.secret()?,
+            &mut key,
+        );
+        Ok(Self { key })
+    }
+    fn aead_key(&self) -> LessSafeKey {
+        // 密钥长度固定为 32 字节，不会出错。
+        LessSafeKey::new(UnboundKey::new(&AES_256_GCM, &self.key).expect("密钥长度错误！"))
+    }
+    /// 加密，返回带前缀的文本。
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, Error> {
+        let mut nonce = [0; NONCE_LEN];
+        random_bytes(&mut nonce)?;
+        let mut data = plaintext.as_bytes().to_vec();
+        self.aead_key()
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+            .map_err(|_| Error::ConfigError("加密失败！".to_owned()))?;
+        let mut bytes = nonce.to_vec();
+        bytes.extend(data);
+        Ok(PREFIX.to_owned() + &base64::engine::general_purpose::STANDARD.encode(bytes))
+    }
+    /// 解密由 [`StorageKey::encrypt`] 加密的文本。
+    pub fn decrypt(&self, ciphertext: &str) -> Result<String, Error> {
+        let invalid = || Error::ConfigError("解密失败，密钥错误或数据已损坏！".to_owned());
+        let bytes = ciphertext
+            .strip_prefix(PREFIX)
+            .and_then(|data| base64::engine::general_purpose::STANDARD.decode(data).ok())
+            .filter(|bytes| bytes.len() >= NONCE_LEN)
+            .ok_or_else(invalid)?;
+        let (nonce, data) = bytes.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| invalid())?;
+        let mut data = data.to_vec();
+        let plaintext = self
+            .aead_key()
+            .open_in_place(nonce, Aad::empty(), &mut data)
+            .map_err(|_| invalid())?;
+        String::from_utf8(plaintext.to_vec()).map_err(|_| invalid())
+    }
+}
+
+/// 数据是否为加密后的文本。
+pub fn is_encrypted(data: &str) -> bool {
+    data.starts_with(PREFIX)
+}
+
+pub(crate) fn random_bytes(bytes: &mut [u8]) -> Result<(), Error> {
+    SystemRandom::new()
+        .fill(bytes)
+        .map_err(|_| Error::ConfigError("无法生成随机数！".to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crypto::{is_encrypted, KeySource, StorageKey};
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let key =
+            StorageKey::derive(&KeySource::Passphrase("口令".to_owned()), b"salt", 10).unwrap();
+        let ciphertext = key.encrypt("密码").unwrap();
+        assert!(is_encrypted(&ciphertext));
+        assert_ne!(key.encrypt("密码").unwrap(), ciphertext);
+        assert_eq!(key.decrypt(&ciphertext).unwrap(), "密码");
+        let other =
+            StorageKey::derive(&KeySource::Passphrase("口令".to_owned()), b"pepper", 10).unwrap();
+        assert!(other.decrypt(&ciphertext).is_err());
+        assert!(key.decrypt("密码").is_err());
+    }
+}
@@ -1,3 +1,7 @@
+mod structured;
+
+pub use structured::*;
+
 use std::{error::Error as ErrorTrait, str::FromStr};
 
 use log::warn;
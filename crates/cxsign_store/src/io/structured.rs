@@ -0,0 +1,379 @@
+//! 结构化的导入导出格式。
+//!
+//! JSON 与 TOML 格式的文档包含版本号 `version` 和以数据表名为键的记录列表，如：
+//!
+//! ```toml
+//! version = 1
+//!
+//! [[location]]
+//! course = 123
+//! addr = "教学楼"
+//! lon = "116.397"
+//! lat = "39.909"
+//! alt = "50"
+//! aliases = ["教室"]
+//! ```
+//!
+//! CSV 格式每行一条记录，第一行为表头。导入时会逐条校验，出错时报告所在的项或行以及字段，且不做任何修改。
+
+use cxsign_error::Error;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::str::FromStr;
+
+/// 结构化格式的版本。
+pub const FORMAT_VERSION: i64 = 1;
+
+/// 导入导出的格式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DataFormat {
+    /// 旧版本的按行格式，如 `课程号$地址,经度,纬度,海拔$别名/...`. 导入时解析出错的行会被跳过。
+    #[default]
+    Legacy,
+    Json,
+    Toml,
+    /// 仅位置支持。
+    Csv,
+}
+
+impl DataFormat {
+    /// 根据文件扩展名判断格式。
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "json" => Some(DataFormat::Json),
+            "toml" => Some(DataFormat::Toml),
+            "csv" => Some(DataFormat::Csv),
+            _ => None,
+        }
+    }
+    /// 根据内容判断是 JSON, TOML 还是旧格式。CSV 与旧格式难以区分，需要显式指定。
+    pub fn detect(data: &str) -> Self {
+        let data = data.trim_start_matches('\u{feff}').trim_start();
+        if data.starts_with('{') {
+            return DataFormat::Json;
+        }
+        let is_toml = data
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .any(|line| {
+                line.starts_with("[[")
+                    || line
+                        .split_once('=')
+                        .is_some_and(|(key, _)| key.trim() == "version")
+            });
+        if is_toml {
+            DataFormat::Toml
+        } else {
+            DataFormat::Legacy
+        }
+    }
+    fn unsupported(self, table: &str) -> Error {
+        Error::ParseError(format!("数据表 {table} 不支持 {self} 格式。"))
+    }
+}
+
+impl Display for DataFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            DataFormat::Legacy => "legacy",
+            DataFormat::Json => "json",
+            DataFormat::Toml => "toml",
+            DataFormat::Csv => "csv",
+        })
+    }
+}
+
+impl FromStr for DataFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "legacy" => Ok(DataFormat::Legacy),
+            "json" => Ok(DataFormat::Json),
+            "toml" => Ok(DataFormat::Toml),
+            "csv" => Ok(DataFormat::Csv),
+            _ => Err(format!(
+                "未知的格式 `{s}`, 可选值为 `legacy`, `json`, `toml` 或 `csv`."
+            )),
+        }
+    }
+}
+
+/// 记录中无效的字段。
+#[derive(Debug)]
+pub struct InvalidField {
+    pub field: &'static str,
+    pub reason: String,
+}
+
+impl InvalidField {
+    pub fn new(field: &'static str, reason: impl Into<String>) -> Self {
+        Self {
+            field,
+            reason: reason.into(),
+        }
+    }
+}
+
+/// 可以以结构化格式导入导出的记录。
+pub trait Record: Serialize + DeserializeOwned {
+    /// 检查各字段是否有效。
+    fn validate(&self) -> Result<(), InvalidField>;
+    /// 同一份数据中不能重复的字段及其值，如账号、别名。
+    fn unique_keys(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+}
+
+/// 逐条校验记录，`locate(i)` 描述第 `i` 条记录所在的位置。
+fn check_records<T: Record>(records: &[T], locate: impl Fn(usize) -> String) -> Result<(), Error> {
+    let mut seen = HashMap::new();
+    for (i, record) in records.iter().enumerate() {
+        record
+            .validate()
+            .map_err(|InvalidField { field, reason }| {
+                Error::ParseError(format!("{}的 `{field}` 字段无效：{reason}", locate(i)))
+            })?;
+        for (field, key) in record.unique_keys() {
+            if let Some(j) = seen.insert((field, key.clone()), i) {
+                return Err(Error::ParseError(format!(
+                    "{}的 `{field}` 字段与{}重复：`{key}`.",
+                    locate(i),
+                    locate(j)
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_version(version: Option<i64>) -> Result<(), Error> {
+    match version {
+        Some(FORMAT_VERSION) => Ok(()),
+        Some(version) if version > FORMAT_VERSION => Err(Error::ParseError(format!(
+            "数据的版本（{version}）高于程序支持的版本（{FORMAT_VERSION}），请更新程序。"
+        ))),
+        Some(version) => Err(Error::ParseError(format!("无效的版本号：{version}."))),
+        None => Err(Error::ParseError(
+            "缺少版本号 `version` 或其不是整数。".to_owned(),
+        )),
+    }
+}
+
+fn check_keys<'k>(key: &str, keys: impl Iterator<Item = &'k String>) -> Result<(), Error> {
+    for k in keys {
+        if k != "version" && k != key {
+            return Err(Error::ParseError(format!("未知的字段 `{k}`.")));
+        }
+    }
+    Ok(())
+}
+
+/// 将记录导出为 JSON 或 TOML 格式的文档，记录位于数据表名 `table` 下。
+pub fn to_structured<T: Record>(
+    table: &str,
+    records: &[T],
+    format: DataFormat,
+) -> Result<String, Error> {
+    let serialize_error = |e: &dyn Display| Error::ParseError(format!("数据序列化失败：{e}."));
+    match format {
+        DataFormat::Json => {
+            let mut document = serde_json::Map::new();
+            document.insert("version".to_owned(), FORMAT_VERSION.into());
+            document.insert(
+                table.to_owned(),
+                serde_json::to_value(records).map_err(|e| serialize_error(&e))?,
+            );
+            let mut data =
+                serde_json::to_string_pretty(&document).map_err(|e| serialize_error(&e))?;
+            data.push('\n');
+            Ok(data)
+        }
+        DataFormat::Toml => {
+            let mut document = toml::Table::new();
+            document.insert("version".to_owned(), FORMAT_VERSION.into());
+            document.insert(
+                table.to_owned(),
+                toml::Value::try_from(records).map_err(|e| serialize_error(&e))?,
+            );
+            toml::to_string(&document).map_err(|e| serialize_error(&e))
+        }
+        DataFormat::Legacy | DataFormat::Csv => Err(format.unsupported(table)),
+    }
+}
+
+/// 从 JSON 或 TOML 格式的文档导入数据表 `table` 的记录，并逐条校验。
+pub fn from_structured<T: Record>(
+    table: &str,
+    data: &str,
+    format: DataFormat,
+) -> Result<Vec<T>, Error> {
+    let data = data.trim_start_matches('\u{feff}');
+    let item_error = |i: usize, e: &dyn Display| {
+        Error::ParseError(format!("`{table}` 的第 {} 项解析失败：{e}", i + 1))
+    };
+    let not_array = || Error::ParseError(format!("`{table}` 应为数组。"));
+    let records = match format {
+        DataFormat::Json => {
+            let mut document: serde_json::Map<String, serde_json::Value> =
+                serde_json::from_str(data)
+                    .map_err(|e| Error::ParseError(format!("JSON 格式错误：{e}.")))?;
+            check_keys(table, document.keys())?;
+            check_version(document.get("version").and_then(serde_json::Value::as_i64))?;
+            match document.remove(table) {
+                Some(serde_json::Value::Array(items)) => items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, item)| serde_json::from_value(item).map_err(|e| item_error(i, &e)))
+                    .collect::<Result<Vec<T>, _>>()?,
+                Some(_) => return Err(not_array()),
+                None => Vec::new(),
+            }
+        }
+        DataFormat::Toml => {
+            let mut document: toml::Table = data
+                .parse()
+                .map_err(|e| Error::ParseError(format!("TOML 格式错误：{e}")))?;
+            check_keys(table, document.keys())?;
+            check_version(document.get("version").and_then(toml::Value::as_integer))?;
+            match document.remove(table) {
+                Some(toml::Value::Array(items)) => items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, item)| item.try_into().map_err(|e| item_error(i, &e)))
+                    .collect::<Result<Vec<T>, _>>()?,
+                Some(_) => return Err(not_array()),
+                None => Vec::new(),
+            }
+        }
+        DataFormat::Legacy | DataFormat::Csv => return Err(format.unsupported(table)),
+    };
+    check_records(&records, |i| format!("`{table}` 的第 {} 项", i + 1))?;
+    Ok(records)
+}
+
+/// 导出为 CSV 格式，第一行为表头。
+pub fn to_csv<R: Serialize>(rows: impl IntoIterator<Item = R>) -> Result<String, Error> {
+    let serialize_error = |e: &dyn Display| Error::ParseError(format!("数据序列化失败：{e}."));
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for row in rows {
+        writer.serialize(row).map_err(|e| serialize_error(&e))?;
+    }
+    let data = writer.into_inner().map_err(|e| serialize_error(&e))?;
+    String::from_utf8(data).map_err(|e| serialize_error(&e))
+}
+
+/// 从 CSV 格式导入记录并逐条校验。第一行为表头，每行解析为 `R` 后由 `into` 转换为记录。
+pub fn from_csv<R: DeserializeOwned, T: Record>(
+    data: &str,
+    into: impl Fn(R) -> T,
+) -> Result<Vec<T>, Error> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(data.trim_start_matches('\u{feff}').as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|e| Error::ParseError(format!("CSV 表头解析失败：{e}.")))?
+        .clone();
+    let mut records = Vec::new();
+    let mut lines = Vec::new();
+    for row in reader.records() {
+        let row = row.map_err(|e| Error::ParseError(format!("CSV 格式错误：{e}.")))?;
+        let line = row.position().map_or(0, csv::Position::line);
+        let row = row
+            .deserialize(Some(&headers))
+            .map_err(|e| Error::ParseError(format!("第 {line} 行解析失败：{e}.")))?;
+        records.push(into(row));
+        lines.push(line);
+    }
+    check_records(&records, |i| format!("第 {} 行", lines[i]))?;
+    Ok(records)
+}
+
+impl Record for i64 {
+    fn validate(&self) -> Result<(), InvalidField> {
+        if *self > 0 {
+            Ok(())
+        } else {
+            Err(InvalidField::new(
+                "id",
+                format!("课程号应为正整数，而不是 {self}."),
+            ))
+        }
+    }
+    fn unique_keys(&self) -> Vec<(&'static str, String)> {
+        vec![("id", self.to_string())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::{from_structured, to_structured, DataFormat};
+    use crate::UnameAndEncPwdPair;
+
+    fn accounts() -> Vec<UnameAndEncPwdPair> {
+        vec![
+            ("a,b$c".to_owned(), "pwd/1".to_owned()).into(),
+            ("uname".to_owned(), "pwd".to_owned()).into(),
+        ]
+    }
+
+    #[test]
+    fn structured_round_trip() {
+        for format in [DataFormat::Json, DataFormat::Toml] {
+            let data = to_structured("account", &accounts(), format).unwrap();
+            assert_eq!(DataFormat::detect(&data), format);
+            let records = from_structured::<UnameAndEncPwdPair>("account", &data, format).unwrap();
+            assert_eq!(records, accounts());
+        }
+        assert_eq!(
+            DataFormat::detect("123$addr,1,2,3$alias\n"),
+            DataFormat::Legacy
+        );
+        let excludes = to_structured("exclude", &[1_i64, 2], DataFormat::Toml).unwrap();
+        assert_eq!(
+            from_structured::<i64>("exclude", &excludes, DataFormat::Toml).unwrap(),
+            vec![1, 2]
+        );
+    }
+
+    #[test]
+    fn invalid_documents_are_rejected() {
+        let check = |data: &str, message: &str| {
+            let e = from_structured::<UnameAndEncPwdPair>("account", data, DataFormat::Json)
+                .unwrap_err()
+                .to_string();
+            assert!(e.contains(message), "{e}");
+        };
+        check(r#"{"account": []}"#, "缺少版本号");
+        check(r#"{"version": 2, "account": []}"#, "高于程序支持的版本");
+        check(r#"{"version": 1, "accounts": []}"#, "未知的字段 `accounts`");
+        check(
+            r#"{"version": 1, "account": [{"uname": "u", "enc_pwd": "p"}, {"uname": "v"}]}"#,
+            "`account` 的第 2 项解析失败：missing field `enc_pwd`",
+        );
+        check(
+            r#"{"version": 1, "account": [{"uname": "u", "enc_pwd": ""}]}"#,
+            "`account` 的第 1 项的 `enc_pwd` 字段无效",
+        );
+        check(
+            r#"{"version": 1, "account": [{"uname": "u", "enc_pwd": "p"}, {"uname": "u", "enc_pwd": "q"}]}"#,
+            "`account` 的第 2 项的 `uname` 字段与`account` 的第 1 项重复",
+        );
+        let e = from_structured::<UnameAndEncPwdPair>(
+            "account",
+            "version = 1\n[[account]]\nuname = \"u\"\n",
+            DataFormat::Toml,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(
+            e.contains("第 1 项解析失败") && e.contains("enc_pwd"),
+            "{e}"
+        );
+    }
+}
@@ -1,6 +1,9 @@
+mod backup;
+pub mod crypto;
 mod io;
 mod sql;
 
+pub use backup::*;
 pub use io::*;
 pub use sql::*;
 #[cfg(test)]
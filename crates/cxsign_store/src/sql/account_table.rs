@@ -1,20 +1,73 @@
-use cxsign_user::Session;
+use cxsign_error::Error;
+use cxsign_user::{ReloginError, Session};
 
-use crate::sql::{DataBase, DataBaseTableTrait};
+use crate::io::{DataFormat, InvalidField, Record};
+use crate::sql::{DataBase, DataBaseTableTrait, Migration, SessionConfigTable, SessionTable};
+use cxsign_utils::WorkerPool;
 use log::{info, warn};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::Display;
 use std::ops::Deref;
 use std::str::FromStr;
+use std::sync::Arc;
 
 pub struct AccountTable<'a> {
     db: &'a DataBase,
 }
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// 批量加载会话的结果，参见 [`AccountTable::load_sessions`].
+#[derive(Debug, Default)]
+pub struct SessionsReport {
+    /// 加载成功的会话。
+    pub sessions: HashMap<String, Session>,
+    /// 加载失败的账号及原因。
+    pub failures: Vec<(String, ReloginError)>,
+    /// 不存在的账号。
+    pub missing: Vec<String>,
+}
+impl SessionsReport {
+    /// 是否所有账号均加载成功。
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty() && self.missing.is_empty()
+    }
+    /// 将失败的账号逐一记录至日志。
+    pub fn warn_failures(&self) {
+        for account in &self.missing {
+            warn!("没有该账号：[`{account}`]，请检查输入或登录。");
+        }
+        for (uname, e) in &self.failures {
+            warn!("账号[{uname}]加载失败！错误信息：{e}.");
+        }
+    }
+}
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct UnameAndEncPwdPair {
     pub uname: String,
     pub enc_pwd: String,
 }
+impl Record for UnameAndEncPwdPair {
+    fn validate(&self) -> Result<(), InvalidField> {
+        if self.uname.trim().is_empty() {
+            Err(InvalidField::new("uname", "账号不能为空。"))
+        } else if self.uname.trim() != self.uname {
+            Err(InvalidField::new("uname", "账号首尾不能有空白字符。"))
+        } else if self.enc_pwd.is_empty() {
+            Err(InvalidField::new("enc_pwd", "加密后的密码不能为空。"))
+        } else {
+            Ok(())
+        }
+    }
+    fn unique_keys(&self) -> Vec<(&'static str, String)> {
+        vec![("uname", self.uname.clone())]
+    }
+}
+impl UnameAndEncPwdPair {
+    /// 是否为只有 Cookies、没有保存密码的账号，参见 [`AccountTable::import_cookies`].
+    pub fn is_cookies_only(&self) -> bool {
+        self.enc_pwd.is_empty()
+    }
+}
 impl From<(String, String)> for UnameAndEncPwdPair {
     fn from((uname, enc_pwd): (String, String)) -> Self {
         UnameAndEncPwdPair { uname, enc_pwd }
@@ -26,7 +79,7 @@ impl Display for UnameAndEncPwdPair {
     }
 }
 impl FromStr for UnameAndEncPwdPair {
-    type Err = cxsign_error::Error;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let s = s
@@ -35,7 +88,7 @@ impl FromStr for UnameAndEncPwdPair {
             .filter(|s| !s.is_empty())
             .collect::<Vec<_>>();
         if s.len() < 2 {
-            Err(cxsign_error::Error::ParseError(
+            Err(Error::ParseError(
                 "登录所需信息解析出错！格式为 `uname,enc_pwd`.".to_string(),
             ))
         } else {
@@ -46,188 +99,354 @@ impl FromStr for UnameAndEncPwdPair {
     }
 }
 impl<'a> AccountTable<'a> {
-    pub fn get_sessions_by_accounts_str(&self, accounts: &str) -> HashMap<String, Session> {
-        let str_list = accounts.split(',').map(|a| a.trim()).collect::<Vec<&str>>();
-        let mut s = HashMap::new();
-        for account in str_list {
-            if let Some(session) = self.get_session(account) {
-                s.insert(account.to_string(), session);
+    pub fn get_sessions_by_accounts_str(
+        &self,
+        accounts: &str,
+    ) -> Result<HashMap<String, Session>, Error> {
+        let report = self.load_sessions_by_accounts_str(accounts)?;
+        report.warn_failures();
+        Ok(report.sessions)
+    }
+    /// 并发地加载以 `,` 分隔的账号的会话，参见 [`AccountTable::load_sessions`].
+    pub fn load_sessions_by_accounts_str(&self, accounts: &str) -> Result<SessionsReport, Error> {
+        let mut pairs = Vec::new();
+        let mut missing = Vec::new();
+        for account in accounts.split(',').map(|a| a.trim()) {
+            match self.get_account(account)? {
+                Some((pair, _)) => pairs.push(pair),
+                None => missing.push(account.to_string()),
             }
         }
-        s
+        let mut report = self.load_sessions_of(pairs)?;
+        report.missing = missing;
+        Ok(report)
     }
-    pub fn get_session(&self, account: &str) -> Option<Session> {
-        if self.has_account(account) {
-            Some(Session::load_json(&self.db.dir, account).unwrap())
+    /// 加载账号的会话，账号不存在或加载失败时返回 `None` 并记录至日志。
+    pub fn get_session(&self, account: &str) -> Result<Option<Session>, Error> {
+        if let Some((pair, _)) = self.get_account(account)? {
+            Ok(self
+                .load_session(&pair)
+                .map_err(|e| warn!("账号[{account}]加载失败！错误信息：{e}."))
+                .ok())
         } else {
             warn!("没有该账号：[`{account}`]，请检查输入或登录。");
-            None
+            Ok(None)
         }
     }
-    pub fn get_sessions(&self) -> HashMap<String, Session> {
-        let binding = self.get_accounts();
-        let str_list = binding.keys().collect::<Vec<_>>();
-        let mut s = HashMap::new();
-        for account in str_list {
-            if self.has_account(&account.uname) {
-                let session = Session::load_json(&self.db.dir, &account.uname).unwrap();
-                s.insert(account.uname.clone(), session);
-            } else {
-                warn!(
-                    "没有该账号：[`{}`]，跳过。请检查输入或登录。",
-                    account.uname
-                );
+    /// 加载账号的会话，Cookies 失效时使用保存的密码重新登录，参见 [`Session::load_or_relogin`].
+    ///
+    /// 只有 Cookies 的账号不会重新登录，会话失效时返回 [`ReloginError::Expired`], 参见 [`Session::load_cookies_only`].
+    pub fn load_session(&self, account: &UnameAndEncPwdPair) -> Result<Session, ReloginError> {
+        let storage = self.db.add_table::<SessionTable>()?.storage()?;
+        let fid = self.get_fid(&account.uname)?;
+        load_session_from(storage, account, fid, &self.get_config(&account.uname)?)
+    }
+    pub fn get_sessions(&self) -> Result<HashMap<String, Session>, Error> {
+        let report = self.load_sessions()?;
+        report.warn_failures();
+        Ok(report.sessions)
+    }
+    /// 并发地加载所有账号的会话，并发数受全局的 [`WorkerPool`] 限制。
+    ///
+    /// 加载失败的账号记录在 [`SessionsReport::failures`] 中，只有读取数据库出错时才返回错误。
+    pub fn load_sessions(&self) -> Result<SessionsReport, Error> {
+        self.load_sessions_of(self.get_accounts()?.into_keys().collect())
+    }
+    fn load_sessions_of(&self, accounts: Vec<UnameAndEncPwdPair>) -> Result<SessionsReport, Error> {
+        // 数据库不能在线程间共享，在此读取好配置，会话的读写则通过 `storage` 进行。
+        let storage = self.db.add_table::<SessionTable>()?.storage()?;
+        let accounts = accounts
+            .into_iter()
+            .map(|account| {
+                let fid = self.get_fid(&account.uname)?;
+                let config = self.get_config(&account.uname)?;
+                Ok((account, fid, config))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let results = WorkerPool::global().map(accounts, move |(account, fid, config)| {
+            let result = load_session_from(Arc::clone(&storage), &account, fid, &config);
+            (account.uname, result)
+        });
+        let mut report = SessionsReport::default();
+        for (uname, result) in results {
+            match result {
+                Ok(session) => {
+                    report.sessions.insert(uname, session);
+                }
+                Err(e) => report.failures.push((uname, e)),
             }
         }
-        s
+        Ok(report)
     }
-    pub fn has_account(&self, uname: &str) -> bool {
-        let mut query = self
-            .db
-            .prepare(format!(
-                "SELECT count(*) FROM {} WHERE uname=?;",
-                Self::TABLE_NAME
-            ))
-            .unwrap();
-        query.bind((1, uname)).unwrap();
-        query.next().unwrap();
-        query.read::<i64, _>(0).unwrap() > 0
+    /// 机构账号所属机构的 fid, 手机号账号为 `None`.
+    pub fn get_fid(&self, uname: &str) -> Result<Option<i64>, Error> {
+        let mut query = self.db.prepare(format!(
+            "SELECT fid FROM {} WHERE uname=?;",
+            Self::TABLE_NAME
+        ))?;
+        query.bind((1, uname))?;
+        match query.next()? {
+            sqlite::State::Row => Ok(query.read::<Option<i64>, _>("fid")?),
+            sqlite::State::Done => Ok(None),
+        }
+    }
+    /// 设置账号所属机构的 fid, 参见 [`AccountTable::get_fid`].
+    pub fn set_fid(&self, uname: &str, fid: Option<i64>) -> Result<(), Error> {
+        let mut query = self.db.prepare(format!(
+            "UPDATE {} SET fid=:fid WHERE uname=:uname;",
+            Self::TABLE_NAME
+        ))?;
+        query.bind::<&[(_, sqlite::Value)]>(
+            &[
+                (":uname", uname.into()),
+                (":fid", fid.map(Into::into).unwrap_or(sqlite::Value::Null)),
+            ][..],
+        )?;
+        query.next()?;
+        Ok(())
+    }
+    /// 获取账号的 [`SessionConfig`](cxsign_user::SessionConfig), 参见 [`SessionConfigTable`].
+    pub fn get_config(&self, uname: &str) -> Result<cxsign_user::SessionConfig, Error> {
+        self.db.add_table::<SessionConfigTable>()?.get_config(uname)
+    }
+    pub fn has_account(&self, uname: &str) -> Result<bool, Error> {
+        let mut query = self.db.prepare(format!(
+            "SELECT count(*) FROM {} WHERE uname=?;",
+            Self::TABLE_NAME
+        ))?;
+        query.bind((1, uname))?;
+        query.next()?;
+        Ok(query.read::<i64, _>(0)? > 0)
     }
 
-    pub fn delete_account(&self, uname: &str) {
-        if self.has_account(uname) {
+    /// 删除账号及其会话和配置，所有修改在同一事务中进行。
+    pub fn delete_account(&self, uname: &str) -> Result<(), Error> {
+        self.db.transaction(|| {
             let mut query = self
                 .db
-                .prepare(format!("DELETE FROM {} WHERE uname=?;", Self::TABLE_NAME))
-                .unwrap();
-            query.bind((1, uname)).unwrap();
-            query.next().unwrap();
+                .prepare(format!("DELETE FROM {} WHERE uname=?;", Self::TABLE_NAME))?;
+            query.bind((1, uname))?;
+            query.next()?;
+            self.db.add_table::<SessionTable>()?.delete_session(uname)?;
+            self.db
+                .add_table::<SessionConfigTable>()?
+                .delete_config(uname)
+        })?;
+        self.remove_json_file(uname);
+        Ok(())
+    }
+    /// 删除旧版本的 Cookies 文件，以免其在迁移时覆盖数据库中的会话。需要在事务提交后调用。
+    fn remove_json_file(&self, uname: &str) {
+        if let Some(dir) = self.db.dir() {
+            if let Err(e) = std::fs::remove_file(dir.get_json_file_path(uname)) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    warn!("账号[{uname}]的 Cookies 文件删除失败！错误信息：{e}.");
+                }
+            }
         }
-        std::fs::remove_file(self.db.dir.get_json_file_path(uname)).unwrap();
     }
 
-    pub fn add_account_or<O: Fn(&Self, &str, &str, &str)>(
+    /// 添加账号，账号已存在时调用 `or`.
+    pub fn add_account_or<O: Fn(&Self, &str, &str, &str) -> Result<(), Error>>(
         &self,
         uname: &str,
         pwd: &str,
         name: &str,
         or: O,
-    ) {
-        let mut query = self
-            .db
-            .prepare(format!(
-                "INSERT INTO {}(uname,pwd,name) values(:uname,:pwd,:name);",
-                Self::TABLE_NAME
-            ))
-            .unwrap();
-        query
-            .bind::<&[(_, sqlite::Value)]>(
-                &[
-                    (":pwd", pwd.into()),
-                    (":uname", uname.into()),
-                    (":name", name.into()),
-                ][..],
-            )
-            .unwrap();
-        match query.next() {
-            Ok(_) => (),
-            Err(_) => or(self, uname, pwd, name),
-        };
+    ) -> Result<(), Error> {
+        if self.has_account(uname)? {
+            return or(self, uname, pwd, name);
+        }
+        let sealed_pwd = self.db.seal(pwd)?;
+        let mut query = self.db.prepare(format!(
+            "INSERT INTO {}(uname,pwd,name) values(:uname,:pwd,:name);",
+            Self::TABLE_NAME
+        ))?;
+        query.bind::<&[(_, sqlite::Value)]>(
+            &[
+                (":pwd", sealed_pwd.into()),
+                (":uname", uname.into()),
+                (":name", name.into()),
+            ][..],
+        )?;
+        query.next()?;
+        Ok(())
     }
 
-    pub fn update_account(&self, uname: &str, pwd: &str, name: &str) {
-        let mut query = self
-            .db
-            .prepare(format!(
-                "UPDATE {} SET pwd=:pwd,name=:name WHERE uname=:uname;",
-                Self::TABLE_NAME
-            ))
-            .unwrap();
-        query
-            .bind::<&[(_, sqlite::Value)]>(
-                &[
-                    (":uname", uname.into()),
-                    (":pwd", pwd.into()),
-                    (":name", name.into()),
-                ][..],
-            )
-            .unwrap();
-        query.next().unwrap();
+    pub fn update_account(&self, uname: &str, pwd: &str, name: &str) -> Result<(), Error> {
+        let pwd = self.db.seal(pwd)?;
+        let mut query = self.db.prepare(format!(
+            "UPDATE {} SET pwd=:pwd,name=:name WHERE uname=:uname;",
+            Self::TABLE_NAME
+        ))?;
+        query.bind::<&[(_, sqlite::Value)]>(
+            &[
+                (":uname", uname.into()),
+                (":pwd", pwd.into()),
+                (":name", name.into()),
+            ][..],
+        )?;
+        query.next()?;
+        Ok(())
     }
 
-    pub fn get_accounts(&self) -> HashMap<UnameAndEncPwdPair, String> {
+    /// 获取所有账号。密码无法解密的账号会被跳过并记录至日志。
+    pub fn get_accounts(&self) -> Result<HashMap<UnameAndEncPwdPair, String>, Error> {
         let mut query = self
             .db
-            .prepare(format!("SELECT * FROM {};", Self::TABLE_NAME))
-            .unwrap();
+            .prepare(format!("SELECT uname,pwd,name FROM {};", Self::TABLE_NAME))?;
         let mut accounts = HashMap::new();
-        for c in query.iter() {
-            if let Ok(row) = c {
-                let uname: &str = row.read("uname");
-                let pwd: &str = row.read("pwd");
-                let name: &str = row.read("name");
-                accounts.insert((uname.into(), pwd.into()).into(), name.into());
-            } else {
-                warn!("账号解析行出错：{c:?}.");
+        while let sqlite::State::Row = query.next()? {
+            let uname: String = query.read("uname")?;
+            let pwd: String = query.read("pwd")?;
+            let name: String = query.read("name")?;
+            match self.db.unseal(&pwd) {
+                Ok(pwd) => {
+                    accounts.insert((uname, pwd).into(), name);
+                }
+                Err(e) => warn!("账号[{uname}]的密码解密失败，已跳过。错误信息：{e}."),
             }
         }
         if accounts.is_empty() {
             warn!("没有登录的账号，请登录。");
         }
-        accounts
+        Ok(accounts)
     }
-    pub fn get_account(&self, account: &str) -> Option<(UnameAndEncPwdPair, String)> {
-        let mut query = self
-            .db
-            .prepare(format!("SELECT * FROM {} WHERE uname=?;", Self::TABLE_NAME))
-            .unwrap();
-        query.bind((1, account)).unwrap();
-        for c in query.iter() {
-            if let Ok(row) = c {
-                let uname: &str = row.read("uname");
-                let pwd: &str = row.read("pwd");
-                let name: &str = row.read("name");
-                return Some(((uname.into(), pwd.into()).into(), name.into()));
-            } else {
-                warn!("账号解析行出错：{c:?}.");
-            }
+    pub fn get_account(
+        &self,
+        account: &str,
+    ) -> Result<Option<(UnameAndEncPwdPair, String)>, Error> {
+        let mut query = self.db.prepare(format!(
+            "SELECT uname,pwd,name FROM {} WHERE uname=?;",
+            Self::TABLE_NAME
+        ))?;
+        query.bind((1, account))?;
+        if let sqlite::State::Row = query.next()? {
+            let uname: String = query.read("uname")?;
+            let pwd: String = query.read("pwd")?;
+            let name: String = query.read("name")?;
+            let pwd = self.db.unseal(&pwd)?;
+            Ok(Some(((uname, pwd).into(), name)))
+        } else {
+            Ok(None)
         }
-        None
     }
+    /// 保存登录所得的会话并添加或更新账号，所有修改在同一事务中进行。
+    fn save_login(&self, session: &Session, enc_pwd: &str, fid: Option<i64>) -> Result<(), Error> {
+        let uname = session.get_uname();
+        self.db.transaction(|| {
+            self.db
+                .add_table::<SessionTable>()?
+                .save_session_record(&session.to_record()?)?;
+            let name = session.get_stu_name();
+            self.add_account_or(uname, enc_pwd, name, AccountTable::update_account)?;
+            self.set_fid(uname, fid)
+        })
+    }
+    /// 登录并添加账号。指定了机构 `fid` 时使用机构账号（学号）登录，并将 `fid` 与账号一同保存。
     pub fn login(
         &self,
         uname: String,
         pwd: Option<String>,
-    ) -> Result<Session, cxsign_error::Error> {
-        let pwd = pwd.ok_or(cxsign_error::Error::LoginError("没有密码！".to_string()))?;
-        let enc_pwd = cxsign_login::des_enc(&pwd);
-        let session = Session::login(&cxsign_dir::DIR, &uname, &enc_pwd)?;
-        let name = session.get_stu_name();
-        self.add_account_or(&uname, &enc_pwd, name, AccountTable::update_account);
+        fid: Option<i64>,
+    ) -> Result<Session, Error> {
+        let pwd = pwd.ok_or(Error::MissingPassword)?;
+        let enc_pwd = cxsign_login::des_enc(&pwd)?;
+        let session = Session::relogin_with_fid(&uname, &enc_pwd, fid, &self.get_config(&uname)?)?;
+        self.save_login(&session, &enc_pwd, fid)?;
         Ok(session)
     }
-    pub fn relogin(&self, uname: String, enc_pwd: &str) -> Result<Session, cxsign_error::Error> {
-        let session = Session::relogin(&uname, enc_pwd)?;
-        self.delete_account(&uname);
-        session.store_json(&cxsign_dir::DIR);
-        let name = session.get_stu_name();
-        self.add_account_or(&uname, enc_pwd, name, AccountTable::update_account);
+    /// 导入浏览器中已登录的 Cookies（参见 [`cxsign_user::from_netscape`]）并添加账号，不保存密码。
+    ///
+    /// 不提供 `uname` 时使用 Cookies 中的 uid 作为账号。导入前会请求一次账号设置页以确认会话有效，
+    /// 会话过期后需要重新导入或使用密码登录，参见 [`UnameAndEncPwdPair::is_cookies_only`]。已有的账号只更新会话，保留其密码。
+    pub fn import_cookies(
+        &self,
+        uname: Option<&str>,
+        cookie_store: cookie_store::CookieStore,
+    ) -> Result<Session, Error> {
+        let uid = cxsign_user::UserCookies::from_cookies_vec(
+            cookie_store.iter_unexpired().cloned().collect(),
+        )
+        .get_uid()
+        .to_owned();
+        let uname = match uname {
+            Some(uname) => uname.to_owned(),
+            None if !uid.is_empty() => uid,
+            None => {
+                return Err(Error::ParseError(
+                    "Cookies 中没有 uid, 请指定账号！".to_owned(),
+                ))
+            }
+        };
+        let mut session =
+            Session::from_cookie_store(cookie_store, &uname, "", &self.get_config(&uname)?)?;
+        session.refresh_stu_name()?;
+        self.db.transaction(|| {
+            self.db
+                .add_table::<SessionTable>()?
+                .save_session_record(&session.to_record()?)?;
+            self.add_account_or(&uname, "", session.get_stu_name(), |_, _, _, _| Ok(()))
+        })?;
+        Ok(session)
+    }
+    /// 使用密码重新登录，并在原有的记录上更新账号和会话，保留其机构 fid 和会话配置。
+    pub fn relogin(&self, uname: String, enc_pwd: &str) -> Result<Session, Error> {
+        let config = self.get_config(&uname)?;
+        let fid = self.get_fid(&uname)?;
+        let session = Session::relogin_with_fid(&uname, enc_pwd, fid, &config)?;
+        self.save_login(&session, enc_pwd, fid)?;
+        self.remove_json_file(&uname);
         Ok(session)
     }
 }
 
+/// 加载账号的会话，只有 Cookies 的账号不会重新登录。
+fn load_session_from(
+    storage: Arc<dyn cxsign_user::SessionStorage>,
+    account: &UnameAndEncPwdPair,
+    fid: Option<i64>,
+    config: &cxsign_user::SessionConfig,
+) -> Result<Session, ReloginError> {
+    if account.is_cookies_only() {
+        Session::load_cookies_only(storage, &account.uname, fid, config)
+    } else {
+        Session::load_or_relogin(storage, &account.uname, &account.enc_pwd, fid, config)
+    }
+}
+
 impl<'a> DataBaseTableTrait<'a> for AccountTable<'a> {
     const TABLE_ARGS: &'static str =
-        "uname CHAR (50) UNIQUE NOT NULL,pwd TEXT NOT NULL,name TEXT NOT NULL";
+        "uname CHAR (50) UNIQUE NOT NULL,pwd TEXT NOT NULL,name TEXT NOT NULL,fid INTEGER";
     const TABLE_NAME: &'static str = "account";
+    const MIGRATIONS: &'static [Migration] = &[Migration {
+        version: 1,
+        description: "添加机构账号的 fid 列",
+        up: |db| {
+            // 旧版本的程序可能已经添加过该列。
+            if !db.has_column(Self::TABLE_NAME, "fid")? {
+                db.execute(format!(
+                    "ALTER TABLE {} ADD COLUMN fid INTEGER;",
+                    Self::TABLE_NAME
+                ))?;
+            }
+            Ok(())
+        },
+    }];
 
     fn from_ref(db: &'a DataBase) -> Self {
         Self { db }
     }
 
-    fn import(db: &'a DataBase, data: String) -> Self {
-        let table = db.add_table::<Self>();
-        let data = crate::io::parse::<cxsign_error::Error, UnameAndEncPwdPair>(data);
+    /// 导入账号并逐一重新登录，每个账号的修改在单独的事务中进行，登录失败的账号会被跳过并记录至日志。
+    ///
+    /// 结构化格式的数据有误时不会导入任何账号。
+    fn import(db: &'a DataBase, data: String, format: DataFormat) -> Result<Self, Error> {
+        let table = db.add_table::<Self>()?;
+        let data = match format {
+            DataFormat::Legacy => crate::io::parse::<Error, UnameAndEncPwdPair>(data),
+            format => crate::io::from_structured(Self::TABLE_NAME, &data, format)?,
+        };
         for UnameAndEncPwdPair { uname, enc_pwd } in data {
             match table.relogin(uname.clone(), &enc_pwd) {
                 Ok(session) => info!(
@@ -237,11 +456,23 @@ impl<'a> DataBaseTableTrait<'a> for AccountTable<'a> {
                 Err(e) => warn!("账号 [{uname}] 导入失败！错误信息：{e}."),
             }
         }
-        table
+        Ok(table)
     }
 
-    fn export(&self) -> String {
-        crate::io::to_string(self.get_accounts().keys())
+    /// 导出账号。没有保存密码的账号无法重新导入，因此不会被导出。
+    fn export(&self, format: DataFormat) -> Result<String, Error> {
+        let mut accounts = self.get_accounts()?.into_keys().collect::<Vec<_>>();
+        accounts.sort();
+        accounts.retain(|account| {
+            if account.is_cookies_only() {
+                warn!("账号[{}]没有保存密码，将不会被导出。", account.uname);
+            }
+            !account.is_cookies_only()
+        });
+        match format {
+            DataFormat::Legacy => Ok(crate::io::to_string(accounts.iter())),
+            format => crate::io::to_structured(Self::TABLE_NAME, &accounts, format),
+        }
     }
 }
 
@@ -1,5 +1,5 @@
 use crate::sql::{DataBase, DataBaseTableTrait};
-use log::warn;
+use cxsign_error::Error;
 use std::ops::Deref;
 
 pub struct AliasTable<'a> {
@@ -7,95 +7,79 @@ pub struct AliasTable<'a> {
 }
 
 impl<'a> AliasTable<'a> {
-    pub fn has_alias(&self, alias: &str) -> bool {
-        let mut query = self
-            .db
-            .prepare(format!(
-                "SELECT count(*) FROM {} WHERE name=?;",
-                Self::TABLE_NAME
-            ))
-            .unwrap();
-        query.bind((1, alias)).unwrap();
-        query.next().unwrap();
-        query.read::<i64, _>(0).unwrap() > 0
+    pub fn has_alias(&self, alias: &str) -> Result<bool, Error> {
+        let mut query = self.db.prepare(format!(
+            "SELECT count(*) FROM {} WHERE name=?;",
+            Self::TABLE_NAME
+        ))?;
+        query.bind((1, alias))?;
+        query.next()?;
+        Ok(query.read::<i64, _>(0)? > 0)
     }
 
-    pub fn delete_alias(&self, alias: &str) {
+    pub fn delete_alias(&self, alias: &str) -> Result<(), Error> {
         let mut query = self
             .db
-            .prepare(format!("DELETE FROM {} WHERE name=?;", Self::TABLE_NAME))
-            .unwrap();
-        query.bind((1, alias)).unwrap();
-        query.next().unwrap();
+            .prepare(format!("DELETE FROM {} WHERE name=?;", Self::TABLE_NAME))?;
+        query.bind((1, alias))?;
+        query.next()?;
+        Ok(())
     }
 
-    pub fn add_alias_or<O: Fn(&Self, &str, i64)>(&self, alias: &str, location_id: i64, or: O) {
-        let mut query = self
-            .db
-            .prepare(format!(
-                "INSERT INTO {}(name,lid) values(:name,:lid);",
-                Self::TABLE_NAME
-            ))
-            .unwrap();
-        query
-            .bind::<&[(_, sqlite::Value)]>(
-                &[(":name", alias.into()), (":lid", location_id.into())][..],
-            )
-            .unwrap();
-        match query.next() {
-            Ok(_) => (),
-            Err(_) => or(self, alias, location_id),
-        };
+    /// 添加别名，别名已存在时调用 `or`.
+    pub fn add_alias_or<O: Fn(&Self, &str, i64) -> Result<(), Error>>(
+        &self,
+        alias: &str,
+        location_id: i64,
+        or: O,
+    ) -> Result<(), Error> {
+        // 检查与插入在同一条语句中完成，以免其他进程在此期间添加同名的别名。
+        let mut query = self.db.prepare(format!(
+            "INSERT INTO {}(name,lid) values(:name,:lid) ON CONFLICT(name) DO NOTHING;",
+            Self::TABLE_NAME
+        ))?;
+        query.bind::<&[(_, sqlite::Value)]>(
+            &[(":name", alias.into()), (":lid", location_id.into())][..],
+        )?;
+        query.next()?;
+        if self.db.change_count() == 0 {
+            return or(self, alias, location_id);
+        }
+        Ok(())
     }
-    pub fn update_alias(&self, alias: &str, location_id: i64) {
-        let mut query = self
-            .db
-            .prepare(format!(
-                "UPDATE {} SET name=:name,lid=:lid WHERE name=:name;",
-                Self::TABLE_NAME
-            ))
-            .unwrap();
-        query
-            .bind::<&[(_, sqlite::Value)]>(
-                &[(":name", alias.into()), (":lid", location_id.into())][..],
-            )
-            .unwrap();
-        query.next().unwrap();
+    pub fn update_alias(&self, alias: &str, location_id: i64) -> Result<(), Error> {
+        let mut query = self.db.prepare(format!(
+            "UPDATE {} SET name=:name,lid=:lid WHERE name=:name;",
+            Self::TABLE_NAME
+        ))?;
+        query.bind::<&[(_, sqlite::Value)]>(
+            &[(":name", alias.into()), (":lid", location_id.into())][..],
+        )?;
+        query.next()?;
+        Ok(())
     }
-    pub fn get_aliases(&self, location_id: i64) -> Vec<String> {
-        let mut query = self
-            .db
-            .prepare(format!("SELECT * FROM {} WHERE lid=?;", Self::TABLE_NAME))
-            .unwrap();
-        query.bind((1, location_id)).unwrap();
+    pub fn get_aliases(&self, location_id: i64) -> Result<Vec<String>, Error> {
+        let mut query = self.db.prepare(format!(
+            "SELECT name FROM {} WHERE lid=?;",
+            Self::TABLE_NAME
+        ))?;
+        query.bind((1, location_id))?;
         let mut aliases = Vec::new();
-        for c in query.iter() {
-            if let Ok(row) = c {
-                let name: &str = row.read("name");
-                aliases.push(name.to_owned());
-            } else {
-                warn!("位置解析行出错：{c:?}.");
-            }
+        while let sqlite::State::Row = query.next()? {
+            aliases.push(query.read("name")?);
         }
-        aliases
+        Ok(aliases)
     }
 
-    pub fn get_location_id(&self, alias: &str) -> Option<i64> {
-        if self.has_alias(alias) {
-            let mut query = self
-                .db
-                .prepare(format!("SELECT * FROM {} WHERE name=?;", Self::TABLE_NAME))
-                .unwrap();
-            query.bind((1, alias)).unwrap();
-            let c: Vec<sqlite::Row> = query
-                .iter()
-                .filter_map(|e| if let Ok(e) = e { Some(e) } else { None })
-                .collect();
-            let row = &c[0];
-            let location_id: i64 = row.read("lid");
-            Some(location_id)
-        } else {
-            None
+    pub fn get_location_id(&self, alias: &str) -> Result<Option<i64>, Error> {
+        let mut query = self.db.prepare(format!(
+            "SELECT lid FROM {} WHERE name=?;",
+            Self::TABLE_NAME
+        ))?;
+        query.bind((1, alias))?;
+        match query.next()? {
+            sqlite::State::Row => Ok(Some(query.read("lid")?)),
+            sqlite::State::Done => Ok(None),
         }
     }
 }
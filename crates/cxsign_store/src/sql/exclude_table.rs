@@ -1,5 +1,6 @@
+use crate::io::DataFormat;
 use crate::sql::{DataBase, DataBaseTableTrait};
-use log::warn;
+use cxsign_error::Error;
 use std::ops::Deref;
 
 pub struct ExcludeTable<'a> {
@@ -7,63 +8,56 @@ pub struct ExcludeTable<'a> {
 }
 
 impl<'a> ExcludeTable<'a> {
-    pub fn has_exclude(&self, id: i64) -> bool {
-        let mut query = self
-            .db
-            .prepare(format!(
-                "SELECT count(*) FROM {} WHERE id=?;",
-                Self::TABLE_NAME
-            ))
-            .unwrap();
-        query.bind((1, id)).unwrap();
-        query.next().unwrap();
-        query.read::<i64, _>(0).unwrap() > 0
+    pub fn has_exclude(&self, id: i64) -> Result<bool, Error> {
+        let mut query = self.db.prepare(format!(
+            "SELECT count(*) FROM {} WHERE id=?;",
+            Self::TABLE_NAME
+        ))?;
+        query.bind((1, id))?;
+        query.next()?;
+        Ok(query.read::<i64, _>(0)? > 0)
     }
 
-    pub fn get_excludes(&self) -> Vec<i64> {
+    pub fn get_excludes(&self) -> Result<Vec<i64>, Error> {
         let mut query = self
             .db
-            .prepare(format!("SELECT * FROM {};", Self::TABLE_NAME))
-            .unwrap();
+            .prepare(format!("SELECT id FROM {};", Self::TABLE_NAME))?;
         let mut excludes = Vec::new();
-        for c in query.iter() {
-            if let Ok(row) = c {
-                let id = row.read("id");
-                excludes.push(id);
-            } else {
-                warn!("账号解析行出错：{c:?}.");
-            }
+        while let sqlite::State::Row = query.next()? {
+            excludes.push(query.read("id")?);
         }
-        excludes
+        Ok(excludes)
     }
 
-    pub fn add_exclude(&self, id: i64) {
-        let mut query = self
-            .db
-            .prepare(format!("INSERT INTO {}(id) values(:id);", Self::TABLE_NAME))
-            .unwrap();
-        query
-            .bind::<&[(_, sqlite::Value)]>(&[(":id", id.into())][..])
-            .unwrap();
-        let _ = query.next();
+    /// 添加排除的课程，已存在时不做任何事情。
+    pub fn add_exclude(&self, id: i64) -> Result<(), Error> {
+        let mut query = self.db.prepare(format!(
+            "INSERT OR IGNORE INTO {}(id) values(:id);",
+            Self::TABLE_NAME
+        ))?;
+        query.bind::<&[(_, sqlite::Value)]>(&[(":id", id.into())][..])?;
+        query.next()?;
+        Ok(())
     }
 
-    pub fn delete_exclude(&self, id: i64) {
-        if self.has_exclude(id) {
-            let mut query = self
-                .db
-                .prepare(format!("DELETE FROM {} WHERE id=?;", Self::TABLE_NAME))
-                .unwrap();
-            query.bind((1, id)).unwrap();
-            query.next().unwrap();
-        }
+    pub fn delete_exclude(&self, id: i64) -> Result<(), Error> {
+        let mut query = self
+            .db
+            .prepare(format!("DELETE FROM {} WHERE id=?;", Self::TABLE_NAME))?;
+        query.bind((1, id))?;
+        query.next()?;
+        Ok(())
     }
 
-    pub fn update_excludes(&self, excludes: &Vec<i64>) {
-        Self::delete(self.db);
-        for exclude in excludes {
-            self.add_exclude(*exclude);
-        }
+    /// 使用 `excludes` 替换所有排除的课程，所有修改在同一事务中进行。
+    pub fn update_excludes(&self, excludes: &[i64]) -> Result<(), Error> {
+        self.db.transaction(|| {
+            Self::delete(self.db)?;
+            for exclude in excludes {
+                self.add_exclude(*exclude)?;
+            }
+            Ok(())
+        })
     }
 }
 
@@ -75,17 +69,28 @@ impl<'a> DataBaseTableTrait<'a> for ExcludeTable<'a> {
         Self { db }
     }
 
-    fn import(db: &'a DataBase, data: String) -> Self {
+    fn import(db: &'a DataBase, data: String, format: DataFormat) -> Result<Self, Error> {
         let table = Self::from_ref(db);
-        let data = crate::io::parse(data);
-        for id in data {
-            table.add_exclude(id)
-        }
-        table
+        let data = match format {
+            DataFormat::Legacy => crate::io::parse(data),
+            format => crate::io::from_structured::<i64>(Self::TABLE_NAME, &data, format)?,
+        };
+        db.transaction(|| {
+            for id in data {
+                table.add_exclude(id)?;
+            }
+            Ok(())
+        })?;
+        Ok(table)
     }
 
-    fn export(&self) -> String {
-        crate::io::to_string(self.get_excludes().into_iter())
+    fn export(&self, format: DataFormat) -> Result<String, Error> {
+        let mut excludes = self.get_excludes()?;
+        excludes.sort();
+        match format {
+            DataFormat::Legacy => Ok(crate::io::to_string(excludes.into_iter())),
+            format => crate::io::to_structured(Self::TABLE_NAME, &excludes, format),
+        }
     }
 }
 impl<'a> Deref for ExcludeTable<'a> {
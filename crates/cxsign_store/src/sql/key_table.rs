@@ -0,0 +1,82 @@
+use crate::crypto::{StorageKey, CHECK_PLAINTEXT};
+use crate::sql::{DataBase, DataBaseTableTrait};
+use cxsign_error::Error;
+use std::ops::Deref;
+
+/// 加密参数。数据库未加密时该表为空。
+pub struct KeyTable<'a> {
+    db: &'a DataBase,
+}
+
+/// 派生密钥所需的参数，以及用于验证密钥的密文。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyParams {
+    pub salt: Vec<u8>,
+    pub iterations: u32,
+    pub check_value: String,
+}
+
+impl KeyParams {
+    /// 验证密钥是否正确。
+    pub fn verify(&self, key: &StorageKey) -> bool {
+        key.decrypt(&self.check_value)
+            .is_ok_and(|plaintext| plaintext == CHECK_PLAINTEXT)
+    }
+}
+
+impl<'a> KeyTable<'a> {
+    pub fn get_params(&self) -> Result<Option<KeyParams>, Error> {
+        let mut query = self
+            .db
+            .prepare(format!("SELECT * FROM {};", Self::TABLE_NAME))?;
+        if let sqlite::State::Row = query.next()? {
+            let salt: String = query.read("salt")?;
+            Ok(Some(KeyParams {
+                salt: hex::decode(salt)
+                    .map_err(|e| Error::ParseError(format!("加密参数解析失败：{e}.")))?,
+                iterations: query.read::<i64, _>("iterations")? as u32,
+                check_value: query.read("check_value")?,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+    /// 设置加密参数，为 `None` 时表示不加密。
+    pub(crate) fn set_params(&self, params: Option<&KeyParams>) -> Result<(), Error> {
+        self.db
+            .execute(format!("DELETE FROM {};", Self::TABLE_NAME))?;
+        if let Some(params) = params {
+            let mut query = self.db.prepare(format!(
+                "INSERT INTO {}(salt,iterations,check_value) values(:salt,:iterations,:check_value);",
+                Self::TABLE_NAME
+            ))?;
+            query.bind::<&[(_, sqlite::Value)]>(
+                &[
+                    (":salt", hex::encode(&params.salt).into()),
+                    (":iterations", (params.iterations as i64).into()),
+                    (":check_value", params.check_value.as_str().into()),
+                ][..],
+            )?;
+            query.next()?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> DataBaseTableTrait<'a> for KeyTable<'a> {
+    const TABLE_ARGS: &'static str =
+        "salt TEXT NOT NULL,iterations INTEGER NOT NULL,check_value TEXT NOT NULL";
+    const TABLE_NAME: &'static str = "storage_key";
+
+    fn from_ref(db: &'a DataBase) -> Self {
+        Self { db }
+    }
+}
+
+impl<'a> Deref for KeyTable<'a> {
+    type Target = DataBase;
+
+    fn deref(&self) -> &Self::Target {
+        self.db
+    }
+}
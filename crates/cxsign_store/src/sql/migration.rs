@@ -0,0 +1,109 @@
+//! 数据表结构的版本管理。
+//!
+//! 每个数据表通过 [`DataBaseTableTrait::MIGRATIONS`] 登记其结构的变更，已应用的版本记录在
+//! `schema_version` 表中。数据表不存在时直接按最新的结构（[`DataBaseTableTrait::TABLE_ARGS`]）创建，
+//! 已存在时在同一事务中依次执行尚未应用的变更。
+
+use crate::sql::{DataBase, DataBaseTableTrait};
+use cxsign_error::Error;
+use log::info;
+
+/// 记录各数据表版本的数据表。
+pub const SCHEMA_VERSION_TABLE_NAME: &str = "schema_version";
+
+/// 数据表结构的一次变更。
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    /// 变更后的版本号，从 `1` 开始依次递增。
+    pub version: i64,
+    pub description: &'static str,
+    /// 执行变更。
+    ///
+    /// 引入版本管理之前的数据表视为版本 `0`, 其结构可能已被旧版本的程序修改过，变更需要考虑这种情况。
+    pub up: fn(&DataBase) -> Result<(), Error>,
+}
+
+impl DataBase {
+    /// 数据表的版本，没有记录时为 `None`.
+    pub fn get_schema_version(&self, table: &str) -> Result<Option<i64>, Error> {
+        if !self.has_table(SCHEMA_VERSION_TABLE_NAME)? {
+            return Ok(None);
+        }
+        let mut query = self.prepare(format!(
+            "SELECT version FROM {SCHEMA_VERSION_TABLE_NAME} WHERE table_name=?;"
+        ))?;
+        query.bind((1, table))?;
+        match query.next()? {
+            sqlite::State::Row => Ok(Some(query.read(0)?)),
+            sqlite::State::Done => Ok(None),
+        }
+    }
+    pub(crate) fn set_schema_version(&self, table: &str, version: i64) -> Result<(), Error> {
+        let mut query = self.prepare(format!(
+            "INSERT OR REPLACE INTO {SCHEMA_VERSION_TABLE_NAME}(table_name,version) values(?,?);"
+        ))?;
+        query.bind((1, table))?;
+        query.bind((2, version))?;
+        query.next()?;
+        Ok(())
+    }
+    /// 创建数据表或执行其尚未应用的变更，所有修改在同一事务中进行，失败时不会留下部分修改。
+    ///
+    /// 打开数据库时会对本 crate 中的数据表执行，其他 crate 中的数据表（如位置）
+    /// 在第一次 [`DataBase::add_table`] 时执行。每个数据表在同一个 [`DataBase`] 中只会检查一次。
+    pub fn migrate<'a, T: DataBaseTableTrait<'a>>(&self) -> Result<(), Error> {
+        if self.migrated.borrow().contains(T::TABLE_NAME) {
+            return Ok(());
+        }
+        debug_assert!(T::MIGRATIONS
+            .windows(2)
+            .all(|w| w[0].version < w[1].version));
+        let latest = T::MIGRATIONS.last().map_or(0, |m| m.version);
+        let up_to_date = |db: &Self| -> Result<bool, Error> {
+            Ok(db.has_table(T::TABLE_NAME)?
+                && db.get_schema_version(T::TABLE_NAME)? == Some(latest))
+        };
+        if !up_to_date(self)? {
+            // 其他进程可能同时在升级，获取写锁后再检查一次。
+            self.transaction(|| {
+                if !up_to_date(self)? {
+                    self.apply_migrations::<T>(latest)?;
+                }
+                Ok(())
+            })?;
+        }
+        self.migrated.borrow_mut().insert(T::TABLE_NAME);
+        Ok(())
+    }
+    fn apply_migrations<'a, T: DataBaseTableTrait<'a>>(&self, latest: i64) -> Result<(), Error> {
+        self.execute(format!(
+            "CREATE TABLE IF NOT EXISTS {SCHEMA_VERSION_TABLE_NAME} \
+            (table_name TEXT UNIQUE NOT NULL,version INTEGER NOT NULL);"
+        ))?;
+        if !self.has_table(T::TABLE_NAME)? {
+            self.execute(format!(
+                "CREATE TABLE {} ({});",
+                T::TABLE_NAME,
+                T::TABLE_ARGS
+            ))?;
+        } else {
+            let current = self.get_schema_version(T::TABLE_NAME)?.unwrap_or(0);
+            if current > latest {
+                return Err(Error::ConfigError(format!(
+                    "数据表 {} 的版本（{current}）高于程序支持的版本（{latest}），请更新程序。",
+                    T::TABLE_NAME
+                )));
+            }
+            for migration in T::MIGRATIONS.iter().filter(|m| m.version > current) {
+                (migration.up)(self)?;
+                info!(
+                    "数据表 {} 已升级至版本 {}：{}。",
+                    T::TABLE_NAME,
+                    migration.version,
+                    migration.description
+                );
+            }
+        }
+        self.set_schema_version(T::TABLE_NAME, latest)
+    }
+}
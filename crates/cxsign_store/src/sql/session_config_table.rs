@@ -0,0 +1,81 @@
+use crate::sql::{DataBase, DataBaseTableTrait};
+use cxsign_error::Error;
+use cxsign_user::SessionConfig;
+use log::warn;
+use std::ops::Deref;
+
+/// 按账号保存的 [`SessionConfig`].
+///
+/// 代理地址中可能含有用户名和密码，因此配置与 Cookies 一样在数据库加密时加密保存，参见 [`DataBase::seal`].
+pub struct SessionConfigTable<'a> {
+    db: &'a DataBase,
+}
+
+impl<'a> SessionConfigTable<'a> {
+    pub fn has_config(&self, uname: &str) -> Result<bool, Error> {
+        let mut query = self.db.prepare(format!(
+            "SELECT count(*) FROM {} WHERE uname=?;",
+            Self::TABLE_NAME
+        ))?;
+        query.bind((1, uname))?;
+        query.next()?;
+        Ok(query.read::<i64, _>(0)? > 0)
+    }
+    /// 获取账号的配置，没有保存或无法解析时返回默认配置。数据库已加密且未解锁时返回错误。
+    pub fn get_config(&self, uname: &str) -> Result<SessionConfig, Error> {
+        let mut query = self.db.prepare(format!(
+            "SELECT config FROM {} WHERE uname=?;",
+            Self::TABLE_NAME
+        ))?;
+        query.bind((1, uname))?;
+        if let sqlite::State::Row = query.next()? {
+            let config = self.db.unseal(&query.read::<String, _>("config")?)?;
+            match serde_json::from_str(&config) {
+                Ok(config) => return Ok(config),
+                Err(e) => warn!("账号[{uname}]的会话配置解析失败，将使用默认配置。错误信息：{e}."),
+            }
+        }
+        Ok(SessionConfig::default())
+    }
+    /// 保存账号的配置，会先检查配置是否有效。
+    pub fn set_config(&self, uname: &str, config: &SessionConfig) -> Result<(), Error> {
+        config.validate()?;
+        let config = serde_json::to_string(config)
+            .map_err(|e| Error::ParseError(format!("会话配置序列化失败：{e}.")))?;
+        let config = self.db.seal(&config)?;
+        let mut query = self.db.prepare(format!(
+            "INSERT OR REPLACE INTO {}(uname,config) values(:uname,:config);",
+            Self::TABLE_NAME
+        ))?;
+        query.bind::<&[(_, sqlite::Value)]>(
+            &[(":uname", uname.into()), (":config", config.into())][..],
+        )?;
+        query.next()?;
+        Ok(())
+    }
+    pub fn delete_config(&self, uname: &str) -> Result<(), Error> {
+        let mut query = self
+            .db
+            .prepare(format!("DELETE FROM {} WHERE uname=?;", Self::TABLE_NAME))?;
+        query.bind((1, uname))?;
+        query.next()?;
+        Ok(())
+    }
+}
+
+impl<'a> DataBaseTableTrait<'a> for SessionConfigTable<'a> {
+    const TABLE_ARGS: &'static str = "uname CHAR (50) UNIQUE NOT NULL,config TEXT NOT NULL";
+    const TABLE_NAME: &'static str = "session_config";
+
+    fn from_ref(db: &'a DataBase) -> Self {
+        Self { db }
+    }
+}
+
+impl<'a> Deref for SessionConfigTable<'a> {
+    type Target = DataBase;
+
+    fn deref(&self) -> &Self::Target {
+        self.db
+    }
+}
@@ -0,0 +1,194 @@
+use crate::sql::{AccountTable, DataBase, DataBaseTableTrait};
+use cookie_store::CookieStore;
+use cxsign_error::Error;
+use cxsign_user::{SessionRecord, SessionStorage};
+use log::{info, warn};
+use std::ops::Deref;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// 在 `schema_version` 表中记录旧版本 Cookies 文件已迁移完毕的标记。
+const JSON_FILES_MIGRATION: &str = "session_json_files";
+
+/// 按账号保存的会话，包括 Cookies、缓存的姓名、uid、fid 和上次确认会话有效的时间。
+pub struct SessionTable<'a> {
+    db: &'a DataBase,
+}
+
+impl<'a> SessionTable<'a> {
+    pub fn has_session(&self, uname: &str) -> Result<bool, Error> {
+        let mut query = self.db.prepare(format!(
+            "SELECT count(*) FROM {} WHERE uname=?;",
+            Self::TABLE_NAME
+        ))?;
+        query.bind((1, uname))?;
+        query.next()?;
+        Ok(query.read::<i64, _>(0)? > 0)
+    }
+    pub fn get_session_record(&self, uname: &str) -> Result<Option<SessionRecord>, Error> {
+        let mut query = self
+            .db
+            .prepare(format!("SELECT * FROM {} WHERE uname=?;", Self::TABLE_NAME))?;
+        query.bind((1, uname))?;
+        if let sqlite::State::Row = query.next()? {
+            Ok(Some(SessionRecord {
+                uname: query.read("uname")?,
+                stu_name: query.read("name")?,
+                uid: query.read("uid")?,
+                fid: query.read("fid")?,
+                cookies: self.db.unseal(&query.read::<String, _>("cookies")?)?,
+                validated_at: query.read("validated_at")?,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+    pub fn save_session_record(&self, record: &SessionRecord) -> Result<(), Error> {
+        let cookies = self.db.seal(&record.cookies)?;
+        let mut query = self.db.prepare(format!(
+            "INSERT OR REPLACE INTO {}(uname,name,uid,fid,cookies,validated_at) \
+            values(:uname,:name,:uid,:fid,:cookies,:validated_at);",
+            Self::TABLE_NAME
+        ))?;
+        query.bind::<&[(_, sqlite::Value)]>(
+            &[
+                (":uname", record.uname.as_str().into()),
+                (":name", record.stu_name.as_str().into()),
+                (":uid", record.uid.as_str().into()),
+                (":fid", record.fid.as_str().into()),
+                (":cookies", cookies.into()),
+                (":validated_at", record.validated_at.into()),
+            ][..],
+        )?;
+        query.next()?;
+        Ok(())
+    }
+    pub fn delete_session(&self, uname: &str) -> Result<(), Error> {
+        let mut query = self
+            .db
+            .prepare(format!("DELETE FROM {} WHERE uname=?;", Self::TABLE_NAME))?;
+        query.bind((1, uname))?;
+        query.next()?;
+        Ok(())
+    }
+    /// 供 [`Session::load_or_relogin`](cxsign_user::Session::load_or_relogin) 等使用的 [`SessionStorage`].
+    ///
+    /// 第一次调用时打开一个新的连接，之后的调用共用该连接，直到密钥改变。
+    pub fn storage(&self) -> Result<Arc<dyn SessionStorage>, Error> {
+        let mut cached = self.db.session_storage.borrow_mut();
+        if let Some(storage) = &*cached {
+            return Ok(Arc::clone(storage));
+        }
+        let db = DataBase::reopen(self.db.source(), None)?;
+        db.set_key(self.db.get_key());
+        let storage: Arc<dyn SessionStorage> = Arc::new(SessionTableStorage { db: Mutex::new(db) });
+        *cached = Some(Arc::clone(&storage));
+        Ok(storage)
+    }
+}
+
+impl DataBase {
+    /// 将旧版本保存在 `<uname>.json` 中的 Cookies 导入会话表，导入成功后删除该文件。
+    ///
+    /// 全部导入成功后才记录完成，否则在下次打开数据库或解锁时重试。数据库已加密但未解锁时暂不导入。
+    pub(crate) fn migrate_json_files(&self) -> Result<(), Error> {
+        let Some(dir) = self.dir() else {
+            return Ok(());
+        };
+        if self.get_schema_version(JSON_FILES_MIGRATION)?.is_some()
+            || (self.is_encrypted()? && self.get_key().is_none())
+        {
+            return Ok(());
+        }
+        let table = self.add_table::<SessionTable>()?;
+        let accounts = self.add_table::<AccountTable>()?.get_accounts()?;
+        let mut count = 0;
+        let mut failures = 0;
+        for (pair, name) in accounts {
+            let path = dir.get_json_file_path(&pair.uname);
+            if !path.exists() {
+                continue;
+            }
+            let result = std::fs::File::open(&path)
+                .map_err(Error::from)
+                .and_then(|file| {
+                    CookieStore::load_json(std::io::BufReader::new(file))
+                        .map_err(|e| Error::ParseError(format!("Cookies 解析失败：{e}.")))
+                })
+                .and_then(|cookie_store| SessionRecord::new(&pair.uname, &name, &cookie_store, 0))
+                .and_then(|record| table.save_session_record(&record));
+            match result {
+                Ok(()) => {
+                    count += 1;
+                    if let Err(e) = std::fs::remove_file(&path) {
+                        warn!(
+                            "账号[{}]的 Cookies 文件删除失败！错误信息：{e}.",
+                            pair.uname
+                        );
+                    }
+                }
+                Err(e) => {
+                    failures += 1;
+                    warn!(
+                        "账号[{}]的 Cookies 文件迁移失败，将在下次打开数据库时重试。错误信息：{e}.",
+                        pair.uname
+                    )
+                }
+            }
+        }
+        if count > 0 {
+            info!("已将 {count} 个账号的 Cookies 文件迁移至数据库。");
+        }
+        if failures == 0 {
+            self.set_schema_version(JSON_FILES_MIGRATION, 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> DataBaseTableTrait<'a> for SessionTable<'a> {
+    const TABLE_ARGS: &'static str = "uname CHAR (50) UNIQUE NOT NULL,name TEXT NOT NULL,\
+        uid TEXT NOT NULL,fid TEXT NOT NULL,cookies TEXT NOT NULL,validated_at INTEGER NOT NULL";
+    const TABLE_NAME: &'static str = "session";
+
+    fn from_ref(db: &'a DataBase) -> Self {
+        Self { db }
+    }
+}
+
+impl<'a> Deref for SessionTable<'a> {
+    type Target = DataBase;
+
+    fn deref(&self) -> &Self::Target {
+        self.db
+    }
+}
+
+/// 基于 [`SessionTable`] 的 [`SessionStorage`].
+///
+/// [`DataBase`] 不能在线程间共享，因此持有一个单独打开的连接，并使用创建时已解锁的密钥。
+struct SessionTableStorage {
+    db: Mutex<DataBase>,
+}
+
+impl std::fmt::Debug for SessionTableStorage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionTableStorage")
+            .finish_non_exhaustive()
+    }
+}
+
+impl SessionTableStorage {
+    fn with_table<R>(&self, f: impl FnOnce(SessionTable) -> Result<R, Error>) -> Result<R, Error> {
+        let db = self.db.lock().unwrap_or_else(PoisonError::into_inner);
+        f(db.add_table::<SessionTable>()?)
+    }
+}
+
+impl SessionStorage for SessionTableStorage {
+    fn load(&self, uname: &str) -> Result<Option<SessionRecord>, Error> {
+        self.with_table(|table| table.get_session_record(uname))
+    }
+    fn save(&self, record: &SessionRecord) -> Result<(), Error> {
+        self.with_table(|table| table.save_session_record(record))
+    }
+}
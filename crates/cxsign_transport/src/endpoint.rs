@@ -0,0 +1,246 @@
+//! 接口地址注册表。
+//!
+//! 各 `protocol` 模块中的接口均以 [`Endpoint`] 的形式声明，其主机部分在运行时通过本模块解析，
+//! 因此可以通过 [`set_host`]、环境变量或配置文件将整个程序指向本地的替身服务器。
+
+use cxsign_error::Error;
+use log::{debug, warn};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::sync::RwLock;
+
+static OVERRIDES: RwLock<Overrides> = RwLock::new(Overrides::new());
+
+/// 被覆盖的主机地址。全局只有一份，参见 [`set_host`].
+#[derive(Debug, Default)]
+struct Overrides(BTreeMap<Host, String>);
+
+impl Overrides {
+    const fn new() -> Self {
+        Self(BTreeMap::new())
+    }
+    fn url(&self, host: Host) -> String {
+        self.0
+            .get(&host)
+            .cloned()
+            .unwrap_or_else(|| host.default_url())
+    }
+    fn set(&mut self, host: Host, url: &str) {
+        let url = url.trim_end_matches('/').to_owned();
+        debug!("主机[{}]的地址已设置为：{url}.", host.name());
+        self.0.insert(host, url);
+    }
+    /// 从 JSON 中读取主机地址，参见 [`load_from_str`].
+    fn load_from_str(&mut self, json: &str) -> Result<usize, Error> {
+        let map: BTreeMap<String, String> = serde_json::from_str(json)
+            .map_err(|e| Error::ParseError(format!("接口配置解析失败：{e}.")))?;
+        let mut hosts = Vec::new();
+        for (name, url) in map {
+            let host = Host::from_name(&name)
+                .ok_or_else(|| Error::ParseError(format!("未知的主机名称：`{name}`.")))?;
+            hosts.push((host, url));
+        }
+        let count = hosts.len();
+        for (host, url) in hosts {
+            self.set(host, &url);
+        }
+        Ok(count)
+    }
+}
+
+/// 环境变量前缀。如 `CXSIGN_HOST_MOBILELEARN=http://127.0.0.1:8080`.
+pub static ENV_PREFIX: &str = "CXSIGN_HOST_";
+
+/// 超星的各个主机。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Host {
+    MobileLearn,
+    Passport2,
+    Mooc1Api,
+    PanYz,
+    Captcha,
+    Photo,
+}
+
+impl Host {
+    pub const ALL: [Host; 6] = [
+        Host::MobileLearn,
+        Host::Passport2,
+        Host::Mooc1Api,
+        Host::PanYz,
+        Host::Captcha,
+        Host::Photo,
+    ];
+    /// 主机名称，同时也是配置文件中的键。
+    pub fn name(&self) -> &'static str {
+        match self {
+            Host::MobileLearn => "mobilelearn",
+            Host::Passport2 => "passport2",
+            Host::Mooc1Api => "mooc1-api",
+            Host::PanYz => "pan-yz",
+            Host::Captcha => "captcha",
+            Host::Photo => "photo",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|host| host.name() == name)
+    }
+    /// 默认地址，不以 `/` 结尾。
+    pub fn default_url(&self) -> String {
+        format!("https://{}.chaoxing.com", self.name())
+    }
+    /// 当前生效的地址，不以 `/` 结尾。
+    pub fn url(&self) -> String {
+        OVERRIDES
+            .read()
+            .map(|overrides| overrides.url(*self))
+            .unwrap_or_else(|_| self.default_url())
+    }
+    fn env_key(&self) -> String {
+        ENV_PREFIX.to_owned() + &self.name().to_uppercase().replace('-', "_")
+    }
+}
+
+/// 接口地址，由主机和路径组成。
+///
+/// 实现了 [`Display`], 可以直接用于 `format!`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Endpoint {
+    host: Host,
+    path: &'static str,
+    idempotent: bool,
+}
+
+impl Endpoint {
+    /// 默认视为不能安全重试的接口，参见 [`Endpoint::idempotent`].
+    pub const fn new(host: Host, path: &'static str) -> Self {
+        Self {
+            host,
+            path,
+            idempotent: false,
+        }
+    }
+    /// 标记为可以安全重试的接口，即重复请求不会产生额外的效果，如各种查询接口。
+    ///
+    /// 参见 [`retry`](crate::retry).
+    pub const fn idempotent(mut self) -> Self {
+        self.idempotent = true;
+        self
+    }
+    pub fn is_idempotent(&self) -> bool {
+        self.idempotent
+    }
+    pub fn get_host(&self) -> Host {
+        self.host
+    }
+    pub fn get_path(&self) -> &'static str {
+        self.path
+    }
+    pub fn url(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Display for Endpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.host.url(), self.path)
+    }
+}
+
+/// 覆盖某个主机的地址。
+pub fn set_host(host: Host, url: &str) {
+    if let Ok(mut overrides) = OVERRIDES.write() {
+        overrides.set(host, url);
+    }
+}
+
+/// 恢复某个主机的默认地址。
+pub fn reset_host(host: Host) {
+    if let Ok(mut overrides) = OVERRIDES.write() {
+        overrides.0.remove(&host);
+    }
+}
+
+/// 恢复所有主机的默认地址。
+pub fn reset_hosts() {
+    if let Ok(mut overrides) = OVERRIDES.write() {
+        overrides.0.clear();
+    }
+}
+
+/// 从环境变量中读取主机地址，返回读取到的个数。
+///
+/// 环境变量名为 [`ENV_PREFIX`] 加上大写的主机名称，`-` 替换为 `_`, 如 `CXSIGN_HOST_MOOC1_API`.
+pub fn load_from_env() -> usize {
+    let mut count = 0;
+    for host in Host::ALL {
+        if let Ok(url) = std::env::var(host.env_key()) {
+            set_host(host, &url);
+            count += 1;
+        }
+    }
+    count
+}
+
+/// 从 JSON 中读取主机地址，格式为 `{"mobilelearn": "http://127.0.0.1:8080"}`.
+///
+/// 未知的主机名称会导致错误，此时不会修改任何地址。
+pub fn load_from_str(json: &str) -> Result<usize, Error> {
+    match OVERRIDES.write() {
+        Ok(mut overrides) => overrides.load_from_str(json),
+        Err(_) => Ok(0),
+    }
+}
+
+/// 从配置文件中读取主机地址，格式参见 [`load_from_str`]. 文件不存在时什么也不做。
+pub fn load_from_file(path: impl AsRef<Path>) -> Result<usize, Error> {
+    match std::fs::read_to_string(path.as_ref()) {
+        Ok(json) => load_from_str(&json),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+        Err(e) => {
+            warn!("无法读取接口配置文件 {:?}：{e}.", path.as_ref());
+            Err(e.into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::endpoint::{Endpoint, Host, Overrides};
+
+    static TEST: Endpoint = Endpoint::new(Host::MobileLearn, "/pptSign/stuSignajax");
+
+    fn url_in(endpoint: &Endpoint, overrides: &Overrides) -> String {
+        overrides.url(endpoint.get_host()) + endpoint.get_path()
+    }
+
+    // 在局部的覆盖表上测试，以免影响同时运行的其他测试。
+    #[test]
+    fn override_host() {
+        let mut overrides = Overrides::new();
+        assert_eq!(
+            format!("{}?a=1", url_in(&TEST, &overrides)),
+            "https://mobilelearn.chaoxing.com/pptSign/stuSignajax?a=1"
+        );
+        overrides.set(Host::MobileLearn, "http://127.0.0.1:8080/");
+        assert_eq!(
+            url_in(&TEST, &overrides),
+            "http://127.0.0.1:8080/pptSign/stuSignajax"
+        );
+        assert!(overrides
+            .load_from_str(r#"{"passport2": "http://localhost", "bad": "x"}"#)
+            .is_err());
+        assert_eq!(
+            overrides.url(Host::Passport2),
+            "https://passport2.chaoxing.com"
+        );
+        assert_eq!(
+            overrides
+                .load_from_str(r#"{"passport2": "http://localhost"}"#)
+                .unwrap(),
+            1
+        );
+        assert_eq!(overrides.url(Host::Passport2), "http://localhost");
+    }
+}
@@ -0,0 +1,160 @@
+pub mod endpoint;
+pub mod record;
+mod request;
+pub mod retry;
+
+pub use request::*;
+
+use cookie_store::CookieStore;
+use cxsign_error::Error;
+use std::fmt::Debug;
+use ureq::Response;
+
+/// HTTP 传输层。
+///
+/// `Session` 持有一个实现了本特型的对象，
+/// 所有 `protocol` 模块中的函数均通过它发送请求。默认实现为 [`ureq::Agent`],
+/// 测试或其他工具可以实现本特型以使用内存中的假响应。
+///
+/// 实现者应当在服务器返回 4xx 或 5xx 状态码时返回 [`Error::HttpStatusError`],
+/// 与 [`ureq::Agent`] 的行为保持一致。
+pub trait Transport: Debug + Send + Sync {
+    /// 发送请求并返回响应。
+    fn call(&self, request: Request) -> Result<Response, Error>;
+    /// 获取当前 Cookies 的快照。
+    fn cookie_store(&self) -> CookieStore;
+    /// Cookies 被整体替换（如重新登录）的次数，改变时需要重新读取从 Cookies 中解析的信息。
+    ///
+    /// 默认实现不会替换 Cookies, 始终为 `0`.
+    fn cookies_generation(&self) -> u64 {
+        0
+    }
+}
+
+impl Transport for ureq::Agent {
+    fn call(&self, request: Request) -> Result<Response, Error> {
+        let Request {
+            method,
+            url,
+            headers,
+            body,
+            ..
+        } = request;
+        let mut r = self.request(method.as_str(), &url);
+        for (name, value) in &headers {
+            r = r.set(name, value);
+        }
+        Ok(match body {
+            Body::Empty => r.call(),
+            Body::Text(text) => r.send_string(&text),
+            Body::Bytes(bytes) => r.send_bytes(&bytes),
+        }?)
+    }
+
+    fn cookie_store(&self) -> CookieStore {
+        ureq::Agent::cookie_store(self).clone()
+    }
+}
+
+/// 保存响应原地址的响应头，参见 [`buffer_response`].
+pub const URL_HEADER: &str = "X-Cxsign-Url";
+
+/// 响应的地址。
+///
+/// [`ureq::Response`] 不能在构造时指定地址，经 [`buffer_response`] 重新构造的响应的原地址保存在 [`URL_HEADER`] 中。
+pub fn response_url(response: &Response) -> &str {
+    response
+        .header(URL_HEADER)
+        .unwrap_or_else(|| response.get_url())
+}
+
+/// 读取响应的全部内容，并以相同的状态码和响应头重新构造响应，以便检查内容后继续使用。
+///
+/// 新响应的 [`Response::get_url`] 不再是原地址，原地址可以通过 [`response_url`] 获取。
+pub fn buffer_response(response: Response) -> Result<(Response, Vec<u8>), Error> {
+    let mut builder = http::Response::builder()
+        .status(response.status())
+        .header(URL_HEADER, response_url(&response));
+    for name in response.headers_names() {
+        if name.eq_ignore_ascii_case(URL_HEADER) {
+            continue;
+        }
+        for value in response.all(&name) {
+            builder = builder.header(&name, value);
+        }
+    }
+    let mut body = Vec::new();
+    std::io::Read::read_to_end(&mut response.into_reader(), &mut body)?;
+    let response = builder
+        .body(body.clone())
+        .map_err(|e| Error::ParseError(format!("无法构造响应：{e}.")))?;
+    Ok((response.into(), body))
+}
+
+/// 为所有 [`Transport`] 提供与 [`ureq::Agent`] 类似的请求构造方法。
+pub trait TransportExt {
+    fn request(&self, method: Method, url: &str) -> RequestBuilder<'_>;
+    fn get(&self, url: &str) -> RequestBuilder<'_> {
+        self.request(Method::Get, url)
+    }
+    fn post(&self, url: &str) -> RequestBuilder<'_> {
+        self.request(Method::Post, url)
+    }
+}
+
+impl<T: Transport> TransportExt for T {
+    fn request(&self, method: Method, url: &str) -> RequestBuilder<'_> {
+        RequestBuilder::new(self, method, url)
+    }
+}
+
+impl TransportExt for dyn Transport + '_ {
+    fn request(&self, method: Method, url: &str) -> RequestBuilder<'_> {
+        RequestBuilder::new(self, method, url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Body, Method, Request, Transport, TransportExt};
+    use cookie_store::CookieStore;
+    use cxsign_error::Error;
+    use std::sync::Mutex;
+    use ureq::Response;
+
+    #[derive(Debug, Default)]
+    struct Echo {
+        requests: Mutex<Vec<Request>>,
+    }
+
+    impl Transport for Echo {
+        fn call(&self, request: Request) -> Result<Response, Error> {
+            let body = match &request.body {
+                Body::Empty => String::new(),
+                Body::Text(text) => text.clone(),
+                Body::Bytes(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+            };
+            self.requests.lock().unwrap().push(request);
+            Ok(Response::new(200, "OK", &body)?)
+        }
+        fn cookie_store(&self) -> CookieStore {
+            CookieStore::default()
+        }
+    }
+
+    #[test]
+    fn request_builder_goes_through_transport() {
+        let echo = Echo::default();
+        let transport: &dyn Transport = &echo;
+        let r = transport
+            .post("https://example.com/a")
+            .set("Content-Type", "text/plain")
+            .send_string("hello")
+            .unwrap();
+        assert_eq!(r.into_string().unwrap(), "hello");
+        let requests = echo.requests.lock().unwrap();
+        assert_eq!(requests[0].method, Method::Post);
+        assert_eq!(requests[0].url, "https://example.com/a");
+        assert_eq!(requests[0].header("content-type"), Some("text/plain"));
+    }
+}
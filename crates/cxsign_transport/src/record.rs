@@ -0,0 +1,571 @@
+//! 请求录制与回放。
+//!
+//! [`Recorder`] 包装一个 [`Transport`], 将经过它的请求和响应脱敏后保存到目录中，每个请求一个 JSON 文件；
+//! [`Replayer`] 读取这样的目录并按请求的方法和路径返回录制的响应。
+//! 录制所得的目录可以直接作为解析函数的回归测试数据。
+//!
+//! 脱敏时，请求参数中 [`SENSITIVE_KEYS`] 所列的值以及响应设置的所有 Cookies 的值会被替换为 `REDACTED_<名称>`,
+//! 这些值在其他请求和响应中出现时也会被一并替换。请求头（包括 Cookies）不会被保存。
+
+use crate::{Body, Method, Request, Transport};
+use cookie_store::CookieStore;
+use cxsign_error::Error;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use ureq::Response;
+
+/// 需要脱敏的请求参数。
+pub static SENSITIVE_KEYS: [&str; 11] = [
+    "uname", "password", "uid", "puid", "_uid", "fid", "name", "_token", "token", "validate",
+    "enc2",
+];
+/// 需要保存的响应头，其余的响应头会被丢弃。
+static KEPT_HEADERS: [&str; 3] = ["Content-Type", "Location", "Set-Cookie"];
+
+static RECORD_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+static RECORDER_COUNT: AtomicUsize = AtomicUsize::new(0);
+static EXTRA_SECRETS: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+/// 设置录制目录。设置后 [`wrap`] 返回的 [`Transport`] 均会录制请求。
+pub fn set_record_dir(dir: Option<PathBuf>) {
+    if let Ok(mut record_dir) = RECORD_DIR.write() {
+        *record_dir = dir;
+    }
+}
+
+pub fn get_record_dir() -> Option<PathBuf> {
+    RECORD_DIR.read().ok().and_then(|dir| dir.clone())
+}
+
+/// 添加无法从请求参数中得知的敏感值，如从页面中解析出的姓名等，所有 [`Recorder`] 保存时都会将其替换。
+///
+/// 每次创建或刷新会话时都会调用，已添加过的值会被忽略。
+pub fn add_secret(name: &str, value: &str) {
+    if value.is_empty() || get_record_dir().is_none() {
+        return;
+    }
+    push_secret(
+        &mut EXTRA_SECRETS.lock().unwrap_or_else(|e| e.into_inner()),
+        name,
+        value,
+    );
+}
+
+fn push_secret(secrets: &mut Vec<(String, String)>, name: &str, value: &str) {
+    if !secrets.iter().any(|(_, v)| v == value) {
+        secrets.push((name.to_owned(), value.to_owned()));
+    }
+}
+
+/// 设置了录制目录时，将 `transport` 包装为 [`Recorder`], 录制内容保存在录制目录下单独的子目录中。
+pub fn wrap<T: Transport + 'static>(transport: T) -> Arc<dyn Transport> {
+    if let Some(dir) = get_record_dir() {
+        let time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let n = RECORDER_COUNT.fetch_add(1, Ordering::SeqCst);
+        Arc::new(Recorder::new(transport, dir.join(format!("{time}-{n}"))))
+    } else {
+        Arc::new(transport)
+    }
+}
+
+/// 一次请求及其响应。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Exchange {
+    pub method: Method,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_body: Option<String>,
+    pub status: u16,
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    /// 文本响应内容。
+    #[serde(default)]
+    pub body: String,
+    /// 非文本响应内容的十六进制表示。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_hex: Option<String>,
+}
+
+impl Exchange {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let json = std::fs::read_to_string(path.as_ref())?;
+        serde_json::from_str(&json)
+            .map_err(|e| Error::ParseError(format!("录制文件 {:?} 解析失败：{e}.", path.as_ref())))
+    }
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::ParseError(format!("录制内容序列化失败：{e}.")))?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+    /// 读取目录中的所有录制文件，按文件名排序。
+    pub fn load_dir(dir: impl AsRef<Path>) -> Result<Vec<Self>, Error> {
+        let mut paths = std::fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths.into_iter().map(Self::load).collect()
+    }
+    pub fn body_bytes(&self) -> Vec<u8> {
+        match &self.body_hex {
+            Some(body_hex) => hex::decode(body_hex).unwrap_or_default(),
+            None => self.body.as_bytes().to_vec(),
+        }
+    }
+    /// 请求路径，不含查询参数。
+    pub fn path(&self) -> &str {
+        path_of(&self.url)
+    }
+    /// 转换为响应，状态码为 4xx 或 5xx 时返回 [`Error::HttpStatusError`].
+    pub fn to_response(&self) -> Result<Response, Error> {
+        if self.status >= 400 {
+            return Err(Error::HttpStatusError {
+                status: self.status,
+                url: self.url.clone(),
+            });
+        }
+        let mut builder = http::Response::builder().status(self.status);
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        let response = builder
+            .body(self.body_bytes())
+            .map_err(|e| Error::ParseError(format!("无法构造响应：{e}.")))?;
+        Ok(response.into())
+    }
+    fn file_name(&self, index: usize) -> String {
+        let slug = self
+            .path()
+            .split("://")
+            .last()
+            .unwrap_or_default()
+            .split(['/', '.'])
+            .filter(|s| !s.is_empty() && !matches!(*s, "chaoxing" | "com"))
+            .collect::<Vec<_>>()
+            .join("-");
+        format!("{index:04}-{slug}.json")
+    }
+}
+
+fn path_of(url: &str) -> &str {
+    url.split(['?', '#']).next().unwrap_or(url)
+}
+
+fn query_pairs(query: &str) -> impl Iterator<Item = (String, String)> + '_ {
+    query.split('&').filter_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        let decode = |s: &str| {
+            percent_encoding::percent_decode_str(&s.replace('+', " "))
+                .decode_utf8_lossy()
+                .into_owned()
+        };
+        Some((decode(key), decode(value)))
+    })
+}
+
+/// 去除时间戳参数后的地址，用于回放时匹配请求。
+fn normalize(url: &str) -> String {
+    let Some((path, query)) = url.split_once('?') else {
+        return url.to_owned();
+    };
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty() && !pair.starts_with("_="))
+        .collect::<Vec<_>>()
+        .join("&");
+    format!("{path}?{query}")
+}
+
+/// 脱敏规则，记录所有已知的敏感值及其替换值。
+#[derive(Debug, Default, Clone)]
+pub struct Sanitizer {
+    secrets: BTreeMap<String, String>,
+}
+
+impl Sanitizer {
+    fn placeholder(name: &str) -> String {
+        let name = name
+            .trim_matches(|c: char| !c.is_ascii_alphanumeric())
+            .to_ascii_uppercase()
+            .replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+        format!("REDACTED_{name}")
+    }
+    /// 添加一个敏感值。过短的值以及 `-1` 等无意义的值会被忽略。
+    pub fn add_secret(&mut self, name: &str, value: &str) {
+        let value = value.trim();
+        let too_short = if value.parse::<i64>().is_ok() {
+            value.len() < 4
+        } else {
+            value.chars().count() < 2
+        };
+        if too_short || value.starts_with("REDACTED_") {
+            return;
+        }
+        self.secrets
+            .entry(value.to_owned())
+            .or_insert_with(|| Self::placeholder(name));
+    }
+    /// 从地址的查询参数或表单中记录敏感值。
+    pub fn learn_query(&mut self, query: &str) {
+        let query = query.split_once('?').map_or(query, |(_, query)| query);
+        for (key, value) in query_pairs(query) {
+            if SENSITIVE_KEYS.contains(&key.as_str()) {
+                self.add_secret(&key, &value);
+            }
+        }
+    }
+    /// 从 `Set-Cookie` 中记录敏感值。
+    pub fn learn_set_cookie(&mut self, set_cookie: &str) {
+        let cookie = set_cookie.split(';').next().unwrap_or_default();
+        if let Some((name, value)) = cookie.split_once('=') {
+            self.add_secret(name.trim(), value);
+        }
+    }
+    /// 替换文本中所有已知的敏感值，包括其百分号编码形式。
+    pub fn apply(&self, text: &str) -> String {
+        let mut secrets = self.secrets.iter().collect::<Vec<_>>();
+        // 先替换较长的值，避免部分替换。
+        secrets.sort_by_key(|(value, _)| std::cmp::Reverse(value.len()));
+        let mut text = text.to_owned();
+        for (value, placeholder) in secrets {
+            let encoded =
+                percent_encoding::utf8_percent_encode(value, percent_encoding::NON_ALPHANUMERIC)
+                    .to_string();
+            for value in [value.as_str(), encoded.as_str()] {
+                text = if value.bytes().all(|b| b.is_ascii_digit()) {
+                    replace_number(&text, value, placeholder)
+                } else {
+                    text.replace(value, placeholder)
+                };
+            }
+        }
+        text
+    }
+}
+
+/// 替换数字，只替换前后均不是数字的匹配。
+fn replace_number(text: &str, number: &str, placeholder: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = rest.find(number) {
+        let before = rest[..i].chars().next_back();
+        let after = rest[i + number.len()..].chars().next();
+        result += &rest[..i];
+        if before.is_some_and(|c| c.is_ascii_digit()) || after.is_some_and(|c| c.is_ascii_digit()) {
+            result += number;
+        } else {
+            result += placeholder;
+        }
+        rest = &rest[i + number.len()..];
+    }
+    result + rest
+}
+
+fn body_text(body: &Body) -> Option<String> {
+    match body {
+        Body::Empty => None,
+        Body::Text(text) => Some(text.clone()),
+        Body::Bytes(bytes) => Some(String::from_utf8_lossy(bytes).into_owned()),
+    }
+}
+
+/// 录制请求的 [`Transport`]. 录制内容在 [`Recorder::flush`] 或离开作用域时写入磁盘。
+#[derive(Debug)]
+pub struct Recorder<T: Transport> {
+    inner: T,
+    dir: PathBuf,
+    state: Mutex<(Vec<Exchange>, Sanitizer)>,
+}
+
+impl<T: Transport> Recorder<T> {
+    pub fn new(inner: T, dir: impl AsRef<Path>) -> Self {
+        Self {
+            inner,
+            dir: dir.as_ref().to_path_buf(),
+            state: Mutex::new(Default::default()),
+        }
+    }
+    pub fn get_dir(&self) -> &Path {
+        &self.dir
+    }
+    /// 将脱敏后的录制内容写入目录，返回写入的文件数。
+    pub fn flush(&self) -> Result<usize, Error> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let (exchanges, sanitizer) = &*state;
+        if exchanges.is_empty() {
+            return Ok(0);
+        }
+        let mut sanitizer = sanitizer.clone();
+        for (name, value) in EXTRA_SECRETS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+        {
+            sanitizer.add_secret(name, value);
+        }
+        std::fs::create_dir_all(&self.dir)?;
+        for (index, exchange) in exchanges.iter().enumerate() {
+            let mut exchange = exchange.clone();
+            exchange.url = sanitizer.apply(&exchange.url);
+            exchange.request_body = exchange.request_body.map(|body| sanitizer.apply(&body));
+            for (_, value) in exchange.headers.iter_mut() {
+                *value = sanitizer.apply(value);
+            }
+            if exchange.body_hex.is_none() {
+                exchange.body = sanitizer.apply(&exchange.body);
+            }
+            exchange.save(self.dir.join(exchange.file_name(index)))?;
+        }
+        debug!("已将 {} 个请求录制到 {:?}.", exchanges.len(), self.dir);
+        Ok(exchanges.len())
+    }
+    fn push(&self, exchange: Exchange) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        for (name, value) in &exchange.headers {
+            if name.eq_ignore_ascii_case("Set-Cookie") {
+                state.1.learn_set_cookie(value);
+            }
+        }
+        state.0.push(exchange);
+    }
+}
+
+impl<T: Transport> Transport for Recorder<T> {
+    fn call(&self, request: Request) -> Result<Response, Error> {
+        let request_body = body_text(&request.body);
+        {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.1.learn_query(&request.url);
+            if let Some(body) = &request_body {
+                state.1.learn_query(body);
+            }
+        }
+        let (method, url) = (request.method, request.url.clone());
+        let mut exchange = Exchange {
+            method,
+            url,
+            request_body,
+            status: 0,
+            headers: Vec::new(),
+            body: String::new(),
+            body_hex: None,
+        };
+        let response = match self.inner.call(request) {
+            Ok(response) => response,
+            Err(Error::HttpStatusError { status, url }) => {
+                exchange.status = status;
+                self.push(exchange);
+                return Err(Error::HttpStatusError { status, url });
+            }
+            Err(e) => return Err(e),
+        };
+        exchange.status = response.status();
+        for name in KEPT_HEADERS {
+            for value in response.all(name) {
+                exchange.headers.push((name.to_owned(), value.to_owned()));
+            }
+        }
+        let mut bytes = Vec::new();
+        response.into_reader().read_to_end(&mut bytes)?;
+        match String::from_utf8(bytes) {
+            Ok(text) => exchange.body = text,
+            Err(e) => exchange.body_hex = Some(hex::encode(e.into_bytes())),
+        }
+        let response = exchange.to_response();
+        self.push(exchange);
+        response
+    }
+    fn cookie_store(&self) -> CookieStore {
+        self.inner.cookie_store()
+    }
+    fn cookies_generation(&self) -> u64 {
+        self.inner.cookies_generation()
+    }
+}
+
+impl<T: Transport> Drop for Recorder<T> {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            warn!("录制内容保存失败：{e}.");
+        }
+    }
+}
+
+/// 回放录制内容的 [`Transport`].
+///
+/// 请求按方法和路径匹配录制内容，优先使用查询参数（不含时间戳）完全相同且未使用过的录制，
+/// 其次是任意未使用过的录制，都已使用过时重复使用最后一个。严格模式下只使用查询参数完全相同的录制。
+#[derive(Debug)]
+pub struct Replayer {
+    exchanges: Vec<Exchange>,
+    used: Mutex<Vec<bool>>,
+    cookie_store: CookieStore,
+    strict: bool,
+}
+
+impl Replayer {
+    pub fn new(exchanges: Vec<Exchange>) -> Self {
+        let mut cookie_store = CookieStore::default();
+        for exchange in &exchanges {
+            let Ok(url) = url::Url::parse(&exchange.url) else {
+                continue;
+            };
+            for (name, value) in &exchange.headers {
+                if name.eq_ignore_ascii_case("Set-Cookie") {
+                    let _ = cookie_store.parse(value, &url);
+                }
+            }
+        }
+        let used = Mutex::new(vec![false; exchanges.len()]);
+        Self {
+            exchanges,
+            used,
+            cookie_store,
+            strict: false,
+        }
+    }
+    /// 启用严格模式，用于检查请求参数是否与录制时一致。
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+    /// 读取录制目录。
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self, Error> {
+        Ok(Self::new(Exchange::load_dir(dir)?))
+    }
+    fn find(&self, request: &Request) -> Option<usize> {
+        let path = path_of(&request.url);
+        let url = normalize(&request.url);
+        let mut used = self.used.lock().unwrap_or_else(|e| e.into_inner());
+        let candidates = self
+            .exchanges
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.method == request.method && e.path() == path)
+            .map(|(i, e)| (i, normalize(&e.url) == url))
+            .filter(|(_, exact)| *exact || !self.strict)
+            .collect::<Vec<_>>();
+        let index = candidates
+            .iter()
+            .find(|(i, exact)| *exact && !used[*i])
+            .or_else(|| candidates.iter().find(|(i, _)| !used[*i]))
+            .or_else(|| candidates.last())
+            .map(|(i, _)| *i)?;
+        used[index] = true;
+        Some(index)
+    }
+}
+
+impl Transport for Replayer {
+    fn call(&self, request: Request) -> Result<Response, Error> {
+        match self.find(&request) {
+            Some(index) => self.exchanges[index].to_response(),
+            None => {
+                warn!(
+                    "没有找到与请求 {} {} 对应的录制。",
+                    request.method, request.url
+                );
+                Err(Error::HttpStatusError {
+                    status: 404,
+                    url: request.url,
+                })
+            }
+        }
+    }
+    fn cookie_store(&self) -> CookieStore {
+        self.cookie_store.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::record::{push_secret, Recorder, Replayer, Sanitizer};
+    use crate::{Request, Transport, TransportExt};
+    use cookie_store::CookieStore;
+    use cxsign_error::Error;
+    use ureq::Response;
+
+    #[derive(Debug)]
+    struct Fake;
+
+    impl Transport for Fake {
+        fn call(&self, request: Request) -> Result<Response, Error> {
+            let body = if request.url.ends_with("/login") {
+                "HTTP/1.1 200 OK\r\nSet-Cookie: _uid=12345678; Path=/\r\nDate: now\r\n\r\n{\"status\":true}".to_owned()
+            } else {
+                format!(
+                    "HTTP/1.1 200 OK\r\n\r\n<span class=\"colorBlue\">张三</span>{}",
+                    request.url
+                )
+            };
+            Ok(body.parse::<Response>()?)
+        }
+        fn cookie_store(&self) -> CookieStore {
+            CookieStore::default()
+        }
+    }
+
+    #[test]
+    fn sanitize() {
+        let mut sanitizer = Sanitizer::default();
+        sanitizer.learn_query("https://a.com/x?uid=1234&name=%E5%BC%A0%E4%B8%89&fid=-1&a=1");
+        sanitizer.learn_set_cookie("vc3=abcdef; Path=/; Domain=chaoxing.com");
+        assert_eq!(
+            sanitizer.apply("uid=1234&t=112345&name=张三&n=%E5%BC%A0%E4%B8%89&c=abcdef&fid=-1"),
+            "uid=REDACTED_UID&t=112345&name=REDACTED_NAME&n=REDACTED_NAME&c=REDACTED_VC3&fid=-1"
+        );
+    }
+
+    #[test]
+    fn secrets_are_deduplicated() {
+        let mut secrets = Vec::new();
+        for _ in 0..3 {
+            push_secret(&mut secrets, "name", "张三");
+        }
+        push_secret(&mut secrets, "name", "李四");
+        assert_eq!(secrets.len(), 2);
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let dir = std::env::temp_dir().join(format!("cxsign_record_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        {
+            let recorder = Recorder::new(Fake, &dir);
+            recorder.post("https://a.com/login").call().unwrap();
+            recorder
+                .get("https://a.com/page?uid=12345678&name=张三&_=1")
+                .call()
+                .unwrap();
+        }
+        let replayer = Replayer::load(&dir).unwrap();
+        let page = replayer
+            .get("https://a.com/page?uid=REDACTED_UID&name=REDACTED_NAME&_=2")
+            .call()
+            .unwrap()
+            .into_string()
+            .unwrap();
+        assert_eq!(
+            page,
+            "<span class=\"colorBlue\">REDACTED_NAME</span>https://a.com/page?uid=REDACTED_UID&name=REDACTED_NAME&_=1"
+        );
+        let cookies = replayer.cookie_store();
+        assert_eq!(
+            cookies.iter_any().next().map(|c| c.value().to_owned()),
+            Some("REDACTED_UID".to_owned())
+        );
+        assert!(replayer.get("https://a.com/none").call().is_err());
+        let replayer = Replayer::load(&dir).unwrap().strict();
+        assert!(replayer.get("https://a.com/page?uid=1").call().is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,113 @@
+use crate::endpoint::Endpoint;
+use crate::Transport;
+use cxsign_error::Error;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::io::Read;
+use ureq::Response;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Method {
+    Get,
+    Post,
+}
+
+impl Method {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Get => "GET",
+            Method::Post => "POST",
+        }
+    }
+}
+
+impl Display for Method {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 请求体。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Body {
+    Empty,
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+/// 与具体 HTTP 库无关的请求。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: Method,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Body,
+    /// 请求能否安全地重复发送，参见 [`Endpoint::is_idempotent`].
+    pub idempotent: bool,
+}
+
+impl Request {
+    pub fn new(method: Method, url: &str) -> Self {
+        Self {
+            method,
+            url: url.to_owned(),
+            headers: Vec::new(),
+            body: Body::Empty,
+            idempotent: false,
+        }
+    }
+    /// 获取请求头，名称不区分大小写。
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// 请求构造器，用法与 [`ureq::Request`] 类似。
+pub struct RequestBuilder<'t> {
+    transport: &'t dyn Transport,
+    request: Request,
+}
+
+impl<'t> RequestBuilder<'t> {
+    pub fn new(transport: &'t dyn Transport, method: Method, url: &str) -> Self {
+        Self {
+            transport,
+            request: Request::new(method, url),
+        }
+    }
+    /// 设置请求头。
+    pub fn set(mut self, name: &str, value: &str) -> Self {
+        self.request
+            .headers
+            .push((name.to_owned(), value.to_owned()));
+        self
+    }
+    /// 按 `endpoint` 标记请求能否安全地重复发送，未标记的请求视为不能重复发送。
+    pub fn endpoint(mut self, endpoint: &Endpoint) -> Self {
+        self.request.idempotent = endpoint.is_idempotent();
+        self
+    }
+    /// 发送不带请求体的请求。
+    pub fn call(self) -> Result<Response, Error> {
+        self.transport.call(self.request)
+    }
+    pub fn send_string(mut self, text: &str) -> Result<Response, Error> {
+        self.request.body = Body::Text(text.to_owned());
+        self.call()
+    }
+    pub fn send_bytes(mut self, bytes: &[u8]) -> Result<Response, Error> {
+        self.request.body = Body::Bytes(bytes.to_owned());
+        self.call()
+    }
+    /// 读取全部内容后作为请求体发送。
+    pub fn send(mut self, mut reader: impl Read) -> Result<Response, Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        self.request.body = Body::Bytes(bytes);
+        self.call()
+    }
+}
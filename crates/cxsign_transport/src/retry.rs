@@ -0,0 +1,250 @@
+//! 重试策略。
+//!
+//! 接口按 [`Endpoint::is_idempotent`] 分为可以安全重试的（如查询类接口）和不能直接重试的（如提交签到）。
+//! 前者在遇到 [`is_transient`] 的错误时按 [`RetryPolicy`] 指数退避重试；
+//! 后者须由调用者先确认上一次请求没有生效，再通过 [`retry_with_check`] 重试。
+
+use crate::buffer_response;
+use crate::endpoint::Endpoint;
+use cxsign_error::Error;
+use log::warn;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+use ureq::Response;
+
+/// 重试策略。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// 最大重试次数（不含第一次请求）。为 0 时不重试。
+    pub max_retries: u32,
+    /// 第一次重试前的等待时间，之后每次翻倍。
+    pub initial_backoff: Duration,
+    /// 单次等待时间的上限。
+    pub max_backoff: Duration,
+    /// 总耗时预算，超出后不再重试。
+    pub budget: Duration,
+}
+
+impl RetryPolicy {
+    /// 不重试。
+    pub const NONE: RetryPolicy = RetryPolicy {
+        max_retries: 0,
+        initial_backoff: Duration::ZERO,
+        max_backoff: Duration::ZERO,
+        budget: Duration::ZERO,
+    };
+    /// 第 `retry` 次重试（从 0 开始）前的等待时间。
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(2),
+            budget: Duration::from_secs(10),
+        }
+    }
+}
+
+static POLICY: RwLock<Option<RetryPolicy>> = RwLock::new(None);
+
+/// 设置全局的重试策略。
+pub fn set_retry_policy(policy: RetryPolicy) {
+    if let Ok(mut p) = POLICY.write() {
+        *p = Some(policy);
+    }
+}
+
+/// 获取全局的重试策略，未设置时为 [`RetryPolicy::default`].
+pub fn get_retry_policy() -> RetryPolicy {
+    POLICY.read().ok().and_then(|p| *p).unwrap_or_default()
+}
+
+/// 错误是否可能是暂时的：网络错误、5xx 状态码、429 状态码或会话已重新登录。
+pub fn is_transient(e: &Error) -> bool {
+    match e {
+        Error::TransportError(_) | Error::ResponseReadError { .. } | Error::SessionRenewed => true,
+        Error::HttpStatusError { status, .. } => *status >= 500 || *status == 429,
+        _ => false,
+    }
+}
+
+/// 对 `endpoint` 发送请求，接口可以安全重试时按全局策略重试暂时性的错误。
+///
+/// 响应内容在重试的范围内读取完毕（参见 [`buffer_response`]），读取过程中的暂时性错误同样会被重试。
+pub fn call(
+    endpoint: &Endpoint,
+    mut f: impl FnMut() -> Result<Response, Error>,
+) -> Result<Response, Error> {
+    let policy = if endpoint.is_idempotent() {
+        get_retry_policy()
+    } else {
+        RetryPolicy::NONE
+    };
+    retry(&policy, endpoint.get_path(), || read_all(endpoint, f()?))
+}
+
+/// 读取响应的全部内容，读取过程中的 I/O 错误返回 [`Error::ResponseReadError`].
+pub fn read_all(endpoint: &Endpoint, response: Response) -> Result<Response, Error> {
+    buffer_response(response)
+        .map(|(response, _)| response)
+        .map_err(|e| match e {
+            Error::IoError(source) => Error::ResponseReadError {
+                endpoint: endpoint.get_path(),
+                source,
+            },
+            e => e,
+        })
+}
+
+/// 按策略重试 `f`, 只重试 [`is_transient`] 的错误。
+pub fn retry<T>(
+    policy: &RetryPolicy,
+    name: &str,
+    mut f: impl FnMut() -> Result<T, Error>,
+) -> Result<T, Error> {
+    retry_with_check(policy, name, &mut f, || Ok(None))
+}
+
+/// 按策略重试不能直接重试的请求。
+///
+/// 每次重试前调用 `check` 确认上一次请求的效果：返回 `Some` 时说明请求实际上已经生效，直接将其作为结果返回；
+/// 返回 `None` 时才会再次发送请求。`check` 本身出错时不会重试，而是返回最初的错误。
+pub fn retry_with_check<T>(
+    policy: &RetryPolicy,
+    name: &str,
+    mut f: impl FnMut() -> Result<T, Error>,
+    mut check: impl FnMut() -> Result<Option<T>, Error>,
+) -> Result<T, Error> {
+    let start = Instant::now();
+    let mut retry = 0;
+    loop {
+        let e = match f() {
+            Ok(t) => return Ok(t),
+            Err(e) => e,
+        };
+        let backoff = policy.backoff(retry);
+        if !is_transient(&e)
+            || retry >= policy.max_retries
+            || start.elapsed() + backoff > policy.budget
+        {
+            return Err(e);
+        }
+        warn!(
+            "请求 `{name}` 失败，将在 {}ms 后第 {} 次重试。错误信息：{e}.",
+            backoff.as_millis(),
+            retry + 1
+        );
+        std::thread::sleep(backoff);
+        match check() {
+            Ok(Some(t)) => return Ok(t),
+            Ok(None) => (),
+            Err(check_error) => {
+                warn!("无法确认请求 `{name}` 是否已生效，不再重试。错误信息：{check_error}.");
+                return Err(e);
+            }
+        }
+        retry += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::endpoint::{Endpoint, Host};
+    use crate::retry::{call, retry, retry_with_check, RetryPolicy};
+    use cxsign_error::{Error, ResponseExt};
+    use std::time::Duration;
+
+    fn policy(max_retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_retries,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(4),
+            budget: Duration::from_secs(1),
+        }
+    }
+
+    fn unavailable() -> Error {
+        Error::HttpStatusError {
+            status: 503,
+            url: "https://example.com".to_owned(),
+        }
+    }
+
+    #[test]
+    fn retries_transient_errors_only() {
+        let mut calls = 0;
+        let r = retry(&policy(3), "test", || {
+            calls += 1;
+            if calls < 3 {
+                Err(unavailable())
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(r.unwrap(), 3);
+        let mut calls = 0;
+        let r = retry(&policy(3), "test", || {
+            calls += 1;
+            Err::<(), _>(Error::ParseError(String::new()))
+        });
+        assert!(r.is_err());
+        assert_eq!(calls, 1);
+        let mut calls = 0;
+        let r = retry(&policy(2), "test", || {
+            calls += 1;
+            Err::<(), _>(unavailable())
+        });
+        assert!(r.is_err());
+        assert_eq!(calls, 3);
+        assert_eq!(policy(0).backoff(5), Duration::from_millis(4));
+    }
+
+    #[test]
+    fn check_before_retry() {
+        let mut calls = 0;
+        let r = retry_with_check(
+            &policy(3),
+            "test",
+            || {
+                calls += 1;
+                Err(unavailable())
+            },
+            || Ok(Some("checked")),
+        );
+        assert_eq!(r.unwrap(), "checked");
+        assert_eq!(calls, 1);
+        let mut calls = 0;
+        let r = retry_with_check(
+            &policy(3),
+            "test",
+            || {
+                calls += 1;
+                Err::<(), _>(unavailable())
+            },
+            || Err(Error::LocationError),
+        );
+        assert!(matches!(r, Err(Error::HttpStatusError { status: 503, .. })));
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn call_retries_body_read() {
+        static ENDPOINT: Endpoint = Endpoint::new(Host::MobileLearn, "/test").idempotent();
+        let mut calls = 0;
+        let r = call(&ENDPOINT, || {
+            calls += 1;
+            // 第一次的响应内容在 `Content-Length` 之前中断。
+            let length = if calls == 1 { 100 } else { 2 };
+            Ok(format!("HTTP/1.1 200 OK\r\nContent-Length: {length}\r\n\r\n{{}}").parse()?)
+        });
+        assert_eq!(r.unwrap().read_string("test").unwrap(), "{}");
+        assert_eq!(calls, 2);
+    }
+}
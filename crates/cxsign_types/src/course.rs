@@ -1,11 +1,12 @@
 use crate::protocol;
+use cxsign_error::ResponseExt;
 use cxsign_user::Session;
+use cxsign_utils::WorkerPool;
 use log::{info, warn};
 use serde::{Deserialize, Serialize};
 use std::collections::hash_map::OccupiedError;
 use std::collections::HashMap;
 use std::fmt::Display;
-use std::ops::Deref;
 use ureq::serde_json;
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -17,6 +18,31 @@ pub struct Course {
     name: String,
 }
 
+/// [`Course::fetch_courses`] 的结果。
+#[derive(Debug, Default)]
+pub struct CoursesReport {
+    /// 课程及可以访问该课程的用户。
+    pub courses: HashMap<Course, Vec<Session>>,
+    /// 未能获取课程的用户及错误信息。
+    pub failures: Vec<(Session, cxsign_error::Error)>,
+}
+
+impl CoursesReport {
+    /// 是否所有用户的课程均获取成功。
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty()
+    }
+    /// 将失败的用户逐一记录至日志。
+    pub fn warn_failures(&self) {
+        for (session, e) in &self.failures {
+            warn!(
+                "未能获取用户[{}]的课程，错误信息：{e}.",
+                session.get_stu_name()
+            );
+        }
+    }
+}
+
 impl Display for Course {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -28,46 +54,74 @@ impl Display for Course {
 }
 
 impl Course {
-    pub fn get_courses<'a, Sessions: Iterator<Item = &'a Session>>(
+    /// 并发地获取各用户的课程，并发数受全局的 [`WorkerPool`] 限制。获取失败的用户记录在
+    /// [`CoursesReport::failures`] 中。
+    pub fn fetch_courses<'a, Sessions: Iterator<Item = &'a Session>>(
         sessions: Sessions,
-    ) -> Result<HashMap<Course, Vec<Session>>, Box<ureq::Error>> {
-        let mut courses = HashMap::new();
-        for session in sessions {
-            let courses_ = Course::get_session_courses(session).unwrap_or_else(|e| {
-                warn!(
-                    "未能获取用户[{}]的课程，错误信息：{e}.",
-                    session.get_stu_name()
-                );
-                Default::default()
-            });
-            for course in courses_ {
-                if let Err(OccupiedError {
-                    mut entry,
-                    value: _,
-                }) = courses.try_insert(course, vec![session.clone()])
-                {
-                    entry.get_mut().push(session.clone());
+    ) -> CoursesReport {
+        let sessions = sessions.cloned().collect::<Vec<_>>();
+        let results = WorkerPool::global().map(sessions.clone(), |session| {
+            Course::get_session_courses(&session)
+        });
+        let mut report = CoursesReport::default();
+        for (session, result) in sessions.into_iter().zip(results) {
+            match result {
+                Ok(courses) => {
+                    for course in courses {
+                        if let Err(OccupiedError {
+                            mut entry,
+                            value: _,
+                        }) = report.courses.try_insert(course, vec![session.clone()])
+                        {
+                            entry.get_mut().push(session.clone());
+                        }
+                    }
                 }
+                Err(e) => report.failures.push((session, e)),
             }
         }
-        Ok(courses)
+        report
     }
-    pub fn get_session_courses(session: &Session) -> Result<Vec<Course>, Box<ureq::Error>> {
-        let r = protocol::back_clazz_data(session.deref())?;
+    /// 获取各用户的课程，获取失败的用户仅记录日志，参见 [`Course::fetch_courses`].
+    pub fn get_courses<'a, Sessions: Iterator<Item = &'a Session>>(
+        sessions: Sessions,
+    ) -> Result<HashMap<Course, Vec<Session>>, cxsign_error::Error> {
+        let report = Course::fetch_courses(sessions);
+        report.warn_failures();
+        Ok(report.courses)
+    }
+    pub fn get_session_courses(session: &Session) -> Result<Vec<Course>, cxsign_error::Error> {
+        let r = protocol::back_clazz_data(session)?;
         let courses = Course::get_list_from_response(r)?;
         info!("用户[{}]已获取课程列表。", session.get_stu_name());
         Ok(courses)
     }
-    fn get_list_from_response(r: ureq::Response) -> Result<Vec<Course>, Box<ureq::Error>> {
-        let r: GetCoursesR = r.into_json().unwrap();
+    /// [`Course::get_courses`] 的异步版本，请求在独立的线程中进行。
+    #[cfg(feature = "async")]
+    pub async fn get_courses_async<'a, Sessions: Iterator<Item = &'a Session>>(
+        sessions: Sessions,
+    ) -> Result<HashMap<Course, Vec<Session>>, cxsign_error::Error> {
+        let sessions = sessions.cloned().collect::<Vec<_>>();
+        cxsign_utils::unblock(move || Course::get_courses(sessions.iter())).await
+    }
+    /// [`Course::get_session_courses`] 的异步版本，请求在独立的线程中进行。
+    #[cfg(feature = "async")]
+    pub async fn get_session_courses_async(
+        session: &Session,
+    ) -> Result<Vec<Course>, cxsign_error::Error> {
+        let session = session.clone();
+        cxsign_utils::unblock(move || Course::get_session_courses(&session)).await
+    }
+    fn get_list_from_response(r: ureq::Response) -> Result<Vec<Course>, cxsign_error::Error> {
+        let r: GetCoursesR = r.read_json("backclazzdata")?;
         let mut arr = Vec::new();
         for c in r.channel_list {
             if let Some(data) = c.content.course {
                 for course in data.data {
-                    if c.id.is_i64() {
+                    if let Some(class_id) = c.id.as_i64() {
                         arr.push(Course::new(
                             course.id,
-                            c.id.as_i64().unwrap(),
+                            class_id,
                             course.teacher.as_str(),
                             course.image_url.unwrap_or("".into()).as_str(),
                             course.name.as_str(),
@@ -146,3 +200,27 @@ struct GetCoursesR {
     #[serde(rename = "channelList")]
     channel_list: Vec<ClassRaw>,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::Course;
+    use cxsign_user::Session;
+
+    // 模拟服务器的往返测试：测试数据由 `cxsign_mock` 的响应录制而成，只检查录制、脱敏与回放能否衔接，
+    // 不能发现真实接口的变化。
+    #[test]
+    fn get_list_from_mock_recording() {
+        let session = Session::replay(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/mock/courses"),
+            "13800000000",
+        )
+        .unwrap();
+        assert_eq!(session.get_stu_name(), "REDACTED_NAME");
+        assert_eq!(session.get_uid(), "REDACTED_UID");
+        let courses = Course::get_session_courses(&session).unwrap();
+        assert_eq!(
+            courses,
+            vec![Course::new(200, 100, "测试教师", "", "测试课程")]
+        );
+    }
+}
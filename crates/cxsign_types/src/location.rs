@@ -4,6 +4,7 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 use std::{cell::SyncUnsafeCell, collections::HashMap};
 
 use crate::Course;
+use cxsign_error::ResponseExt;
 use cxsign_user::Session;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
@@ -208,7 +209,7 @@ impl LocationWithRange {
     pub fn from_log(
         session: &Session,
         course: &Course,
-    ) -> Result<HashMap<String, Self>, Box<ureq::Error>> {
+    ) -> Result<HashMap<String, Self>, cxsign_error::Error> {
         #[derive(Debug, Clone, Deserialize, Serialize)]
         struct LocationWithRangeAndActiveId {
             #[serde(rename = "activeid")]
@@ -238,7 +239,7 @@ impl LocationWithRange {
             data: Vec<LocationWithRangeAndActiveId>,
         }
         let r = crate::protocol::get_location_log(session, course)?;
-        let data: Data = r.into_json().unwrap();
+        let data: Data = r.read_json("getLocationLog")?;
         let mut map = HashMap::new();
         for l in data.data {
             map.insert(l.active_id.to_string(), l.to_location_with_range());
@@ -324,6 +325,7 @@ impl LocationWithRange {
 #[cfg(test)]
 mod tests {
     use crate::LocationWithRange;
+    use cxsign_transport::record::Exchange;
 
     #[test]
     fn a() {
@@ -335,4 +337,26 @@ mod tests {
         };
         println!("{}", l.to_shifted_location())
     }
+
+    // 模拟服务器的往返测试：测试数据由 `cxsign_mock` 的响应录制而成，只检查录制、脱敏与解析能否衔接，
+    // 不能发现真实页面的变化。
+    #[test]
+    fn find_in_mock_recorded_pre_sign() {
+        let exchange = Exchange::load(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/fixtures/mock/location_pre_sign.json"
+        ))
+        .unwrap();
+        let l = LocationWithRange::find_in_html(&exchange.body).unwrap();
+        assert_eq!(
+            l,
+            LocationWithRange {
+                addr: "测试地点".into(),
+                lon: "116.397128".into(),
+                lat: "39.916527".into(),
+                range: 50,
+            }
+        );
+        assert!(LocationWithRange::find_in_html("<title>签到</title>").is_none());
+    }
 }
@@ -1,36 +1,36 @@
+use cxsign_error::{Error, ResponseExt};
 use cxsign_user::Session;
 use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::path::Path;
 
-// TODO: 删除 unwrap
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Serialize, Deserialize)]
 pub struct Photo {
     object_id: String,
 }
 
 impl Photo {
-    pub fn get_pan_token(session: &Session) -> Result<String, Box<ureq::Error>> {
+    pub fn get_pan_token(session: &Session) -> Result<String, Error> {
         let r = cxsign_pan::protocol::pan_token(session)?;
         #[derive(Deserialize)]
         struct Tmp {
             #[serde(rename = "_token")]
             token: String,
         }
-        let r: Tmp = r.into_json().unwrap();
+        let r: Tmp = r.read_json("pan/token")?;
         Ok(r.token)
     }
 
-    pub fn new(session: &Session, file: &File, file_name: &str) -> Result<Self, Box<ureq::Error>> {
+    pub fn new(session: &Session, file: &File, file_name: &str) -> Result<Self, Error> {
         let token = Self::get_pan_token(session)?;
         let r =
-            cxsign_pan::protocol::pan_upload(session, file, session.get_uid(), &token, file_name)?;
+            cxsign_pan::protocol::pan_upload(session, file, &session.get_uid(), &token, file_name)?;
         #[derive(Deserialize)]
         struct Tmp {
             #[serde(rename = "objectId")]
             object_id: String,
         }
-        let tmp: Tmp = r.into_json().unwrap();
+        let tmp: Tmp = r.read_json("pan/upload")?;
         Ok(Self {
             object_id: tmp.object_id,
         })
@@ -38,22 +38,24 @@ impl Photo {
     pub fn get_object_id(&self) -> &str {
         &self.object_id
     }
-    pub fn default(session: &Session) -> Option<Self> {
-        Self::find_in_cxpan(session, |a| a == "1.png" || a == "1.jpg").unwrap()
+    pub fn default(session: &Session) -> Result<Option<Self>, Error> {
+        Self::find_in_cxpan(session, |a| a == "1.png" || a == "1.jpg")
     }
     pub fn find_in_cxpan(
         session: &Session,
         p: impl Fn(&str) -> bool,
-    ) -> Result<Option<Self>, Box<ureq::Error>> {
+    ) -> Result<Option<Self>, Error> {
         let r = cxsign_pan::protocol::pan_chaoxing(session)?;
-        let r_text = r.into_string().unwrap();
-        let start_of_enc = r_text.find("enc =\"").unwrap() + 6;
-        let end_of_enc = r_text[start_of_enc..r_text.len()].find('"').unwrap() + start_of_enc;
-        let enc = &r_text[start_of_enc..end_of_enc];
-        let start_of_root_dir = r_text.find("_rootdir = \"").unwrap() + 12;
-        let end_of_root_dir =
-            r_text[start_of_root_dir..r_text.len()].find('"').unwrap() + start_of_root_dir;
-        let parent_id = &r_text[start_of_root_dir..end_of_root_dir];
+        let r_text = r.read_string("pan")?;
+        let find_value = |key: &str| {
+            let start = r_text.find(key)? + key.len();
+            let end = r_text[start..r_text.len()].find('"')? + start;
+            Some(&r_text[start..end])
+        };
+        let (Some(enc), Some(parent_id)) = (find_value("enc =\""), find_value("_rootdir = \""))
+        else {
+            return Err(Error::unexpected_response("pan", &r_text));
+        };
         let r = cxsign_pan::protocol::pan_list(session, parent_id, enc)?;
         #[derive(Deserialize)]
         struct CloudFile {
@@ -65,7 +67,7 @@ impl Photo {
         struct TmpR {
             list: Vec<CloudFile>,
         }
-        let r: TmpR = r.into_json().map_err(ureq::Error::from)?;
+        let r: TmpR = r.read_json("pan/list")?;
         for item in r.list {
             if p(&item.name) {
                 return Ok(item.object_id.map(|object_id| Self { object_id }));
@@ -73,9 +75,13 @@ impl Photo {
         }
         Ok(None)
     }
-    pub fn get_from_file(session: &Session, file_path: impl AsRef<Path>) -> Self {
-        let f = File::open(&file_path).unwrap();
-        let file_name = file_path.as_ref().file_name().unwrap().to_str().unwrap();
-        Self::new(session, &f, file_name).unwrap()
+    pub fn get_from_file(session: &Session, file_path: impl AsRef<Path>) -> Result<Self, Error> {
+        let f = File::open(&file_path)?;
+        let file_name = file_path
+            .as_ref()
+            .file_name()
+            .and_then(|file_name| file_name.to_str())
+            .ok_or_else(|| Error::ParseError(format!("无效的文件名：{:?}.", file_path.as_ref())))?;
+        Self::new(session, &f, file_name)
     }
 }
@@ -1,10 +1,14 @@
-use ureq::{Agent, Response};
+use cxsign_transport::endpoint::{Endpoint, Host};
+use cxsign_transport::{Transport, TransportExt};
+use ureq::Response;
 
 // 获取课程
-static BACK_CLAZZ_DATA: &str = "https://mooc1-api.chaoxing.com/mycourse/backclazzdata";
+static BACK_CLAZZ_DATA: Endpoint =
+    Endpoint::new(Host::Mooc1Api, "/mycourse/backclazzdata").idempotent();
 
-pub fn back_clazz_data(client: &Agent) -> Result<Response, Box<ureq::Error>> {
-    Ok(client
+pub fn back_clazz_data(client: &dyn Transport) -> Result<Response, cxsign_error::Error> {
+    client
         .get(&format!("{BACK_CLAZZ_DATA}?view=json&rss=1"))
-        .call()?)
+        .endpoint(&BACK_CLAZZ_DATA)
+        .call()
 }
@@ -1,15 +1,22 @@
 use crate::course::Course;
-use ureq::{Agent, Response};
+use cxsign_transport::endpoint::{Endpoint, Host};
+use cxsign_transport::{Transport, TransportExt};
+use ureq::Response;
 
 // 获取位置信息列表
-static GET_LOCATION_LOG: &str = "https://mobilelearn.chaoxing.com/v2/apis/sign/getLocationLog";
+static GET_LOCATION_LOG: Endpoint =
+    Endpoint::new(Host::MobileLearn, "/v2/apis/sign/getLocationLog").idempotent();
 
-pub fn get_location_log(session: &Agent, course: &Course) -> Result<Response, Box<ureq::Error>> {
-    Ok(session
+pub fn get_location_log(
+    session: &dyn Transport,
+    course: &Course,
+) -> Result<Response, cxsign_error::Error> {
+    session
         .get(&format!(
             "{GET_LOCATION_LOG}?DB_STRATEGY=COURSEID&STRATEGY_PARA=courseId&courseId={}&classId={}",
             course.get_id(),
             course.get_class_id()
         ))
-        .call()?)
+        .endpoint(&GET_LOCATION_LOG)
+        .call()
 }
@@ -1,6 +1,8 @@
 use crate::location::Location;
-use cxsign_store::{AliasTable, DataBase, DataBaseTableTrait};
+use cxsign_error::Error;
+use cxsign_store::{AliasTable, DataBase, DataBaseTableTrait, DataFormat, InvalidField, Record};
 use log::{debug, warn};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::Display;
 use std::ops::Deref;
@@ -15,7 +17,7 @@ pub struct LocationAndAliasesPair {
     pub aliases: Vec<String>,
 }
 impl FromStr for LocationAndAliasesPair {
-    type Err = cxsign_error::Error;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let data: Vec<&str> = s.split('$').collect();
@@ -41,12 +43,10 @@ impl FromStr for LocationAndAliasesPair {
                         aliases,
                     })
                 }
-                Err(e) => Err(cxsign_error::Error::ParseError(format!(
-                    "位置解析出错：{e}."
-                ))),
+                Err(e) => Err(Error::ParseError(format!("位置解析出错：{e}."))),
             }
         } else {
-            Err(cxsign_error::Error::ParseError(
+            Err(Error::ParseError(
                 "格式应为 `课程号$地址,经度,纬度,海拔$别名/...`".to_string(),
             ))
         }
@@ -59,166 +59,318 @@ impl Display for LocationAndAliasesPair {
         write!(f, "{}${}${}", self.course, self.location, aliases_contents)
     }
 }
+/// 结构化格式中的一条位置记录。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LocationRecord {
+    /// 课程号，为 `-1` 时表示全局位置。
+    course: i64,
+    addr: String,
+    lon: String,
+    lat: String,
+    alt: String,
+    #[serde(default)]
+    aliases: Vec<String>,
+}
+impl Record for LocationRecord {
+    fn validate(&self) -> Result<(), InvalidField> {
+        let check_number = |field: &'static str, value: &str, range: Option<f64>| {
+            let number = value
+                .parse::<f64>()
+                .ok()
+                .filter(|n| n.is_finite())
+                .ok_or_else(|| InvalidField::new(field, format!("`{value}` 不是有效的数字。")))?;
+            match range {
+                Some(range) if number.abs() > range => Err(InvalidField::new(
+                    field,
+                    format!("{value} 超出范围 [-{range}, {range}]."),
+                )),
+                _ => Ok(()),
+            }
+        };
+        if self.course != -1 && self.course <= 0 {
+            return Err(InvalidField::new(
+                "course",
+                format!("课程号应为正整数或 `-1`, 而不是 {}.", self.course),
+            ));
+        }
+        if self.addr.trim().is_empty() {
+            return Err(InvalidField::new("addr", "地址不能为空。"));
+        }
+        check_number("lon", &self.lon, Some(180.0))?;
+        check_number("lat", &self.lat, Some(90.0))?;
+        if !self.alt.is_empty() {
+            check_number("alt", &self.alt, None)?;
+        }
+        for alias in &self.aliases {
+            if alias.trim().is_empty() || alias.trim() != alias {
+                return Err(InvalidField::new(
+                    "aliases",
+                    format!("别名 `{alias}` 不能为空或首尾有空白字符。"),
+                ));
+            }
+        }
+        Ok(())
+    }
+    fn unique_keys(&self) -> Vec<(&'static str, String)> {
+        self.aliases
+            .iter()
+            .map(|alias| ("aliases", alias.clone()))
+            .collect()
+    }
+}
+impl From<LocationRecord> for LocationAndAliasesPair {
+    fn from(record: LocationRecord) -> Self {
+        let LocationRecord {
+            course,
+            addr,
+            lon,
+            lat,
+            alt,
+            aliases,
+        } = record;
+        LocationAndAliasesPair {
+            course,
+            location: Location::new(&addr, &lon, &lat, &alt),
+            aliases,
+        }
+    }
+}
+impl From<LocationAndAliasesPair> for LocationRecord {
+    fn from(pair: LocationAndAliasesPair) -> Self {
+        let [addr, lon, lat, alt] = pair.location.to_owned_fields();
+        LocationRecord {
+            course: pair.course,
+            addr,
+            lon,
+            lat,
+            alt,
+            aliases: pair.aliases,
+        }
+    }
+}
+/// CSV 格式中的一行，多个别名以 `/` 分隔。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LocationCsvRow {
+    course: i64,
+    addr: String,
+    lon: String,
+    lat: String,
+    alt: String,
+    #[serde(default)]
+    aliases: String,
+}
+impl From<LocationCsvRow> for LocationRecord {
+    fn from(row: LocationCsvRow) -> Self {
+        let LocationCsvRow {
+            course,
+            addr,
+            lon,
+            lat,
+            alt,
+            aliases,
+        } = row;
+        let aliases = aliases
+            .split('/')
+            .map(|alias| alias.trim().to_owned())
+            .filter(|alias| !alias.is_empty())
+            .collect();
+        LocationRecord {
+            course,
+            addr,
+            lon,
+            lat,
+            alt,
+            aliases,
+        }
+    }
+}
+impl TryFrom<LocationRecord> for LocationCsvRow {
+    type Error = Error;
+
+    fn try_from(record: LocationRecord) -> Result<Self, Self::Error> {
+        if let Some(alias) = record.aliases.iter().find(|alias| alias.contains('/')) {
+            return Err(Error::ParseError(format!(
+                "别名 `{alias}` 含有 `/`, 无法导出为 CSV 格式。"
+            )));
+        }
+        let LocationRecord {
+            course,
+            addr,
+            lon,
+            lat,
+            alt,
+            aliases,
+        } = record;
+        Ok(LocationCsvRow {
+            course,
+            addr,
+            lon,
+            lat,
+            alt,
+            aliases: aliases.join("/"),
+        })
+    }
+}
 impl<'a> LocationTable<'a> {
-    pub fn has_location(&self, location_id: i64) -> bool {
-        let mut query = self
-            .db
-            .prepare(format!(
-                "SELECT count(*) FROM {} WHERE lid=?;",
-                Self::TABLE_NAME
-            ))
-            .unwrap();
-        query.bind((1, location_id)).unwrap();
-        query.next().unwrap();
-        query.read::<i64, _>(0).unwrap() > 0
+    pub fn has_location(&self, location_id: i64) -> Result<bool, Error> {
+        let mut query = self.db.prepare(format!(
+            "SELECT count(*) FROM {} WHERE lid=?;",
+            Self::TABLE_NAME
+        ))?;
+        query.bind((1, location_id))?;
+        query.next()?;
+        Ok(query.read::<i64, _>(0)? > 0)
     }
-    pub fn add_location_or<O: Fn(&Self, i64, i64, &Location)>(
+    /// 添加位置，`location_id` 已存在时调用 `or`.
+    pub fn add_location_or<O: Fn(&Self, i64, i64, &Location) -> Result<(), Error>>(
         &self,
         location_id: i64,
         course_id: i64,
         location: &Location,
         or: O,
-    ) {
+    ) -> Result<(), Error> {
         let addr = location.get_addr();
         let lat = location.get_lat();
         let lon = location.get_lon();
         let alt = location.get_alt();
-        let mut query =self.db.prepare(format!("INSERT INTO {}(lid,courseid,addr,lat,lon,alt) values(:lid,:courseid,:addr,:lat,:lon,:alt);",Self::TABLE_NAME)).unwrap();
-        query
-            .bind::<&[(_, sqlite::Value)]>(
-                &[
-                    (":lid", location_id.into()),
-                    (":courseid", course_id.into()),
-                    (":addr", addr.into()),
-                    (":lat", lat.into()),
-                    (":lon", lon.into()),
-                    (":alt", alt.into()),
-                ][..],
-            )
-            .unwrap();
-        match query.next() {
-            Ok(_) => (),
-            Err(_) => or(self, location_id, course_id, location),
+        // 检查与插入在同一条语句中完成，以免其他进程在此期间添加相同 ID 的位置。
+        let mut query = self.db.prepare(format!(
+            "INSERT INTO {}(lid,courseid,addr,lat,lon,alt) values(:lid,:courseid,:addr,:lat,:lon,:alt) \
+            ON CONFLICT(lid) DO NOTHING;",
+            Self::TABLE_NAME
+        ))?;
+        query.bind::<&[(_, sqlite::Value)]>(
+            &[
+                (":lid", location_id.into()),
+                (":courseid", course_id.into()),
+                (":addr", addr.into()),
+                (":lat", lat.into()),
+                (":lon", lon.into()),
+                (":alt", alt.into()),
+            ][..],
+        )?;
+        query.next()?;
+        if self.db.change_count() == 0 {
+            return or(self, location_id, course_id, location);
         }
+        Ok(())
+    }
+    pub fn update_location(
+        &self,
+        location_id: i64,
+        course_id: i64,
+        location: &Location,
+    ) -> Result<(), Error> {
+        let addr = location.get_addr();
+        let lat = location.get_lat();
+        let lon = location.get_lon();
+        let alt = location.get_alt();
+        let mut query =self.db.prepare(format!("UPDATE {} SET courseid=:courseid,addr=:addr,lat=:lat,lon=:lon,alt=:alt WHERE lid=:lid;",Self::TABLE_NAME))?;
+        query.bind::<&[(_, sqlite::Value)]>(
+            &[
+                (":lid", location_id.into()),
+                (":courseid", course_id.into()),
+                (":addr", addr.into()),
+                (":lat", lat.into()),
+                (":lon", lon.into()),
+                (":alt", alt.into()),
+            ][..],
+        )?;
+        query.next()?;
+        Ok(())
     }
     /// 添加位置，返回 LocationId.
-    pub fn insert_location(&self, course_id: i64, location: &Location) -> i64 {
+    pub fn insert_location(&self, course_id: i64, location: &Location) -> Result<i64, Error> {
         // 为指定课程添加位置。
         let mut lid = 0_i64;
-        loop {
-            if self.has_location(lid) {
-                lid += 1;
-                continue;
-            }
-            self.add_location_or(lid, course_id, location, |_, _, _, _| {});
-            break;
+        while self.has_location(lid)? {
+            lid += 1;
         }
-        lid
+        self.add_location_or(lid, course_id, location, |_, _, _, _| Ok(()))?;
+        Ok(lid)
     }
-    pub fn delete_location(&self, location_id: i64) {
-        self.db
-            .execute(format!(
+    /// 删除位置及其别名，所有修改在同一事务中进行。
+    pub fn delete_location(&self, location_id: i64) -> Result<(), Error> {
+        self.db.transaction(|| {
+            self.db.execute(format!(
                 "DELETE FROM {} WHERE lid={location_id};",
                 Self::TABLE_NAME
-            ))
-            .unwrap();
-        let alias_table = AliasTable::from_ref(self.db);
-        let aliases = alias_table.get_aliases(location_id);
-        for alias in aliases {
-            alias_table.delete_alias(&alias)
-        }
+            ))?;
+            let alias_table = AliasTable::from_ref(self.db);
+            for alias in alias_table.get_aliases(location_id)? {
+                alias_table.delete_alias(&alias)?;
+            }
+            Ok(())
+        })
     }
     /// location_id, (course_id, location)
-    pub fn get_locations(&self) -> HashMap<i64, (i64, Location)> {
+    pub fn get_locations(&self) -> Result<HashMap<i64, (i64, Location)>, Error> {
         let mut query = self
             .db
-            .prepare(format!("SELECT * FROM {};", Self::TABLE_NAME))
-            .unwrap();
+            .prepare(format!("SELECT * FROM {};", Self::TABLE_NAME))?;
         let mut location_map = HashMap::new();
-        for c in query.iter() {
-            if let Ok(row) = c {
-                let os_id = row.read("lid");
-                let addr = row.read("addr");
-                let lat = row.read("lat");
-                let lon = row.read("lon");
-                let alt = row.read("alt");
-                let course_id = row.read("courseid");
-                location_map.insert(os_id, (course_id, Location::new(addr, lon, lat, alt)));
-            } else {
-                warn!("位置解析行出错：{c:?}.");
-            }
+        while let sqlite::State::Row = query.next()? {
+            let location_id = query.read("lid")?;
+            let course_id = query.read("courseid")?;
+            location_map.insert(location_id, (course_id, Self::read_location(&query)?));
         }
-        location_map
+        Ok(location_map)
     }
-    pub fn get_location(&self, location_id: i64) -> (i64, Location) {
+    /// 获取位置及其绑定的课程号，位置不存在时返回 `None`.
+    pub fn get_location(&self, location_id: i64) -> Result<Option<(i64, Location)>, Error> {
         let mut query = self
             .db
-            .prepare(format!("SELECT * FROM {} WHERE lid=?;", Self::TABLE_NAME))
-            .unwrap();
-        query.bind((1, location_id)).unwrap();
-        let c: Vec<sqlite::Row> = query
-            .iter()
-            .filter_map(|e| if let Ok(e) = e { Some(e) } else { None })
-            .collect();
-        let row = &c[0];
-        let addr = row.read("addr");
-        let lat = row.read("lat");
-        let lon = row.read("lon");
-        let alt = row.read("alt");
-        let course_id = row.read("courseid");
-        (course_id, Location::new(addr, lon, lat, alt))
+            .prepare(format!("SELECT * FROM {} WHERE lid=?;", Self::TABLE_NAME))?;
+        query.bind((1, location_id))?;
+        match query.next()? {
+            sqlite::State::Row => Ok(Some((
+                query.read("courseid")?,
+                Self::read_location(&query)?,
+            ))),
+            sqlite::State::Done => Ok(None),
+        }
     }
-    pub fn get_location_by_alias(&self, alias: &str) -> Option<Location> {
-        AliasTable::from_ref(self.db)
-            .get_location_id(alias)
-            .map(|id| self.get_location(id).1)
+    pub fn get_location_by_alias(&self, alias: &str) -> Result<Option<Location>, Error> {
+        match AliasTable::from_ref(self.db).get_location_id(alias)? {
+            Some(id) => Ok(self.get_location(id)?.map(|(_, location)| location)),
+            None => Ok(None),
+        }
     }
-    pub fn get_location_map_by_course(&self, course_id: i64) -> HashMap<i64, Location> {
-        let mut query = self
-            .db
-            .prepare(format!(
-                "SELECT * FROM {} WHERE courseid=?;",
-                Self::TABLE_NAME
-            ))
-            .unwrap();
-        query.bind((1, course_id)).unwrap();
+    pub fn get_location_map_by_course(
+        &self,
+        course_id: i64,
+    ) -> Result<HashMap<i64, Location>, Error> {
+        let mut query = self.db.prepare(format!(
+            "SELECT * FROM {} WHERE courseid=?;",
+            Self::TABLE_NAME
+        ))?;
+        query.bind((1, course_id))?;
         let mut location_map = HashMap::new();
-        for c in query.iter() {
-            if let Ok(row) = c {
-                let location_id = row.read("lid");
-                let addr = row.read("addr");
-                let lon = row.read("lon");
-                let lat = row.read("lat");
-                let alt = row.read("alt");
-                location_map.insert(location_id, Location::new(addr, lon, lat, alt));
-            } else {
-                warn!("位置解析行出错：{c:?}.");
-            }
+        while let sqlite::State::Row = query.next()? {
+            location_map.insert(query.read("lid")?, Self::read_location(&query)?);
         }
-        location_map
+        Ok(location_map)
     }
-    pub fn get_location_list_by_course(&self, course_id: i64) -> Vec<Location> {
-        let mut query = self
-            .db
-            .prepare(format!(
-                "SELECT * FROM {} WHERE courseid=?;",
-                Self::TABLE_NAME
-            ))
-            .unwrap();
-        query.bind((1, course_id)).unwrap();
+    pub fn get_location_list_by_course(&self, course_id: i64) -> Result<Vec<Location>, Error> {
+        let mut query = self.db.prepare(format!(
+            "SELECT * FROM {} WHERE courseid=?;",
+            Self::TABLE_NAME
+        ))?;
+        query.bind((1, course_id))?;
         let mut location_list = Vec::new();
-        for c in query.iter() {
-            if let Ok(row) = c {
-                let addr = row.read("addr");
-                let lat = row.read("lat");
-                let lon = row.read("lon");
-                let alt = row.read("alt");
-                location_list.push(Location::new(addr, lon, lat, alt));
-            } else {
-                warn!("位置解析行出错：{c:?}.");
-            }
+        while let sqlite::State::Row = query.next()? {
+            location_list.push(Self::read_location(&query)?);
         }
-        location_list
+        Ok(location_list)
+    }
+    fn read_location(query: &sqlite::Statement) -> Result<Location, Error> {
+        let addr: String = query.read("addr")?;
+        let lon: String = query.read("lon")?;
+        let lat: String = query.read("lat")?;
+        let alt: String = query.read("alt")?;
+        Ok(Location::new(&addr, &lon, &lat, &alt))
     }
 }
 
@@ -230,42 +382,75 @@ impl<'a> DataBaseTableTrait<'a> for LocationTable<'a> {
         Self { db }
     }
 
-    fn import(db: &'a DataBase, data: String) -> Self {
-        let location_table = Self::from_ref(db);
+    /// 导入位置及其别名。除旧格式外，还支持 JSON, TOML 和 CSV 格式。
+    fn import(db: &'a DataBase, data: String, format: DataFormat) -> Result<Self, Error> {
+        let location_table = db.add_table::<Self>()?;
         let alias_table = AliasTable::from_ref(db);
-        let data = cxsign_store::parse::<cxsign_error::Error, LocationAndAliasesPair>(data);
-        for LocationAndAliasesPair {
-            course,
-            location,
-            aliases,
-        } in data
-        {
-            let location_id = location_table.insert_location(course, &location);
-            for alias in aliases {
-                if !alias.is_empty() {
-                    alias_table.add_alias_or(&alias, location_id, |t, a, l| {
-                        t.update_alias(a, l);
-                    })
+        let data = match format {
+            DataFormat::Legacy => cxsign_store::parse::<Error, LocationAndAliasesPair>(data),
+            DataFormat::Csv => {
+                cxsign_store::from_csv::<LocationCsvRow, LocationRecord>(&data, Into::into)?
+                    .into_iter()
+                    .map(LocationAndAliasesPair::from)
+                    .collect()
+            }
+            format => {
+                cxsign_store::from_structured::<LocationRecord>(Self::TABLE_NAME, &data, format)?
+                    .into_iter()
+                    .map(LocationAndAliasesPair::from)
+                    .collect()
+            }
+        };
+        db.transaction(|| {
+            for LocationAndAliasesPair {
+                course,
+                location,
+                aliases,
+            } in data
+            {
+                let location_id = location_table.insert_location(course, &location)?;
+                for alias in aliases {
+                    if !alias.is_empty() {
+                        alias_table.add_alias_or(&alias, location_id, AliasTable::update_alias)?;
+                    }
                 }
             }
-        }
-        location_table
+            Ok(())
+        })?;
+        Ok(location_table)
     }
 
-    fn export(&self) -> String {
+    fn export(&self, format: DataFormat) -> Result<String, Error> {
         let alias_table = AliasTable::from_ref(self);
-        let data = self
-            .get_locations()
+        let mut locations = self.get_locations()?.into_iter().collect::<Vec<_>>();
+        locations.sort_by_key(|(location_id, _)| *location_id);
+        let data = locations
             .into_iter()
             .map(|(location_id, (course, location))| {
-                let aliases = alias_table.get_aliases(location_id);
-                LocationAndAliasesPair {
+                let aliases = alias_table.get_aliases(location_id)?;
+                Ok(LocationAndAliasesPair {
                     course,
                     location,
                     aliases,
-                }
-            });
-        cxsign_store::to_string(data)
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        match format {
+            DataFormat::Legacy => Ok(cxsign_store::to_string(data.into_iter())),
+            DataFormat::Csv => cxsign_store::to_csv(
+                data.into_iter()
+                    .map(|pair| LocationCsvRow::try_from(LocationRecord::from(pair)))
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            format => cxsign_store::to_structured(
+                Self::TABLE_NAME,
+                &data
+                    .into_iter()
+                    .map(LocationRecord::from)
+                    .collect::<Vec<_>>(),
+                format,
+            ),
+        }
     }
 }
 impl<'a> Deref for LocationTable<'a> {
@@ -275,3 +460,121 @@ impl<'a> Deref for LocationTable<'a> {
         self.db
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::store::location::LocationTable;
+    use crate::Location;
+    use cxsign_store::{AliasTable, DataBase, DataBaseTableTrait, DataFormat};
+
+    fn sorted_export(db: &DataBase) -> Vec<(i64, String, Vec<String>)> {
+        let table = db.add_table::<LocationTable>().unwrap();
+        let alias_table = db.add_table::<AliasTable>().unwrap();
+        let mut locations = table
+            .get_locations()
+            .unwrap()
+            .into_iter()
+            .map(|(location_id, (course, location))| {
+                let mut aliases = alias_table.get_aliases(location_id).unwrap();
+                aliases.sort();
+                (course, location.to_string(), aliases)
+            })
+            .collect::<Vec<_>>();
+        locations.sort();
+        locations
+    }
+
+    #[test]
+    fn structured_round_trip() {
+        let db = DataBase::in_memory().unwrap();
+        // 旧格式仍然可以导入。
+        LocationTable::import(
+            &db,
+            "123$教学楼,116.397,39.909,50$教室/三楼\n".to_owned(),
+            DataFormat::Legacy,
+        )
+        .unwrap();
+        let table = db.add_table::<LocationTable>().unwrap();
+        let location_id = table.get_locations().unwrap().into_keys().next().unwrap();
+        let mut location = table.get_location(location_id).unwrap().unwrap().1;
+        // 旧格式无法表示含有 `$`, `,` 和 `/` 的地址。
+        location.set_addr("A$B,C/D");
+        table.update_location(location_id, -1, &location).unwrap();
+        let expected = sorted_export(&db);
+
+        for format in [DataFormat::Json, DataFormat::Toml, DataFormat::Csv] {
+            let data = table.export(format).unwrap();
+            let other = DataBase::in_memory().unwrap();
+            LocationTable::import(&other, data, format).unwrap();
+            assert_eq!(sorted_export(&other), expected, "{format}");
+        }
+    }
+
+    #[test]
+    fn add_or_falls_back_on_conflict() {
+        let db = DataBase::in_memory().unwrap();
+        let table = db.add_table::<LocationTable>().unwrap();
+        let location = Location::parse("教学楼,116.397,39.909,50").unwrap();
+        let other = Location::parse("图书馆,116.397,39.909,50").unwrap();
+        let conflicts = std::cell::Cell::new(0);
+        table
+            .add_location_or(1, 123, &location, |_, _, _, _| unreachable!())
+            .unwrap();
+        table
+            .add_location_or(1, 123, &other, |table, location_id, course_id, location| {
+                table.update_location(location_id, course_id, location)
+            })
+            .unwrap();
+        assert_eq!(
+            table.get_location(1).unwrap().unwrap().1.get_addr(),
+            "图书馆"
+        );
+        let alias_table = db.add_table::<AliasTable>().unwrap();
+        for location_id in [1, 2] {
+            alias_table
+                .add_alias_or("教室", location_id, |_, _, _| {
+                    conflicts.set(conflicts.get() + 1);
+                    Ok(())
+                })
+                .unwrap();
+        }
+        assert_eq!(conflicts.get(), 1);
+        assert_eq!(alias_table.get_aliases(1).unwrap(), vec!["教室"]);
+    }
+
+    #[test]
+    fn invalid_rows_are_reported() {
+        let db = DataBase::in_memory().unwrap();
+        let e = match LocationTable::import(
+            &db,
+            "course,addr,lon,lat,alt,aliases\n\
+            123,教学楼,116.397,39.909,50,教室\n\
+            123,图书馆,116.397,139.9,50,\n"
+                .to_owned(),
+            DataFormat::Csv,
+        ) {
+            Err(e) => e.to_string(),
+            Ok(_) => panic!("应导入失败"),
+        };
+        assert!(e.contains("第 3 行的 `lat` 字段无效"), "{e}");
+        let e = match LocationTable::import(
+            &db,
+            "course,addr,lon,lat,alt,aliases\n\
+            123,教学楼,116.397,39.909,50,教室\n\
+            -1,图书馆,116.397,39.9,50,教室\n"
+                .to_owned(),
+            DataFormat::Csv,
+        ) {
+            Err(e) => e.to_string(),
+            Ok(_) => panic!("应导入失败"),
+        };
+        assert!(e.contains("第 3 行的 `aliases` 字段与第 2 行重复"), "{e}");
+        // 有误时不导入任何位置。
+        assert!(db
+            .add_table::<LocationTable>()
+            .unwrap()
+            .get_locations()
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -1,81 +1,86 @@
+use cxsign_transport::endpoint::{Endpoint, Host};
+use cxsign_transport::{Transport, TransportExt};
 use log::warn;
-use ureq::{Agent, Response};
-static PPT_SIGN: &str = "https://mobilelearn.chaoxing.com/pptSign/stuSignajax";
+use ureq::Response;
+static PPT_SIGN: Endpoint = Endpoint::new(Host::MobileLearn, "/pptSign/stuSignajax");
 // // web 聊天页
 // static WEB_IM: &str = "https://im.chaoxing.com/webim/me";
 
 // 无课程群聊的预签到
-static CHAT_GROUP_PRE_SIGN: &str = "https://mobilelearn.chaoxing.com/sign/preStuSign";
+static CHAT_GROUP_PRE_SIGN: Endpoint = Endpoint::new(Host::MobileLearn, "/sign/preStuSign");
 pub fn chat_group_pre_sign(
-    client: &Agent,
+    client: &dyn Transport,
     active_id: &str,
     uid: &str,
     chat_id: &str,
     tuid: &str,
-) -> Result<Response, Box<ureq::Error>> {
+) -> Result<Response, cxsign_error::Error> {
     let url = CHAT_GROUP_PRE_SIGN;
     let url = format!("{url}?activeId={active_id}&code=&uid={uid}&courseId=null&classId=0&general=0&chatId={chat_id}&appType=0&tid={tuid}&atype=null&sys=0");
-    Ok(client.get(&url).call()?)
+    client.get(&url).call()
 }
 // 无课程群聊的签到
-static CHAT_GROUP_SIGN: &str = "https://mobilelearn.chaoxing.com/sign/stuSignajax";
+static CHAT_GROUP_SIGN: Endpoint = Endpoint::new(Host::MobileLearn, "/sign/stuSignajax");
 pub fn chat_group_general_sign(
-    client: &Agent,
+    client: &dyn Transport,
     active_id: &str,
     uid: &str,
-) -> Result<Response, Box<ureq::Error>> {
+) -> Result<Response, cxsign_error::Error> {
     let url = CHAT_GROUP_SIGN;
     let url = format!("{url}?activeId={active_id}&uid={uid}&clientip=");
-    Ok(client.get(&url).call()?)
+    client.get(&url).call()
 }
 
 pub fn chat_group_photo_sign(
-    client: &Agent,
+    client: &dyn Transport,
     active_id: &str,
     uid: &str,
     object_id: &str,
-) -> Result<Response, Box<ureq::Error>> {
+) -> Result<Response, cxsign_error::Error> {
     let url = CHAT_GROUP_SIGN;
     let url = format!("{url}?activeId={active_id}&uid={uid}&clientip=&useragent=&latitude=-1&longitude=-1&fid=0&objectId={object_id}");
-    Ok(client.get(&url).call()?)
+    client.get(&url).call()
 }
 pub fn chat_group_location_sign(
-    client: &Agent,
+    client: &dyn Transport,
     address: &str,
     active_id: &str,
     uid: &str,
     lat: &str,
     lon: &str,
-) -> Result<Response, Box<ureq::Error>> {
+) -> Result<Response, cxsign_error::Error> {
     let address =
         percent_encoding::utf8_percent_encode(address, percent_encoding::NON_ALPHANUMERIC)
             .to_string();
     let body = format!(
         r#"address={address}&activeId={active_id}&uid={uid}&clientip=&useragent=&latitude={lat}&longitude={lon}&fid=&ifTiJiao=1"#
     );
-    Ok(client
-        .post(PPT_SIGN)
+    client
+        .post(&PPT_SIGN.url())
         .set(
             "Content-Type",
             "application/x-www-form-urlencoded; charset=UTF-8",
         )
-        .send_string(&body)?)
+        .send_string(&body)
 }
 pub fn chat_group_signcode_sign(
-    client: &Agent,
+    client: &dyn Transport,
     active_id: &str,
     uid: &str,
     signcode: &str,
-) -> Result<Response, Box<ureq::Error>> {
+) -> Result<Response, cxsign_error::Error> {
     warn!("`chat_group_signcode_sign` 该函数需要测试！");
     let url =
         format!("{CHAT_GROUP_SIGN}?activeId={active_id}&uid={uid}&clientip=&signCode={signcode}");
-    Ok(client.get(&url).call()?)
+    client.get(&url).call()
 }
 
-static AUTO_REFRESH_SIGN_LIST: &str =
-    "https://mobilelearn.chaoxing.com/pptSign/autoRefeashSignList4Json2";
-pub fn get_signed_list(client: &Agent, active_id: &str) -> Result<Response, Box<ureq::Error>> {
+static AUTO_REFRESH_SIGN_LIST: Endpoint =
+    Endpoint::new(Host::MobileLearn, "/pptSign/autoRefeashSignList4Json2");
+pub fn get_signed_list(
+    client: &dyn Transport,
+    active_id: &str,
+) -> Result<Response, cxsign_error::Error> {
     let url = format!("{AUTO_REFRESH_SIGN_LIST}?activeId={active_id}");
-    Ok(client.get(&url).call()?)
+    client.get(&url).call()
 }
@@ -0,0 +1,228 @@
+//! Cookies 的查看，以及与浏览器通用的 Netscape `cookies.txt` 格式之间的导入导出。
+//!
+//! 导入浏览器中已登录的 Cookies 后，可以不保存密码而直接使用该会话。
+
+use crate::storage::now_secs;
+use cookie_store::{Cookie, CookieDomain, CookieExpiration, CookieStore};
+use cxsign_error::Error;
+
+/// 与登录状态相关的 Cookies, 其中最早过期者决定了会话的过期时间。
+const AUTH_COOKIES: [&str; 6] = ["_uid", "UID", "vc", "vc3", "uf", "p_auth_token"];
+/// Netscape 格式中标记 HttpOnly 的前缀。
+const HTTP_ONLY_PREFIX: &str = "#HttpOnly_";
+
+/// 单个 Cookie 的信息。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CookieInfo {
+    pub name: String,
+    pub value: String,
+    /// 不带前导 `.` 的域名。
+    pub domain: String,
+    /// 是否同时发送给子域名，即设置了 `Domain` 属性。
+    pub include_subdomains: bool,
+    pub path: String,
+    pub secure: bool,
+    pub http_only: bool,
+    /// 过期时间（Unix 时间戳，秒），为 `None` 时在会话结束时过期。
+    pub expires_at: Option<i64>,
+}
+
+impl CookieInfo {
+    fn from_cookie(cookie: &Cookie) -> Self {
+        let (domain, include_subdomains) = match &cookie.domain {
+            CookieDomain::HostOnly(host) => (host.clone(), false),
+            CookieDomain::Suffix(suffix) => (suffix.clone(), true),
+            CookieDomain::NotPresent | CookieDomain::Empty => (String::new(), false),
+        };
+        let expires_at = match &cookie.expires {
+            CookieExpiration::AtUtc(time) => Some(time.unix_timestamp()),
+            CookieExpiration::SessionEnd => None,
+        };
+        Self {
+            name: cookie.name().to_owned(),
+            value: cookie.value().to_owned(),
+            domain,
+            include_subdomains,
+            path: String::from(&cookie.path),
+            secure: cookie.secure().unwrap_or(false),
+            http_only: cookie.http_only().unwrap_or(false),
+            expires_at,
+        }
+    }
+    /// 是否为与登录状态相关的 Cookie.
+    pub fn is_auth(&self) -> bool {
+        AUTH_COOKIES.contains(&self.name.as_str())
+    }
+    fn to_netscape_line(&self) -> String {
+        let bool_str = |b: bool| if b { "TRUE" } else { "FALSE" };
+        format!(
+            "{}{}{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            if self.http_only { HTTP_ONLY_PREFIX } else { "" },
+            if self.include_subdomains { "." } else { "" },
+            self.domain,
+            bool_str(self.include_subdomains),
+            self.path,
+            bool_str(self.secure),
+            self.expires_at.unwrap_or(0),
+            self.name,
+            self.value
+        )
+    }
+    fn from_netscape_line(line: &str) -> Option<Self> {
+        let (http_only, line) = match line.strip_prefix(HTTP_ONLY_PREFIX) {
+            Some(line) => (true, line),
+            None => (false, line),
+        };
+        let fields = line.split('\t').collect::<Vec<_>>();
+        if !(6..=7).contains(&fields.len()) {
+            return None;
+        }
+        let parse_bool = |s: &str| match s.to_ascii_uppercase().as_str() {
+            "TRUE" => Some(true),
+            "FALSE" => Some(false),
+            _ => None,
+        };
+        let domain = fields[0].trim_start_matches('.');
+        let expires_at = fields[4].parse::<i64>().ok()?;
+        if domain.is_empty() || fields[5].is_empty() {
+            return None;
+        }
+        Some(Self {
+            name: fields[5].to_owned(),
+            value: fields.get(6).copied().unwrap_or_default().to_owned(),
+            domain: domain.to_owned(),
+            include_subdomains: parse_bool(fields[1])?,
+            path: fields[2].to_owned(),
+            secure: parse_bool(fields[3])?,
+            http_only,
+            expires_at: (expires_at > 0).then_some(expires_at),
+        })
+    }
+    /// 转为 `Set-Cookie` 头的格式，过期时间以相对于当前时间的 `Max-Age` 表示。
+    fn to_set_cookie(&self, now: i64) -> String {
+        let mut set_cookie = format!("{}={}; Path={}", self.name, self.value, self.path);
+        if self.include_subdomains {
+            set_cookie += &format!("; Domain={}", self.domain);
+        }
+        if self.secure {
+            set_cookie += "; Secure";
+        }
+        if self.http_only {
+            set_cookie += "; HttpOnly";
+        }
+        if let Some(expires_at) = self.expires_at {
+            set_cookie += &format!("; Max-Age={}", expires_at - now);
+        }
+        set_cookie
+    }
+}
+
+/// 列出所有未过期的 Cookies.
+pub fn inspect_cookies(cookie_store: &CookieStore) -> Vec<CookieInfo> {
+    cookie_store
+        .iter_unexpired()
+        .map(CookieInfo::from_cookie)
+        .collect()
+}
+
+/// 估计会话的过期时间（Unix 时间戳，秒），即与登录状态相关的 Cookies 中最早的过期时间。
+///
+/// 没有这些 Cookies 时使用所有 Cookies 中最早的过期时间。均在会话结束时过期时返回 `None`.
+pub fn session_expires_at(cookie_store: &CookieStore) -> Option<i64> {
+    let cookies = inspect_cookies(cookie_store);
+    let earliest = |auth_only: bool| {
+        cookies
+            .iter()
+            .filter(|cookie| !auth_only || cookie.is_auth())
+            .filter_map(|cookie| cookie.expires_at)
+            .min()
+    };
+    if cookies.iter().any(CookieInfo::is_auth) {
+        earliest(true)
+    } else {
+        earliest(false)
+    }
+}
+
+/// 将未过期的 Cookies 导出为 Netscape `cookies.txt` 格式。
+pub fn to_netscape(cookie_store: &CookieStore) -> String {
+    let mut text = "# Netscape HTTP Cookie File\n".to_owned();
+    for cookie in inspect_cookies(cookie_store) {
+        text += &cookie.to_netscape_line();
+        text.push('\n');
+    }
+    text
+}
+
+/// 从 Netscape `cookies.txt` 格式导入 Cookies, 已过期的 Cookies 会被忽略。
+pub fn from_netscape(text: &str) -> Result<CookieStore, Error> {
+    let now = now_secs();
+    let mut cookie_store = CookieStore::default();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() || (line.starts_with('#') && !line.starts_with(HTTP_ONLY_PREFIX))
+        {
+            continue;
+        }
+        let cookie = CookieInfo::from_netscape_line(line).ok_or_else(|| {
+            Error::ParseError(format!("Cookies 第 {} 行格式错误：{line}.", n + 1))
+        })?;
+        if cookie
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= now)
+        {
+            continue;
+        }
+        let url = url::Url::parse(&format!("https://{}{}", cookie.domain, cookie.path))
+            .map_err(|e| Error::ParseError(format!("Cookies 第 {} 行域名错误：{e}.", n + 1)))?;
+        cookie_store
+            .parse(&cookie.to_set_cookie(now), &url)
+            .map_err(|e| Error::ParseError(format!("Cookies 第 {} 行导入失败：{e}.", n + 1)))?;
+    }
+    Ok(cookie_store)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cookie_jar::{from_netscape, inspect_cookies, session_expires_at, to_netscape};
+    use crate::storage::now_secs;
+
+    #[test]
+    fn netscape_round_trip() {
+        let expires_at = now_secs() + 3600;
+        let text = format!(
+            "# Netscape HTTP Cookie File\n\n\
+            .chaoxing.com\tTRUE\t/\tFALSE\t{expires_at}\t_uid\t10001\n\
+            #HttpOnly_.chaoxing.com\tTRUE\t/\tTRUE\t{}\tvc3\ttoken\n\
+            i.chaoxing.com\tFALSE\t/base\tFALSE\t0\tJSESSIONID\tabc\n\
+            .chaoxing.com\tTRUE\t/\tFALSE\t1\texpired\tx\n",
+            expires_at + 60
+        );
+        let cookie_store = from_netscape(&text).unwrap();
+        let mut cookies = inspect_cookies(&cookie_store);
+        cookies.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(cookies.len(), 3);
+        let session_cookie = &cookies[0];
+        assert_eq!(session_cookie.name, "JSESSIONID");
+        assert_eq!(session_cookie.domain, "i.chaoxing.com");
+        assert!(!session_cookie.include_subdomains);
+        assert_eq!(session_cookie.path, "/base");
+        assert_eq!(session_cookie.expires_at, None);
+        let vc3 = &cookies[2];
+        assert!(vc3.http_only && vc3.secure && vc3.include_subdomains);
+        let expires = session_expires_at(&cookie_store).unwrap();
+        assert!((expires - expires_at).abs() <= 1);
+
+        let exported = to_netscape(&cookie_store);
+        let mut reimported = inspect_cookies(&from_netscape(&exported).unwrap());
+        reimported.sort_by(|a, b| a.name.cmp(&b.name));
+        for (a, b) in cookies.iter().zip(&reimported) {
+            assert_eq!(
+                (&a.name, &a.value, &a.domain, &a.path),
+                (&b.name, &b.value, &b.domain, &b.path)
+            );
+            assert_eq!((a.secure, a.http_only), (b.secure, b.http_only));
+        }
+        assert!(from_netscape("chaoxing.com\tTRUE\t/\n").is_err());
+    }
+}
@@ -1,5 +1,5 @@
 use cookie_store::Cookie;
-use ureq::Agent;
+use cxsign_transport::Transport;
 #[allow(non_snake_case)]
 #[derive(Debug, Clone)]
 pub struct UserCookies {
@@ -21,7 +21,7 @@ pub struct UserCookies {
 }
 
 impl UserCookies {
-    pub fn new(client: &Agent) -> Self {
+    pub fn new(client: &dyn Transport) -> Self {
         let cookies = {
             let mut cookies = Vec::new();
             for c in client.cookie_store().iter_any() {
@@ -131,9 +131,7 @@ impl UserCookies {
                 "_uid" => {
                     _uid = c.value().into();
                 }
-                _ => {
-                    fid = c.value().into();
-                }
+                _ => {}
             }
         }
         UserCookies {
@@ -167,3 +165,21 @@ impl Default for UserCookies {
         Self::create("-1", "")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::UserCookies;
+    use cookie_store::Cookie;
+
+    #[test]
+    fn other_cookies_do_not_override_fid() {
+        let url = url::Url::parse("https://passport2.chaoxing.com/").unwrap();
+        let cookies = ["fid=1234", "_uid=10001", "vc3=token", "JSESSIONID=abc"]
+            .into_iter()
+            .map(|c| Cookie::parse(c, &url).unwrap())
+            .collect();
+        let cookies = UserCookies::from_cookies_vec(cookies);
+        assert_eq!(cookies.get_fid(), "1234");
+        assert_eq!(cookies.get_uid(), "10001");
+    }
+}
@@ -1,8 +1,16 @@
 #![feature(map_try_insert)]
+#![feature(let_chains)]
 
+mod cookie_jar;
 mod cookies;
 pub mod protocol;
+mod relogin;
 mod session;
+mod storage;
 
+pub use cookie_jar::*;
 pub use cookies::*;
+pub use cxsign_login::SessionConfig;
+pub use relogin::ReloginError;
 pub use session::*;
+pub use storage::{SessionRecord, SessionStorage};
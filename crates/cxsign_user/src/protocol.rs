@@ -1,8 +1,14 @@
-use ureq::{Agent, Response};
+use cxsign_transport::endpoint::{Endpoint, Host};
+use cxsign_transport::{Transport, TransportExt};
+use ureq::Response;
 
 // 账号设置页
-static ACCOUNT_MANAGE: &str = "https://passport2.chaoxing.com/mooc/accountManage";
+static ACCOUNT_MANAGE: Endpoint =
+    Endpoint::new(Host::Passport2, "/mooc/accountManage").idempotent();
 
-pub fn account_manage(client: &Agent) -> Result<Response, Box<ureq::Error>> {
-    Ok(client.get(ACCOUNT_MANAGE).call()?)
+pub fn account_manage(client: &dyn Transport) -> Result<Response, cxsign_error::Error> {
+    client
+        .get(&ACCOUNT_MANAGE.url())
+        .endpoint(&ACCOUNT_MANAGE)
+        .call()
 }
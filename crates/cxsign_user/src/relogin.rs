@@ -0,0 +1,183 @@
+use crate::storage::{now_secs, SessionRecord, SessionStorage};
+use cookie_store::CookieStore;
+use cxsign_error::{Error, LoginOutcome};
+use cxsign_transport::{buffer_response, response_url, Request, Transport};
+use log::{info, warn};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use ureq::Response;
+
+/// 会话失效时跳转到的登录页的标题。
+const LOGIN_PAGE_TITLE: &str = "<title>用户登录</title>";
+
+/// 无法重新登录的原因。
+#[derive(Debug, thiserror::Error)]
+pub enum ReloginError {
+    /// 服务器拒绝登录，如密码已被修改或账号被锁定，原因参见 [`LoginOutcome`].
+    #[error("服务器拒绝登录：{0}")]
+    Rejected(LoginOutcome),
+    /// 会话已失效，且账号没有保存密码（如通过导入 Cookies 添加的账号），需要重新导入 Cookies.
+    #[error("会话已过期，请重新导入 Cookies")]
+    Expired,
+    /// 网络错误，可以稍后重试。
+    #[error("网络错误：{0}")]
+    Network(Error),
+    /// 其他错误，如响应不符合预期。
+    #[error(transparent)]
+    Other(Error),
+}
+
+impl From<Error> for ReloginError {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::LoginRejected(outcome) => ReloginError::Rejected(outcome),
+            Error::SessionExpired => ReloginError::Expired,
+            #[allow(deprecated)]
+            Error::LoginError(msg) => ReloginError::Rejected(LoginOutcome::Unknown(msg)),
+            Error::TransportError(_)
+            | Error::ResponseReadError { .. }
+            | Error::HttpStatusError { .. } => ReloginError::Network(e),
+            e => ReloginError::Other(e),
+        }
+    }
+}
+
+fn is_login_url(url: &str) -> bool {
+    url::Url::parse(url).is_ok_and(|url| {
+        url.host_str()
+            .is_some_and(|host| host.starts_with("passport2."))
+            && matches!(url.path(), "/login" | "/mlogin")
+    })
+}
+
+fn is_html(response: &Response) -> bool {
+    response.content_type().contains("html")
+}
+
+/// 在响应为登录页（即会话已失效）时自动重新登录并重发请求的 [`Transport`].
+///
+/// 登录所得的 Cookies 保存在内层的 [`Transport`] 中。提供了 [`SessionStorage`] 时，
+/// 重新登录后会将新的 Cookies 写入其中。
+///
+/// 没有密码时（参见 [`AutoRelogin::cookies_only`]）不会重新登录，会话失效时返回 [`Error::SessionExpired`].
+#[derive(Debug)]
+pub(crate) struct AutoRelogin {
+    inner: Arc<dyn Transport>,
+    uname: String,
+    enc_passwd: Option<String>,
+    /// 机构账号所属机构的 fid.
+    fid: Option<i64>,
+    storage: Option<Arc<dyn SessionStorage>>,
+    /// 重新登录的次数，用于避免并发的请求重复登录。
+    generation: AtomicU64,
+    lock: Mutex<()>,
+}
+
+impl AutoRelogin {
+    pub(crate) fn new(
+        inner: Arc<dyn Transport>,
+        uname: &str,
+        enc_passwd: &str,
+        fid: Option<i64>,
+        storage: Option<Arc<dyn SessionStorage>>,
+    ) -> Self {
+        Self {
+            inner,
+            uname: uname.to_owned(),
+            enc_passwd: Some(enc_passwd.to_owned()),
+            fid,
+            storage,
+            generation: AtomicU64::new(0),
+            lock: Mutex::new(()),
+        }
+    }
+    /// 用于只有 Cookies 的账号，只检查会话是否失效，不重新登录。
+    pub(crate) fn cookies_only(inner: Arc<dyn Transport>, uname: &str) -> Self {
+        Self {
+            enc_passwd: None,
+            ..Self::new(inner, uname, "", None, None)
+        }
+    }
+    /// 响应是否为登录页。需要读取内容时会重新构造响应。
+    fn check_response(response: Response) -> Result<(Response, bool), Error> {
+        if is_login_url(response_url(&response)) {
+            return Ok((response, true));
+        }
+        if !is_html(&response) {
+            return Ok((response, false));
+        }
+        let (response, body) = buffer_response(response)?;
+        let expired = String::from_utf8_lossy(&body).contains(LOGIN_PAGE_TITLE);
+        Ok((response, expired))
+    }
+    fn relogin(&self, generation: u64) -> Result<(), Error> {
+        let Some(enc_passwd) = &self.enc_passwd else {
+            info!("用户[{}]的会话已失效，且没有保存密码。", self.uname);
+            return Err(Error::SessionExpired);
+        };
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        // 等待锁的过程中其他请求已经重新登录过了。
+        if self.generation.load(Ordering::SeqCst) != generation {
+            return Ok(());
+        }
+        info!("用户[{}]的会话已失效，正在重新登录。", self.uname);
+        cxsign_login::login_account_with(self.inner.as_ref(), self.fid, &self.uname, enc_passwd)?;
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        if let Some(storage) = &self.storage
+            && let Err(e) = self.save(storage.as_ref())
+        {
+            warn!("用户[{}]的 Cookies 保存失败！错误信息：{e}.", self.uname);
+        }
+        Ok(())
+    }
+    fn save(&self, storage: &dyn SessionStorage) -> Result<(), Error> {
+        let mut record =
+            SessionRecord::new(&self.uname, "", &self.inner.cookie_store(), now_secs())?;
+        // 保留缓存的姓名和账号所属机构的 fid, 后者不一定与 Cookies 中的相同。
+        if let Some(saved) = storage.load(&self.uname)? {
+            record.stu_name = saved.stu_name;
+            if !saved.fid.is_empty() {
+                record.fid = saved.fid;
+            }
+        }
+        if let Some(fid) = self.fid {
+            record.fid = fid.to_string();
+        }
+        storage.save(&record)
+    }
+}
+
+impl Transport for AutoRelogin {
+    fn call(&self, request: Request) -> Result<Response, Error> {
+        if is_login_url(&request.url) {
+            return self.inner.call(request);
+        }
+        let generation = self.generation.load(Ordering::SeqCst);
+        let (response, expired) = Self::check_response(self.inner.call(request.clone())?)?;
+        if !expired {
+            return Ok(response);
+        }
+        self.relogin(generation)?;
+        // 不能安全地重复发送的请求（如提交签到）交由调用者确认后再重试。
+        if !request.idempotent {
+            return Err(Error::SessionRenewed);
+        }
+        self.inner.call(request)
+    }
+    fn cookie_store(&self) -> CookieStore {
+        self.inner.cookie_store()
+    }
+    fn cookies_generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst) + self.inner.cookies_generation()
+    }
+}
+
+pub(crate) fn save_cookies(
+    path: &std::path::Path,
+    cookie_store: &CookieStore,
+) -> Result<(), Error> {
+    let mut writer = std::fs::File::create(path).map(std::io::BufWriter::new)?;
+    cookie_store
+        .save_incl_expired_and_nonpersistent_json(&mut writer)
+        .map_err(|e| Error::ParseError(format!("Cookies 保存失败：{e}.")))
+}
@@ -1,23 +1,42 @@
+use crate::relogin::{save_cookies, AutoRelogin, ReloginError};
+use crate::storage::{now_secs, SessionRecord, SessionStorage};
 use crate::{cookies::UserCookies, protocol};
+use cookie_store::CookieStore;
 use cxsign_dir::Dir;
+use cxsign_error::{Error, ResponseExt};
+use cxsign_login::SessionConfig;
+use cxsign_transport::endpoint::Host;
+use cxsign_transport::{record, Request, Transport};
 use log::{info, trace};
-use std::{
-    hash::Hash,
-    ops::{Deref, Index},
-};
-use ureq::Agent;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::{hash::Hash, ops::Index};
+use ureq::Response;
 
+/// 已保存的会话在上次确认有效超过该时长（秒）后，加载时会先请求一次以确认其有效，参见 [`Session::load_or_relogin`].
+pub const SESSION_VALIDATE_AFTER_SECS: i64 = 6 * 60 * 60;
+
+/// 登录后的会话。
+///
+/// uid 从当前的 Cookies 中读取，并在 Cookies 被替换（如自动重新登录）后重新读取，因此也是最新的。
+/// fid 优先使用随账号保存的机构 fid, 没有时同样从 Cookies 中读取。
 #[derive(Debug, Clone)]
 pub struct Session {
-    agent: Agent,
+    transport: Arc<dyn Transport>,
     uname: String,
     stu_name: String,
-    cookies: UserCookies,
+    /// 随账号保存的机构 fid, 参见 [`Session::get_fid`].
+    fid: Option<i64>,
+    config: SessionConfig,
+    /// 上次确认会话有效的时间（Unix 时间戳，秒）。
+    validated_at: i64,
+    /// 从 Cookies 中解析的 uid 和 fid, 以及解析时的 [`Transport::cookies_generation`].
+    user_cookies: Arc<Mutex<Option<(u64, UserCookies)>>>,
 }
 
+// 会话常被用作 `HashMap` 的键，而 uid 可能在重新登录后才能得知，因此以账号区分会话。
 impl PartialEq for Session {
     fn eq(&self, other: &Self) -> bool {
-        self.get_uid() == other.get_uid()
+        self.uname == other.uname
     }
 }
 
@@ -25,55 +44,305 @@ impl Eq for Session {}
 
 impl Hash for Session {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.get_uid().hash(state);
-        self.get_fid().hash(state);
-        self.get_stu_name().hash(state);
+        self.uname.hash(state);
     }
 }
 
 impl Session {
-    pub fn load_json(dir: &Dir, uname: &str) -> Result<Self, Box<ureq::Error>> {
-        let client = cxsign_login::load_json(dir.get_json_file_path(uname));
-        let cookies = UserCookies::new(&client);
-        let stu_name = Self::find_stu_name_in_html(&client)?;
-        info!("用户[{}]加载 Cookies 成功！", stu_name);
+    /// 使用已经持有 Cookies 的 [`Transport`] 构造会话，会请求一次账号设置页以获取姓名。
+    pub fn from_transport(transport: Arc<dyn Transport>, uname: &str) -> Result<Self, Error> {
+        let stu_name = Self::find_stu_name_in_html(transport.as_ref())?;
+        record::add_secret("name", &stu_name);
         Ok(Session {
-            agent: client,
+            transport,
             uname: uname.to_string(),
             stu_name,
-            cookies,
+            fid: None,
+            config: SessionConfig::default(),
+            validated_at: now_secs(),
+            user_cookies: Arc::default(),
         })
     }
-    pub fn relogin(uname: &str, enc_passwd: &str) -> Result<Session, cxsign_error::Error> {
-        let client = cxsign_login::login_enc(uname, enc_passwd)?;
-        let cookies = UserCookies::new(&client);
-        let stu_name = Self::find_stu_name_in_html(&client)?;
-        info!("用户[{}]登录成功！", stu_name);
-        let session = Session {
-            agent: client,
+    /// 使用已保存的数据构造会话，不发送任何请求。
+    ///
+    /// 会话是否有效要到之后的请求中才能知道，姓名可以通过 [`Session::refresh_stu_name`] 更新。
+    pub fn from_parts(transport: Arc<dyn Transport>, uname: &str, stu_name: &str) -> Self {
+        record::add_secret("name", stu_name);
+        Session {
+            transport,
             uname: uname.to_string(),
-            stu_name,
-            cookies,
+            stu_name: stu_name.to_string(),
+            fid: None,
+            config: SessionConfig::default(),
+            validated_at: 0,
+            user_cookies: Arc::default(),
+        }
+    }
+    /// 使用已保存的 Cookies 构造会话，不发送任何请求，参见 [`Session::from_parts`].
+    pub fn from_cookie_store(
+        cookie_store: CookieStore,
+        uname: &str,
+        stu_name: &str,
+        config: &SessionConfig,
+    ) -> Result<Self, Error> {
+        let client = cxsign_login::build_agent_with(cookie_store, config)?;
+        let mut session = Self::from_parts(record::wrap(client), uname, stu_name);
+        session.config = config.clone();
+        Ok(session)
+    }
+    /// 请求账号设置页，更新姓名并确认会话有效。
+    pub fn refresh_stu_name(&mut self) -> Result<&str, Error> {
+        self.stu_name = Self::find_stu_name_in_html(self.transport.as_ref())?;
+        self.validated_at = now_secs();
+        record::add_secret("name", &self.stu_name);
+        Ok(&self.stu_name)
+    }
+    pub fn load_json(dir: &Dir, uname: &str) -> Result<Self, Error> {
+        Self::load_json_with_config(dir, uname, &SessionConfig::default())
+    }
+    /// 使用给定的 [`SessionConfig`] 加载 Cookies.
+    pub fn load_json_with_config(
+        dir: &Dir,
+        uname: &str,
+        config: &SessionConfig,
+    ) -> Result<Self, Error> {
+        let client = cxsign_login::load_json_with_config(dir.get_json_file_path(uname), config)?;
+        let mut session = Self::from_transport(record::wrap(client), uname)?;
+        session.config = config.clone();
+        info!("用户[{}]加载 Cookies 成功！", session.get_stu_name());
+        Ok(session)
+    }
+    /// 从 [`SessionStorage`] 中加载会话，若会话不存在，则使用密码登录。
+    ///
+    /// 已保存的会话在 [`SESSION_VALIDATE_AFTER_SECS`] 内确认过有效时直接加载，不发送请求；
+    /// 否则先请求一次账号设置页，会话失效时重新登录，无法登录（如密码已被修改）时返回错误。
+    /// 所得的会话在之后的请求中发现会话失效时，也会自动重新登录。新的 Cookies 均会写入 `storage`.
+    /// 指定了机构 `fid` 时使用机构账号（学号）登录。
+    pub fn load_or_relogin(
+        storage: Arc<dyn SessionStorage>,
+        uname: &str,
+        enc_passwd: &str,
+        fid: Option<i64>,
+        config: &SessionConfig,
+    ) -> Result<Self, ReloginError> {
+        let mut session =
+            Self::load_or_relogin_with(storage, uname, enc_passwd, fid, |cookie_store| {
+                let client = cxsign_login::build_agent_with(cookie_store, config)?;
+                Ok(record::wrap(client))
+            })?;
+        session.config = config.clone();
+        Ok(session)
+    }
+    /// 同 [`Session::load_or_relogin`], 使用 `make_transport` 以已保存的 Cookies 构造 [`Transport`].
+    pub fn load_or_relogin_with(
+        storage: Arc<dyn SessionStorage>,
+        uname: &str,
+        enc_passwd: &str,
+        fid: Option<i64>,
+        make_transport: impl FnOnce(CookieStore) -> Result<Arc<dyn Transport>, Error>,
+    ) -> Result<Self, ReloginError> {
+        let record = storage.load(uname)?;
+        let cookie_store = match record.as_ref().map(SessionRecord::cookie_store) {
+            Some(Ok(cookie_store)) => cookie_store,
+            Some(Err(e)) => {
+                info!("用户[{uname}]的 Cookies 加载失败，将重新登录。错误信息：{e}.");
+                Default::default()
+            }
+            None => {
+                info!("用户[{uname}]没有保存的会话，将重新登录。");
+                Default::default()
+            }
+        };
+        let client = make_transport(cookie_store)?;
+        if let Some(record) = record
+            && !record.stu_name.is_empty()
+            && !record.uid.is_empty()
+        {
+            let transport =
+                AutoRelogin::new(client, uname, enc_passwd, fid, Some(Arc::clone(&storage)));
+            return Self::from_saved(Arc::new(transport), &record, fid, storage.as_ref());
+        }
+        let session = Self::from_transport_with_relogin(
+            client,
+            uname,
+            enc_passwd,
+            fid,
+            Some(Arc::clone(&storage)),
+        )?;
+        session.store(storage.as_ref())?;
+        Ok(session)
+    }
+    /// 从 [`SessionStorage`] 中加载只有 Cookies、没有保存密码的账号（如通过导入 Cookies 添加的账号）的会话。
+    ///
+    /// 与 [`Session::load_or_relogin`] 相同，太久没有确认过的会话会先请求一次以确认其有效，但不会重新登录：
+    /// 会话不存在或已失效时返回 [`ReloginError::Expired`], 需要重新导入 Cookies.
+    pub fn load_cookies_only(
+        storage: Arc<dyn SessionStorage>,
+        uname: &str,
+        fid: Option<i64>,
+        config: &SessionConfig,
+    ) -> Result<Self, ReloginError> {
+        let mut session = Self::load_cookies_only_with(storage, uname, fid, |cookie_store| {
+            let client = cxsign_login::build_agent_with(cookie_store, config)?;
+            Ok(record::wrap(client))
+        })?;
+        session.config = config.clone();
+        Ok(session)
+    }
+    /// 同 [`Session::load_cookies_only`], 使用 `make_transport` 以已保存的 Cookies 构造 [`Transport`].
+    pub fn load_cookies_only_with(
+        storage: Arc<dyn SessionStorage>,
+        uname: &str,
+        fid: Option<i64>,
+        make_transport: impl FnOnce(CookieStore) -> Result<Arc<dyn Transport>, Error>,
+    ) -> Result<Self, ReloginError> {
+        let Some(record) = storage.load(uname)? else {
+            info!("用户[{uname}]没有保存的会话。");
+            return Err(ReloginError::Expired);
         };
+        let client = make_transport(record.cookie_store()?)?;
+        let transport = AutoRelogin::cookies_only(client, uname);
+        Self::from_saved(Arc::new(transport), &record, fid, storage.as_ref())
+    }
+    /// 使用已保存的会话构造会话，太久没有确认过时先确认其有效，参见 [`SESSION_VALIDATE_AFTER_SECS`].
+    fn from_saved(
+        transport: Arc<dyn Transport>,
+        record: &SessionRecord,
+        fid: Option<i64>,
+        storage: &dyn SessionStorage,
+    ) -> Result<Self, ReloginError> {
+        let mut session = Self::from_parts(transport, &record.uname, &record.stu_name);
+        session.fid = fid;
+        session.validated_at = record.validated_at;
+        if now_secs() - record.validated_at > SESSION_VALIDATE_AFTER_SECS {
+            session.refresh_stu_name()?;
+            session.store(storage)?;
+        }
+        Ok(session)
+    }
+    /// 使用给定的 [`Transport`] 构造会话，会话失效时自动使用密码重新登录。
+    ///
+    /// 提供了 `storage` 时，重新登录后会将新的 Cookies 写入其中。
+    pub fn from_transport_with_relogin(
+        transport: Arc<dyn Transport>,
+        uname: &str,
+        enc_passwd: &str,
+        fid: Option<i64>,
+        storage: Option<Arc<dyn SessionStorage>>,
+    ) -> Result<Self, ReloginError> {
+        let transport = AutoRelogin::new(transport, uname, enc_passwd, fid, storage);
+        let mut session = Self::from_transport(Arc::new(transport), uname)?;
+        session.fid = fid;
+        info!("用户[{}]加载成功！", session.get_stu_name());
+        Ok(session)
+    }
+    /// 从录制目录（参见 [`record`]）中回放响应，构造离线会话，用于测试解析函数。
+    pub fn replay(dir: impl AsRef<std::path::Path>, uname: &str) -> Result<Self, Error> {
+        let replayer = record::Replayer::load(dir)?;
+        Self::from_transport(Arc::new(replayer), uname)
+    }
+    pub fn relogin(uname: &str, enc_passwd: &str) -> Result<Session, Error> {
+        Self::relogin_with_config(uname, enc_passwd, &SessionConfig::default())
+    }
+    /// 使用给定的 [`SessionConfig`] 登录。
+    pub fn relogin_with_config(
+        uname: &str,
+        enc_passwd: &str,
+        config: &SessionConfig,
+    ) -> Result<Session, Error> {
+        Self::relogin_with_fid(uname, enc_passwd, None, config)
+    }
+    /// 使用给定的 [`SessionConfig`] 登录。指定了机构 `fid` 时使用机构账号（学号）登录。
+    pub fn relogin_with_fid(
+        uname: &str,
+        enc_passwd: &str,
+        fid: Option<i64>,
+        config: &SessionConfig,
+    ) -> Result<Session, Error> {
+        let client = cxsign_login::build_agent_with(Default::default(), config)?;
+        let mut session = Self::relogin_account_with(record::wrap(client), uname, enc_passwd, fid)?;
+        session.config = config.clone();
         Ok(session)
     }
-    pub fn login(dir: &Dir, uname: &str, enc_passwd: &str) -> Result<Session, cxsign_error::Error> {
-        let session = Session::relogin(uname, enc_passwd)?;
-        session.store_json(dir);
+    /// 通过给定的 [`Transport`] 登录。
+    pub fn relogin_with(
+        transport: Arc<dyn Transport>,
+        uname: &str,
+        enc_passwd: &str,
+    ) -> Result<Session, Error> {
+        Self::relogin_account_with(transport, uname, enc_passwd, None)
+    }
+    /// 通过给定的 [`Transport`] 登录。指定了机构 `fid` 时使用机构账号（学号）登录。
+    pub fn relogin_account_with(
+        transport: Arc<dyn Transport>,
+        uname: &str,
+        enc_passwd: &str,
+        fid: Option<i64>,
+    ) -> Result<Session, Error> {
+        cxsign_login::login_account_with(transport.as_ref(), fid, uname, enc_passwd)?;
+        let mut session = Self::from_transport(transport, uname)?;
+        session.fid = fid;
+        info!("用户[{}]登录成功！", session.get_stu_name());
+        Ok(session)
+    }
+    pub fn login(dir: &Dir, uname: &str, enc_passwd: &str) -> Result<Session, Error> {
+        Self::login_with_config(dir, uname, enc_passwd, &SessionConfig::default())
+    }
+    pub fn login_with_config(
+        dir: &Dir,
+        uname: &str,
+        enc_passwd: &str,
+        config: &SessionConfig,
+    ) -> Result<Session, Error> {
+        let session = Session::relogin_with_config(uname, enc_passwd, config)?;
+        session.store_json(dir)?;
         Ok(session)
     }
-    pub fn store_json(&self, dir: &Dir) {
-        let store_path = dir.get_json_file_path(self.get_uname());
-        let mut writer = std::fs::File::create(store_path)
-            .map(std::io::BufWriter::new)
-            .unwrap();
-        self.cookie_store().save_json(&mut writer).unwrap();
+    /// 将会话写入 [`SessionStorage`].
+    pub fn store(&self, storage: &dyn SessionStorage) -> Result<(), Error> {
+        storage.save(&self.to_record()?)
+    }
+    pub fn to_record(&self) -> Result<SessionRecord, Error> {
+        let mut record = SessionRecord::new(
+            self.get_uname(),
+            self.get_stu_name(),
+            &self.cookie_store(),
+            self.validated_at,
+        )?;
+        record.fid = self.get_fid();
+        Ok(record)
+    }
+    pub fn store_json(&self, dir: &Dir) -> Result<(), Error> {
+        save_cookies(
+            &dir.get_json_file_path(self.get_uname()),
+            &self.cookie_store(),
+        )
     }
-    pub fn get_uid(&self) -> &str {
-        self.cookies.get_uid()
+    /// 当前 Cookies 中的 uid.
+    fn with_user_cookies<R>(&self, f: impl FnOnce(&UserCookies) -> R) -> R {
+        let generation = self.transport.cookies_generation();
+        let mut cached = self
+            .user_cookies
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        match &*cached {
+            Some((g, cookies)) if *g == generation => f(cookies),
+            _ => f(&cached
+                .insert((generation, UserCookies::new(self.transport.as_ref())))
+                .1),
+        }
     }
-    pub fn get_fid(&self) -> &str {
-        self.cookies.get_fid()
+    pub fn get_uid(&self) -> String {
+        self.with_user_cookies(|cookies| cookies.get_uid().to_owned())
+    }
+    /// 账号所属机构的 fid, 登录或加载时指定了机构 fid 时使用该值，否则为当前 Cookies 中的 fid.
+    ///
+    /// 查询活动和签到时均使用该值。
+    pub fn get_fid(&self) -> String {
+        match self.fid {
+            Some(fid) => fid.to_string(),
+            None => self.with_user_cookies(|cookies| cookies.get_fid().to_owned()),
+        }
     }
 
     pub fn get_stu_name(&self) -> &str {
@@ -82,27 +351,89 @@ impl Session {
     pub fn get_uname(&self) -> &str {
         &self.uname
     }
+    /// 上次确认会话有效的时间（Unix 时间戳，秒），从未确认过时为 `0`.
+    pub fn get_validated_at(&self) -> i64 {
+        self.validated_at
+    }
+    /// 当前所有未过期的 Cookies, 参见 [`inspect_cookies`](crate::inspect_cookies).
+    pub fn get_cookies(&self) -> Vec<crate::CookieInfo> {
+        crate::inspect_cookies(&self.cookie_store())
+    }
+    /// 估计的会话过期时间（Unix 时间戳，秒），参见 [`session_expires_at`](crate::session_expires_at).
+    pub fn get_expires_at(&self) -> Option<i64> {
+        crate::session_expires_at(&self.cookie_store())
+    }
     pub fn get_avatar_url(&self, size: usize) -> String {
-        format!("https://photo.chaoxing.com/p/{}_{}", self.get_uid(), size)
+        format!("{}/p/{}_{}", Host::Photo.url(), self.get_uid(), size)
+    }
+    /// 构造该会话时使用的 [`SessionConfig`], 通过 [`Transport`] 构造的会话为默认配置。
+    pub fn get_config(&self) -> &SessionConfig {
+        &self.config
+    }
+    pub fn get_transport(&self) -> &Arc<dyn Transport> {
+        &self.transport
     }
-    fn find_stu_name_in_html(client: &Agent) -> Result<String, Box<ureq::Error>> {
+    fn find_stu_name_in_html(client: &dyn Transport) -> Result<String, Error> {
         let r = protocol::account_manage(client)?;
-        let html_content = r.into_string().unwrap();
+        let html_content = r.read_string("accountManage")?;
         trace!("{html_content}");
-        let e = html_content.find("colorBlue").unwrap();
-        let html_content = html_content.index(e..html_content.len()).to_owned();
-        let e = html_content.find('>').unwrap() + 1;
-        let html_content = html_content.index(e..html_content.len()).to_owned();
         let name = html_content
-            .index(0..html_content.find('<').unwrap())
-            .trim();
-        Ok(name.to_owned())
+            .find("colorBlue")
+            .map(|e| html_content.index(e..html_content.len()))
+            .and_then(|html| html.find('>').map(|e| html.index(e + 1..html.len())))
+            .and_then(|html| html.find('<').map(|e| html.index(0..e).trim()));
+        name.map(str::to_owned)
+            .ok_or_else(|| Error::unexpected_response("accountManage", &html_content))
     }
 }
 
-impl Deref for Session {
-    type Target = Agent;
-    fn deref(&self) -> &Agent {
-        &self.agent
+/// 异步接口。阻塞的请求通过 [`cxsign_utils::unblock`] 在有界的线程池中进行，不依赖特定的异步运行时。
+#[cfg(feature = "async")]
+impl Session {
+    pub async fn load_json_async(dir: &Dir, uname: &str) -> Result<Self, Error> {
+        let (dir, uname) = (dir.clone(), uname.to_owned());
+        cxsign_utils::unblock(move || Self::load_json(&dir, &uname)).await
+    }
+    pub async fn load_or_relogin_async(
+        storage: Arc<dyn SessionStorage>,
+        uname: &str,
+        enc_passwd: &str,
+        fid: Option<i64>,
+        config: &SessionConfig,
+    ) -> Result<Self, ReloginError> {
+        let (uname, enc_passwd, config) = (uname.to_owned(), enc_passwd.to_owned(), config.clone());
+        cxsign_utils::unblock(move || {
+            Self::load_or_relogin(storage, &uname, &enc_passwd, fid, &config)
+        })
+        .await
+    }
+    pub async fn relogin_async(uname: &str, enc_passwd: &str) -> Result<Session, Error> {
+        let (uname, enc_passwd) = (uname.to_owned(), enc_passwd.to_owned());
+        cxsign_utils::unblock(move || Self::relogin(&uname, &enc_passwd)).await
+    }
+    pub async fn relogin_with_async(
+        transport: Arc<dyn Transport>,
+        uname: &str,
+        enc_passwd: &str,
+    ) -> Result<Session, Error> {
+        let (uname, enc_passwd) = (uname.to_owned(), enc_passwd.to_owned());
+        cxsign_utils::unblock(move || Self::relogin_with(transport, &uname, &enc_passwd)).await
+    }
+    pub async fn login_async(dir: &Dir, uname: &str, enc_passwd: &str) -> Result<Session, Error> {
+        let session = Self::relogin_async(uname, enc_passwd).await?;
+        session.store_json(dir)?;
+        Ok(session)
+    }
+}
+
+impl Transport for Session {
+    fn call(&self, request: Request) -> Result<Response, Error> {
+        self.transport.call(request)
+    }
+    fn cookie_store(&self) -> CookieStore {
+        self.transport.cookie_store()
+    }
+    fn cookies_generation(&self) -> u64 {
+        self.transport.cookies_generation()
     }
 }
@@ -0,0 +1,65 @@
+use crate::UserCookies;
+use cookie_store::CookieStore;
+use cxsign_error::Error;
+use std::fmt::Debug;
+
+/// 持久化的会话数据。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionRecord {
+    pub uname: String,
+    /// 缓存的姓名。
+    pub stu_name: String,
+    pub uid: String,
+    pub fid: String,
+    /// JSON 格式的 Cookies, 参见 [`CookieStore::save_json`].
+    pub cookies: String,
+    /// 上次确认会话有效的时间（Unix 时间戳，秒）。从未确认过时为 `0`.
+    pub validated_at: i64,
+}
+
+impl SessionRecord {
+    /// 从 Cookies 中读取 uid 和 fid 并构造记录。
+    pub fn new(
+        uname: &str,
+        stu_name: &str,
+        cookie_store: &CookieStore,
+        validated_at: i64,
+    ) -> Result<Self, Error> {
+        let user_cookies =
+            UserCookies::from_cookies_vec(cookie_store.iter_any().cloned().collect());
+        let mut cookies = Vec::new();
+        cookie_store
+            .save_incl_expired_and_nonpersistent_json(&mut cookies)
+            .map_err(|e| Error::ParseError(format!("Cookies 序列化失败：{e}.")))?;
+        Ok(Self {
+            uname: uname.to_owned(),
+            stu_name: stu_name.to_owned(),
+            uid: user_cookies.get_uid().to_owned(),
+            fid: user_cookies.get_fid().to_owned(),
+            cookies: String::from_utf8(cookies)
+                .map_err(|e| Error::ParseError(format!("Cookies 序列化失败：{e}.")))?,
+            validated_at,
+        })
+    }
+    pub fn cookie_store(&self) -> Result<CookieStore, Error> {
+        CookieStore::load_json(self.cookies.as_bytes())
+            .map_err(|e| Error::ParseError(format!("Cookies 解析失败：{e}.")))
+    }
+}
+
+/// 会话的存储位置。
+///
+/// 自动重新登录（参见 [`Session::load_or_relogin`](crate::Session::load_or_relogin)）后，
+/// 新的 Cookies 会写入其中。实现者可能在多个线程中同时被调用。
+pub trait SessionStorage: Debug + Send + Sync {
+    fn load(&self, uname: &str) -> Result<Option<SessionRecord>, Error>;
+    fn save(&self, record: &SessionRecord) -> Result<(), Error>;
+}
+
+/// 当前的 Unix 时间戳（秒）。
+pub(crate) fn now_secs() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
@@ -1,3 +1,11 @@
+mod pool;
+#[cfg(feature = "async")]
+mod unblock;
+
+pub use pool::*;
+#[cfg(feature = "async")]
+pub use unblock::*;
+
 use chrono::TimeDelta;
 use log::{info, warn};
 use std::io::Read;
@@ -64,6 +72,32 @@ pub fn inquire_pwd(pwd: Option<String>) -> Option<String> {
         }
     })
 }
+/// 从 `options` 中选择一项，取消或出错时返回 `None`.
+pub fn inquire_select<T: std::fmt::Display>(message: &str, options: Vec<T>) -> Option<T> {
+    match inquire::Select::new(message, options).prompt() {
+        Ok(option) => Some(option),
+        Err(e) => {
+            warn!("未能完成选择：{e}.");
+            None
+        }
+    }
+}
+/// 询问口令。`confirm` 为 `true` 时需要输入两次。
+pub fn inquire_passphrase(message: &str, confirm: bool) -> Option<String> {
+    let prompt = inquire::Password::new(message);
+    let prompt = if confirm {
+        prompt.with_custom_confirmation_message("确认：")
+    } else {
+        prompt.without_confirmation()
+    };
+    match prompt.prompt() {
+        Ok(passphrase) => Some(passphrase),
+        Err(e) => {
+            warn!("输入的口令无法解析：{e}.");
+            None
+        }
+    }
+}
 pub fn get_width_str_should_be(s: &str, width: usize) -> usize {
     if UnicodeWidthStr::width(s) > width {
         width
@@ -81,11 +115,15 @@ pub fn zlib_encode(text: &str) -> Vec<u8> {
     encoder.finish().unwrap()
 }
 pub fn zlib_decode<R: Read>(r: R) -> String {
-    let mut decoder = ZlibDecoder::new(r);
+    try_zlib_decode(r).unwrap()
+}
+/// 同 [`zlib_decode`], 数据损坏或不是 UTF-8 文本时返回错误。
+pub fn try_zlib_decode<R: Read>(r: R) -> std::io::Result<String> {
     use flate2::read::ZlibDecoder;
+    let mut decoder = ZlibDecoder::new(r);
     let mut decompressed_data = String::new();
-    decoder.read_to_string(&mut decompressed_data).unwrap();
-    decompressed_data
+    decoder.read_to_string(&mut decompressed_data)?;
+    Ok(decompressed_data)
 }
 
 #[cfg(test)]
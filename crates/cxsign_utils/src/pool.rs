@@ -0,0 +1,173 @@
+use log::debug;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex, OnceLock};
+use std::time::Duration;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// 默认的最大并发数。
+pub const DEFAULT_MAX_WORKERS: usize = 16;
+/// 空闲线程等待该时长后退出。
+const IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+thread_local! {
+    /// 当前线程所属线程池的地址，不是工作线程时为 `0`.
+    static WORKER_OF: Cell<usize> = const { Cell::new(0) };
+}
+
+#[derive(Default)]
+struct PoolState {
+    queue: VecDeque<Job>,
+    workers: usize,
+    idle: usize,
+}
+
+/// 线程池，用于并发地发送请求。
+///
+/// 线程按需创建，数量不超过 [`WorkerPool::get_max_workers`], 空闲一段时间后退出。
+/// 在工作线程中再次调用同一线程池的 [`WorkerPool::map`] 时会直接在当前线程中依次执行，以免所有线程互相等待。
+pub struct WorkerPool {
+    max_workers: AtomicUsize,
+    state: Mutex<PoolState>,
+    cond: Condvar,
+}
+
+static GLOBAL: OnceLock<Arc<WorkerPool>> = OnceLock::new();
+static BLOCKING: OnceLock<Arc<WorkerPool>> = OnceLock::new();
+
+impl WorkerPool {
+    pub fn new(max_workers: usize) -> Arc<Self> {
+        Arc::new(Self {
+            max_workers: AtomicUsize::new(max_workers.max(1)),
+            state: Mutex::new(PoolState::default()),
+            cond: Condvar::new(),
+        })
+    }
+    /// 获取全局共享的线程池，课程和活动的获取均使用该线程池。
+    pub fn global() -> &'static Arc<Self> {
+        GLOBAL.get_or_init(|| Self::new(DEFAULT_MAX_WORKERS))
+    }
+    /// 获取运行异步接口中阻塞操作的线程池，参见 [`unblock`](crate::unblock).
+    ///
+    /// 与 [`WorkerPool::global`] 分开，因此其中的任务仍可以通过后者并发地发送请求。
+    pub fn blocking() -> &'static Arc<Self> {
+        BLOCKING.get_or_init(|| Self::new(DEFAULT_MAX_WORKERS))
+    }
+    pub fn get_max_workers(&self) -> usize {
+        self.max_workers.load(Ordering::SeqCst)
+    }
+    /// 设置最大并发数，至少为 1. 减少时多余的线程会在完成当前任务后退出。
+    pub fn set_max_workers(&self, max_workers: usize) {
+        self.max_workers.store(max_workers.max(1), Ordering::SeqCst);
+        self.cond.notify_all();
+    }
+    fn lock(&self) -> std::sync::MutexGuard<'_, PoolState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+    fn is_worker(self: &Arc<Self>) -> bool {
+        WORKER_OF.with(Cell::get) == Arc::as_ptr(self) as usize
+    }
+    /// 将任务加入队列，有空闲的名额时创建新的工作线程。
+    pub(crate) fn execute(self: &Arc<Self>, job: Job) {
+        let mut state = self.lock();
+        state.queue.push_back(job);
+        if state.idle < state.queue.len() && state.workers < self.get_max_workers() {
+            state.workers += 1;
+            let pool = Arc::clone(self);
+            std::thread::spawn(move || {
+                WORKER_OF.with(|worker_of| worker_of.set(Arc::as_ptr(&pool) as usize));
+                pool.work()
+            });
+        } else {
+            self.cond.notify_one();
+        }
+    }
+    fn work(&self) {
+        let mut state = self.lock();
+        loop {
+            if state.workers > self.get_max_workers() {
+                break;
+            }
+            if let Some(job) = state.queue.pop_front() {
+                drop(state);
+                job();
+                state = self.lock();
+                continue;
+            }
+            state.idle += 1;
+            let (state_, timeout) = self
+                .cond
+                .wait_timeout(state, IDLE_TIMEOUT)
+                .unwrap_or_else(|e| e.into_inner());
+            state = state_;
+            state.idle -= 1;
+            if timeout.timed_out() && state.queue.is_empty() {
+                break;
+            }
+        }
+        state.workers -= 1;
+        debug!("工作线程退出，剩余 {} 个。", state.workers);
+    }
+    /// 并发地对每一项调用 `f`, 按原顺序返回结果。`f` 发生的 panic 会在当前线程中重新抛出。
+    pub fn map<T, R, F>(self: &Arc<Self>, items: impl IntoIterator<Item = T>, f: F) -> Vec<R>
+    where
+        T: Send + 'static,
+        R: Send + 'static,
+        F: Fn(T) -> R + Send + Sync + 'static,
+    {
+        if self.is_worker() {
+            return items.into_iter().map(f).collect();
+        }
+        let f = Arc::new(f);
+        let (tx, rx) = mpsc::channel();
+        let mut len = 0;
+        for (index, item) in items.into_iter().enumerate() {
+            let (f, tx) = (Arc::clone(&f), tx.clone());
+            self.execute(Box::new(move || {
+                let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| f(item)));
+                let _ = tx.send((index, result));
+            }));
+            len += 1;
+        }
+        drop(tx);
+        let mut results = (0..len).map(|_| None).collect::<Vec<_>>();
+        for (index, result) in rx {
+            match result {
+                Ok(result) => results[index] = Some(result),
+                Err(panic) => std::panic::resume_unwind(panic),
+            }
+        }
+        results.into_iter().map(Option::unwrap).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::WorkerPool;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn bounded_and_ordered() {
+        let pool = WorkerPool::new(3);
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+        let (running_, max_running_) = (Arc::clone(&running), Arc::clone(&max_running));
+        let results = pool.map(0..20, move |i| {
+            let n = running_.fetch_add(1, Ordering::SeqCst) + 1;
+            max_running_.fetch_max(n, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(10));
+            running_.fetch_sub(1, Ordering::SeqCst);
+            i * 2
+        });
+        assert_eq!(results, (0..20).map(|i| i * 2).collect::<Vec<_>>());
+        assert!(max_running.load(Ordering::SeqCst) <= 3);
+        // 在工作线程中嵌套调用时不会死锁。
+        let pool_ = Arc::clone(&pool);
+        let nested = pool.map(0..3, move |i| pool_.map(0..i, |j| j).len());
+        assert_eq!(nested, vec![0, 1, 2]);
+    }
+}
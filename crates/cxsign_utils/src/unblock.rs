@@ -0,0 +1,112 @@
+use crate::WorkerPool;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread::Result as ThreadResult;
+
+struct State<T> {
+    result: Option<ThreadResult<T>>,
+    waker: Option<Waker>,
+}
+
+/// [`unblock`] 返回的 [`Future`].
+pub struct Unblock<T> {
+    state: Arc<Mutex<State<T>>>,
+}
+
+/// 在 [`WorkerPool::blocking`] 中运行阻塞的函数 `f`, 返回等待其结果的 [`Future`].
+///
+/// 函数在调用本函数时即加入线程池的队列，无需等待 [`Future`] 被轮询，因此可以先创建多个再依次等待以并发执行。
+/// 同时运行的函数不超过该线程池的最大并发数（默认为 [`DEFAULT_MAX_WORKERS`](crate::DEFAULT_MAX_WORKERS)），
+/// 其余的排队等待。不依赖特定的异步运行时。`f` 发生的 panic 会在轮询时重新抛出。
+pub fn unblock<T, F>(f: F) -> Unblock<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let state = Arc::new(Mutex::new(State {
+        result: None,
+        waker: None,
+    }));
+    let state_ = Arc::clone(&state);
+    WorkerPool::blocking().execute(Box::new(move || {
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
+        let waker = {
+            let mut state = state_.lock().unwrap_or_else(|e| e.into_inner());
+            state.result = Some(result);
+            state.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake()
+        }
+    }));
+    Unblock { state }
+}
+
+impl<T> Future for Unblock<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        match state.result.take() {
+            Some(Ok(result)) => Poll::Ready(result),
+            Some(Err(panic)) => std::panic::resume_unwind(panic),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{unblock, WorkerPool, DEFAULT_MAX_WORKERS};
+    use std::future::Future;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake};
+    use std::time::Duration;
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        struct Unparker(std::thread::Thread);
+        impl Wake for Unparker {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark()
+            }
+        }
+        let waker = Arc::new(Unparker(std::thread::current())).into();
+        let mut cx = Context::from_waker(&waker);
+        let mut future = std::pin::pin!(future);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => std::thread::park(),
+            }
+        }
+    }
+
+    #[test]
+    fn bounded_by_blocking_pool() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+        let tasks = (0..DEFAULT_MAX_WORKERS * 3)
+            .map(|i| {
+                let (running, max_running) = (Arc::clone(&running), Arc::clone(&max_running));
+                unblock(move || {
+                    let n = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_running.fetch_max(n, Ordering::SeqCst);
+                    std::thread::sleep(Duration::from_millis(10));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    // 其中仍可以通过全局线程池并发地执行。
+                    WorkerPool::global().map(0..2, move |j| i + j).len()
+                })
+            })
+            .collect::<Vec<_>>();
+        for task in tasks {
+            assert_eq!(block_on(task), 2);
+        }
+        assert!(max_running.load(Ordering::SeqCst) <= DEFAULT_MAX_WORKERS);
+    }
+}
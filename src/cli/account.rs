@@ -0,0 +1,244 @@
+use crate::cli::arg::AccCmds;
+use cxsign::store::tables::{AccountTable, SessionConfigTable, SessionTable};
+use cxsign::store::DataBase;
+use cxsign::utils::{
+    build_agent, inquire_confirm, inquire_pwd, inquire_select, search_institutions, time_string,
+    Institution,
+};
+use cxsign::{
+    from_netscape, inspect_cookies, session_expires_at, to_netscape, Error, SessionConfig,
+};
+use log::{info, warn};
+use std::time::{Duration, UNIX_EPOCH};
+
+pub fn run(db: &DataBase, command: AccCmds) -> Result<(), Error> {
+    let table = db.add_table::<AccountTable>()?;
+    match command {
+        AccCmds::Add {
+            uname,
+            passwd,
+            fid,
+            school,
+        } => {
+            let fid = match school {
+                Some(keyword) => Some(select_school(&keyword)?.fid),
+                None => fid,
+            };
+            let pwd = inquire_pwd(passwd);
+            match table.login(uname.clone(), pwd, fid) {
+                Ok(session) => info!(
+                    "账号[{uname}]（用户名：{}）添加成功！",
+                    session.get_stu_name()
+                ),
+                Err(e) => return Err(Error::CommandFailed(format!("账号[{uname}]添加失败：{e}"))),
+            }
+        }
+        AccCmds::List => {
+            let mut accounts = table.get_accounts()?.into_iter().collect::<Vec<_>>();
+            accounts.sort();
+            for (pair, name) in accounts {
+                match table.get_fid(&pair.uname)? {
+                    Some(fid) => println!("{}, {name}, fid: {fid}", pair.uname),
+                    None => println!("{}, {name}", pair.uname),
+                }
+            }
+        }
+        AccCmds::Schools { keyword } => {
+            let schools = search_schools(&keyword)?;
+            if schools.is_empty() {
+                warn!("没有找到名称包含[{keyword}]的机构。");
+            }
+            for school in schools {
+                println!("{}, {}", school.fid, school.name);
+            }
+        }
+        AccCmds::Remove { uname, yes } => {
+            if !table.has_account(&uname)? {
+                return Err(no_account(&uname));
+            }
+            if yes || inquire_confirm("删除账号", "删除后需要重新登录才能使用该账号。")
+            {
+                table.delete_account(&uname)?;
+                info!("已删除账号[{uname}]。");
+            }
+        }
+        AccCmds::Cookies { uname, export } => {
+            let cookie_store = match db
+                .add_table::<SessionTable>()?
+                .get_session_record(&uname)
+                .and_then(|record| record.map(|record| record.cookie_store()).transpose())
+            {
+                Ok(Some(cookie_store)) => cookie_store,
+                Ok(None) => {
+                    return Err(Error::CommandFailed(format!("账号[{uname}]没有保存的会话")))
+                }
+                Err(e) => {
+                    return Err(Error::CommandFailed(format!(
+                        "账号[{uname}]的会话读取失败：{e}"
+                    )))
+                }
+            };
+            if let Some(path) = export {
+                std::fs::write(&path, to_netscape(&cookie_store))
+                    .map_err(|e| Error::CommandFailed(format!("Cookies 导出失败：{e}")))?;
+                info!("已将账号[{uname}]的 Cookies 导出至 {}。", path.display());
+                return Ok(());
+            }
+            for cookie in inspect_cookies(&cookie_store) {
+                println!(
+                    "{}{}{}, {}={}, 过期时间：{}",
+                    if cookie.include_subdomains { "." } else { "" },
+                    cookie.domain,
+                    cookie.path,
+                    cookie.name,
+                    cookie.value,
+                    expires_string(cookie.expires_at)
+                );
+            }
+            println!(
+                "会话预计过期时间：{}",
+                expires_string(session_expires_at(&cookie_store))
+            );
+        }
+        AccCmds::Import { file, uname } => {
+            let cookie_store = std::fs::read_to_string(&file)
+                .map_err(Into::into)
+                .and_then(|text| from_netscape(&text))
+                .map_err(|e| Error::CommandFailed(format!("Cookies 文件读取失败：{e}")))?;
+            let session = table
+                .import_cookies(uname.as_deref(), cookie_store)
+                .map_err(|e| Error::CommandFailed(format!("Cookies 导入失败：{e}")))?;
+            info!(
+                "账号[{}]（用户名：{}）导入成功！会话预计过期时间：{}",
+                session.get_uname(),
+                session.get_stu_name(),
+                expires_string(session.get_expires_at())
+            );
+        }
+        AccCmds::Relogin { uname } => {
+            let accounts = if let Some(uname) = uname {
+                vec![table
+                    .get_account(&uname)?
+                    .ok_or_else(|| no_account(&uname))?]
+            } else {
+                table.get_accounts()?.into_iter().collect()
+            };
+            if accounts.is_empty() {
+                warn!("没有需要重新登录的账号。");
+            }
+            let mut failures = 0;
+            for (pair, _) in accounts {
+                match table.relogin(pair.uname.clone(), &pair.enc_pwd) {
+                    Ok(session) => info!(
+                        "账号[{}]（用户名：{}）重新登录成功！",
+                        pair.uname,
+                        session.get_stu_name()
+                    ),
+                    Err(e) => {
+                        failures += 1;
+                        warn!("账号[{}]重新登录失败！错误信息：{e}.", pair.uname)
+                    }
+                }
+            }
+            if failures > 0 {
+                return Err(Error::CommandFailed(format!(
+                    "{failures} 个账号重新登录失败"
+                )));
+            }
+        }
+        AccCmds::Config {
+            uname,
+            connect_timeout,
+            read_timeout,
+            proxy,
+            no_proxy,
+            root_cert,
+            no_root_certs,
+            reset,
+        } => {
+            if !table.has_account(&uname)? {
+                return Err(no_account(&uname));
+            }
+            let config_table = db.add_table::<SessionConfigTable>()?;
+            if reset {
+                config_table.delete_config(&uname)?;
+                info!("账号[{uname}]已恢复默认配置。");
+                return Ok(());
+            }
+            let mut config = config_table.get_config(&uname)?;
+            let old_config = config.clone();
+            let timeout = |secs| (secs > 0).then(|| Duration::from_secs(secs));
+            if let Some(secs) = connect_timeout {
+                config.connect_timeout = timeout(secs);
+            }
+            if let Some(secs) = read_timeout {
+                config.read_timeout = timeout(secs);
+            }
+            if no_proxy {
+                config.proxy = None;
+            } else if proxy.is_some() {
+                config.proxy = proxy;
+            }
+            if no_root_certs {
+                config.root_certs.clear();
+            }
+            config.root_certs.extend(root_cert);
+            if config != old_config {
+                config_table.set_config(&uname, &config).map_err(|e| {
+                    Error::CommandFailed(format!("账号[{uname}]的配置保存失败：{e}"))
+                })?;
+                info!("账号[{uname}]的配置已保存，将在下次加载时生效。");
+            }
+            print_config(&config);
+        }
+    }
+    Ok(())
+}
+
+fn print_config(config: &SessionConfig) {
+    let timeout = |t: Option<Duration>| match t {
+        Some(t) => format!("{}s", t.as_secs_f64()),
+        None => "不限制".to_owned(),
+    };
+    println!("连接超时：{}", timeout(config.connect_timeout));
+    println!("读取超时：{}", timeout(config.read_timeout));
+    println!("代理：{}", config.proxy.as_deref().unwrap_or("无"));
+    if config.root_certs.is_empty() {
+        println!("根证书：无");
+    } else {
+        println!("根证书：");
+        for path in &config.root_certs {
+            println!("    {}", path.display());
+        }
+    }
+}
+
+fn expires_string(expires_at: Option<i64>) -> String {
+    match expires_at {
+        Some(secs) => time_string(UNIX_EPOCH + Duration::from_secs(secs.max(0) as u64)),
+        None => "会话结束时".to_owned(),
+    }
+}
+
+fn no_account(uname: &str) -> Error {
+    Error::CommandFailed(format!("没有该账号：[`{uname}`]，请检查输入"))
+}
+
+fn search_schools(keyword: &str) -> Result<Vec<Institution>, Error> {
+    build_agent(Default::default())
+        .and_then(|client| search_institutions(&client, keyword))
+        .map_err(|e| Error::CommandFailed(format!("搜索机构失败：{e}")))
+}
+
+/// 搜索机构，有多个结果时询问用户选择。
+fn select_school(keyword: &str) -> Result<Institution, Error> {
+    let mut schools = search_schools(keyword)?;
+    match schools.len() {
+        0 => Err(Error::CommandFailed(format!(
+            "没有找到名称包含[{keyword}]的机构"
+        ))),
+        1 => Ok(schools.remove(0)),
+        _ => inquire_select("选择机构：", schools)
+            .ok_or_else(|| Error::Cancelled("没有选择机构".to_owned())),
+    }
+}
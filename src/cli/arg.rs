@@ -0,0 +1,278 @@
+use clap::{Args as ClapArgs, Parser, Subcommand};
+use cxsign::store::DataFormat;
+use std::path::PathBuf;
+
+#[derive(Parser, Debug)]
+#[command(author, version, about = "进行签到。", long_about = None)]
+pub struct Args {
+    /// 将脱敏后的请求和响应录制到该目录中，用于制作测试数据。
+    #[arg(long, global = true)]
+    pub record: Option<PathBuf>,
+    /// 获取课程和活动时的最大并发请求数。
+    #[arg(long, global = true)]
+    pub concurrency: Option<usize>,
+    /// 数据库已加密时使用的密钥文件。不提供时读取环境变量 `CXSIGN_PASSPHRASE` 或在终端中询问口令。
+    #[arg(long, global = true)]
+    pub key_file: Option<PathBuf>,
+    #[command(subcommand)]
+    pub command: MainCmds,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum MainCmds {
+    /// 账号相关操作（列出、添加、删除、重新登录）。
+    Account {
+        #[command(subcommand)]
+        command: AccCmds,
+    },
+    /// 列出所有账号的课程。
+    Course {
+        /// 指定账号，格式为以半角逗号隔开的字符串。
+        #[arg(short, long)]
+        accounts: Option<String>,
+    },
+    /// 位置相关操作（列出、添加、修改、删除、别名、导入、导出）。
+    Location {
+        #[command(subcommand)]
+        command: LocationCmds,
+    },
+    /// 列出签到活动。
+    List {
+        /// 指定账号，格式为以半角逗号隔开的字符串。
+        #[arg(short, long)]
+        accounts: Option<String>,
+        /// 只列出该课程的签到。
+        #[arg(short, long)]
+        course: Option<i64>,
+        /// 列出所有签到以及其他活动（包括无效签到）。
+        #[arg(long)]
+        all: bool,
+    },
+    /// 进行签到。
+    Sign(SignArgs),
+    /// 加密数据库中的账号密码、Cookies 和会话配置，或更换密钥、取消加密。
+    Rekey {
+        /// 新的密钥文件。不提供时将在终端中询问新的口令。
+        #[arg(long, conflicts_with = "plain")]
+        new_key_file: Option<PathBuf>,
+        /// 取消加密。
+        #[arg(long)]
+        plain: bool,
+    },
+    /// 将账号、会话（Cookies）、位置等所有数据备份到单个压缩文件中。
+    Backup {
+        /// 备份文件。
+        path: PathBuf,
+    },
+    /// 从备份文件恢复数据。默认与现有数据合并，备份中的数据优先。
+    Restore {
+        /// 备份文件。
+        path: PathBuf,
+        /// 清除现有数据，完全替换为备份中的数据。
+        #[arg(long)]
+        replace: bool,
+        /// 只列出将要进行的修改，不修改数据库。
+        #[arg(long)]
+        dry_run: bool,
+        /// 无需确认直接恢复。
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AccCmds {
+    /// 添加账号。
+    Add {
+        /// 账号（手机号，或指定机构时为学号）。
+        uname: String,
+        /// 密码。不提供时将在终端中询问。
+        #[arg(short, long)]
+        passwd: Option<String>,
+        /// 机构的 fid, 指定时使用机构账号（学号）登录。
+        #[arg(long, conflicts_with = "school")]
+        fid: Option<i64>,
+        /// 按名称搜索机构并选择，之后使用机构账号（学号）登录。
+        #[arg(long)]
+        school: Option<String>,
+    },
+    /// 按名称搜索机构，列出其 fid.
+    Schools {
+        /// 机构名称。
+        keyword: String,
+    },
+    /// 列出所有账号。
+    List,
+    /// 删除账号。
+    Remove {
+        /// 账号（手机号）。
+        uname: String,
+        /// 无需确认直接删除。
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// 查看账号的 Cookies 及会话的过期时间，或将其导出为 Netscape `cookies.txt` 格式。
+    Cookies {
+        /// 账号（手机号）。
+        uname: String,
+        /// 导出到该文件。
+        #[arg(short, long)]
+        export: Option<PathBuf>,
+    },
+    /// 从浏览器导出的 Netscape `cookies.txt` 文件导入已登录的会话并添加账号，不保存密码。
+    Import {
+        /// Cookies 文件。
+        file: PathBuf,
+        /// 账号。不提供时使用 Cookies 中的 uid.
+        #[arg(short, long)]
+        uname: Option<String>,
+    },
+    /// 使用已保存的密码重新登录。
+    Relogin {
+        /// 账号（手机号）。不提供时重新登录所有账号。
+        uname: Option<String>,
+    },
+    /// 查看或修改账号的网络配置（超时、代理、根证书）。不提供任何选项时显示当前配置。
+    Config {
+        /// 账号（手机号）。
+        uname: String,
+        /// 连接超时秒数，为 `0` 时不限制。
+        #[arg(long)]
+        connect_timeout: Option<u64>,
+        /// 读取超时秒数，为 `0` 时不限制。
+        #[arg(long)]
+        read_timeout: Option<u64>,
+        /// 代理地址，如 `http://127.0.0.1:8080` 或 `socks5://127.0.0.1:1080`.
+        #[arg(long, conflicts_with = "no_proxy")]
+        proxy: Option<String>,
+        /// 不使用代理。
+        #[arg(long)]
+        no_proxy: bool,
+        /// 额外信任的根证书（PEM 格式）文件，可以多次指定。
+        #[arg(long)]
+        root_cert: Vec<PathBuf>,
+        /// 清除已添加的根证书。
+        #[arg(long)]
+        no_root_certs: bool,
+        /// 恢复默认配置。
+        #[arg(long, conflicts_with_all = ["connect_timeout", "read_timeout", "proxy", "no_proxy", "root_cert", "no_root_certs"])]
+        reset: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum LocationCmds {
+    /// 列出位置。
+    List {
+        /// 只列出该课程的位置。全局位置的课程号为 `-1`.
+        #[arg(short, long)]
+        course: Option<i64>,
+    },
+    /// 添加位置。
+    Add {
+        /// 位置，格式为 `地址,经度,纬度,海拔`.
+        location: String,
+        /// 绑定的课程号。不提供时为全局位置（课程号 `-1`）。
+        #[arg(short, long)]
+        course: Option<i64>,
+        /// 位置别名，可以多次指定。
+        #[arg(short, long)]
+        alias: Vec<String>,
+    },
+    /// 修改位置。
+    Set {
+        /// 位置 ID.
+        location_id: i64,
+        /// 位置，格式为 `地址,经度,纬度,海拔`.
+        location: String,
+        /// 绑定的课程号。不提供时保持不变。
+        #[arg(short, long)]
+        course: Option<i64>,
+    },
+    /// 删除位置，同时删除其所有别名。
+    Remove {
+        /// 位置 ID.
+        location_id: i64,
+        /// 无需确认直接删除。
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// 位置别名相关操作。
+    Alias {
+        #[command(subcommand)]
+        command: AliasCmds,
+    },
+    /// 从文件导入位置。
+    ///
+    /// 支持 JSON, TOML 和 CSV 格式，数据有误时报告出错的位置和字段，且不导入任何位置。
+    /// 也支持旧格式，每行为 `课程号$地址,经度,纬度,海拔$别名/...`, 有误的行会被跳过。
+    Import {
+        /// 文件路径。
+        path: PathBuf,
+        /// 文件格式，可选 `legacy`, `json`, `toml` 或 `csv`. 不提供时根据扩展名或内容判断。
+        #[arg(short, long)]
+        format: Option<DataFormat>,
+    },
+    /// 导出位置。
+    Export {
+        /// 文件路径。不提供时输出到标准输出。
+        path: Option<PathBuf>,
+        /// 文件格式，可选 `legacy`, `json`, `toml` 或 `csv`. 不提供时根据扩展名判断，否则为旧格式。
+        #[arg(short, long)]
+        format: Option<DataFormat>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AliasCmds {
+    /// 列出位置的所有别名。
+    List {
+        /// 位置 ID.
+        location_id: i64,
+    },
+    /// 为位置添加别名。若别名已存在，则将其指向该位置。
+    Add {
+        /// 位置 ID.
+        location_id: i64,
+        /// 别名，可以指定多个。
+        #[arg(required = true)]
+        aliases: Vec<String>,
+    },
+    /// 删除别名。
+    Remove {
+        /// 别名，可以指定多个。
+        #[arg(required = true)]
+        aliases: Vec<String>,
+    },
+}
+
+#[derive(ClapArgs, Debug)]
+pub struct SignArgs {
+    /// 签到 ID. 不提供时对所有有效签到进行签到。
+    pub active_id: Option<i64>,
+    /// 指定账号，格式为以半角逗号隔开的字符串。
+    #[arg(short, long)]
+    pub accounts: Option<String>,
+    /// 位置 ID、位置别名或 `地址,经度,纬度,海拔` 格式的位置。
+    ///
+    /// 位置签到和二维码签到时使用。不提供时依次尝试预设位置、课程位置和全局位置。
+    #[arg(short, long)]
+    pub location: Option<String>,
+    /// 图片或图片所在文件夹。
+    ///
+    /// 拍照签到时为要上传的照片；二维码签到时为二维码图片。
+    #[arg(short, long)]
+    pub pic: Option<PathBuf>,
+    /// 二维码签到时的 `enc` 参数。
+    #[arg(short, long)]
+    pub enc: Option<String>,
+    /// 签到码或手势。
+    ///
+    /// 手势按九宫格从左到右、从上到下依次编号为 `1` 到 `9`.
+    #[arg(short, long)]
+    pub signcode: Option<String>,
+    /// 截屏时精确截取二维码。适用于二维码会刷新的情况。
+    #[cfg(any(target_os = "linux", target_os = "windows", target_os = "macos"))]
+    #[arg(long)]
+    pub precisely: bool,
+}
@@ -0,0 +1,95 @@
+use cxsign::store::{Backup, DataBase, RestoreDiff, RestoreMode};
+use cxsign::utils::{inquire_confirm, time_string};
+use cxsign::Error;
+use log::info;
+use std::path::PathBuf;
+use std::time::{Duration, UNIX_EPOCH};
+
+pub fn backup(db: &DataBase, path: PathBuf) -> Result<(), Error> {
+    let backup = db.backup()?;
+    std::fs::write(&path, backup.to_bytes()?)?;
+    let manifest = backup.manifest();
+    info!(
+        "已备份 {} 个账号，共 {} 行数据到文件 `{}`.",
+        manifest.accounts.len(),
+        manifest.tables.values().sum::<usize>(),
+        path.display()
+    );
+    Ok(())
+}
+
+fn print_diff(diff: &RestoreDiff) {
+    for (table, table_diff) in diff.tables.iter().filter(|(_, d)| !d.is_empty()) {
+        println!(
+            "{table}: 新增 {}, 修改 {}, 删除 {}, 不变 {}.",
+            table_diff.added.len(),
+            table_diff.updated.len(),
+            table_diff.removed.len(),
+            table_diff.unchanged
+        );
+        for (mark, rows) in [
+            ('+', &table_diff.added),
+            ('~', &table_diff.updated),
+            ('-', &table_diff.removed),
+        ] {
+            for row in rows {
+                println!("  {mark} {row}");
+            }
+        }
+    }
+}
+
+pub fn restore(
+    db: &DataBase,
+    path: PathBuf,
+    replace: bool,
+    dry_run: bool,
+    yes: bool,
+) -> Result<(), Error> {
+    let backup = std::fs::read(&path)
+        .map_err(Error::from)
+        .and_then(|data| Backup::from_bytes(&data))
+        .map_err(|e| Error::CommandFailed(format!("备份文件 `{}` 无效：{e}", path.display())))?;
+    let manifest = backup.manifest();
+    let accounts = if manifest.accounts.is_empty() {
+        "无".to_owned()
+    } else {
+        manifest.accounts.join(", ")
+    };
+    println!(
+        "备份时间：{}, 程序版本：{}, 账号：{accounts}{}.",
+        time_string(UNIX_EPOCH + Duration::from_secs(manifest.created_at.max(0) as u64)),
+        manifest.program_version,
+        if manifest.encrypted {
+            "（已加密）"
+        } else {
+            ""
+        }
+    );
+    let mode = if replace {
+        RestoreMode::Replace
+    } else {
+        RestoreMode::Merge
+    };
+    let diff = db
+        .diff_backup(&backup, mode)
+        .map_err(|e| Error::CommandFailed(format!("无法恢复该备份：{e}")))?;
+    if diff.is_empty() {
+        info!("数据与备份一致，无需恢复。");
+        return Ok(());
+    }
+    print_diff(&diff);
+    if dry_run {
+        return Ok(());
+    }
+    let tips = if replace {
+        "现有数据将被清除并替换为备份中的数据。"
+    } else {
+        "备份中的数据将覆盖现有的同名数据。"
+    };
+    if yes || inquire_confirm("恢复备份", tips) {
+        db.restore(&backup, mode)?;
+        info!("已从文件 `{}` 恢复备份。", path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use cxsign::store::tables::AccountTable;
+use cxsign::store::DataBase;
+use cxsign::{Course, Error};
+use log::warn;
+
+pub fn run(db: &DataBase, accounts: Option<String>) -> Result<(), Error> {
+    let table = db.add_table::<AccountTable>()?;
+    let sessions = crate::cli::get_sessions(&table, &accounts)?;
+    let report = Course::fetch_courses(sessions.values());
+    let mut courses = report.courses.into_iter().collect::<Vec<_>>();
+    courses.sort_by(|a, b| a.0.cmp(&b.0));
+    for (course, sessions) in courses {
+        let names = sessions
+            .iter()
+            .map(|s| s.get_stu_name())
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!("{course}, 用户: [{names}]");
+    }
+    for (session, e) in &report.failures {
+        warn!(
+            "获取用户[{}]的课程列表失败！错误信息：{e}.",
+            session.get_stu_name()
+        );
+    }
+    if !report.failures.is_empty() {
+        return Err(Error::CommandFailed(format!(
+            "{} 个用户的课程列表获取失败",
+            report.failures.len()
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use cxsign::store::crypto::KeySource;
+use cxsign::store::DataBase;
+use cxsign::utils::inquire_passphrase;
+use cxsign::Error;
+use log::info;
+use std::path::PathBuf;
+
+/// 解锁已加密的数据库，没有提供口令或密钥文件时返回错误。
+pub fn unlock(db: &DataBase, key_file: Option<PathBuf>) -> Result<(), Error> {
+    let source = if let Some(key_file) = key_file {
+        KeySource::KeyFile(key_file)
+    } else if let Ok(passphrase) = std::env::var("CXSIGN_PASSPHRASE") {
+        KeySource::Passphrase(passphrase)
+    } else if let Some(passphrase) = inquire_passphrase("数据库口令：", false) {
+        KeySource::Passphrase(passphrase)
+    } else {
+        return Err(Error::Cancelled("没有提供口令或密钥文件".to_owned()));
+    };
+    db.unlock(&source)
+}
+
+/// 更换或取消数据库的加密，失败时数据不会被修改。
+pub fn rekey(db: &DataBase, new_key_file: Option<PathBuf>, plain: bool) -> Result<(), Error> {
+    let new = if plain {
+        None
+    } else if let Some(new_key_file) = new_key_file {
+        Some(KeySource::KeyFile(new_key_file))
+    } else if let Some(passphrase) = inquire_passphrase("新的口令：", true) {
+        Some(KeySource::Passphrase(passphrase))
+    } else {
+        return Ok(());
+    };
+    db.rekey(new.as_ref())?;
+    if plain {
+        info!("已取消加密。");
+    } else {
+        info!("已加密数据库中的账号密码、Cookies 和会话配置。");
+    }
+    Ok(())
+}
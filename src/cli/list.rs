@@ -0,0 +1,48 @@
+use cxsign::store::tables::{AccountTable, ExcludeTable};
+use cxsign::store::DataBase;
+use cxsign::{Activity, Error, RawSign};
+
+fn print_signs<'a>(signs: impl Iterator<Item = &'a RawSign>, course: Option<i64>) {
+    let mut signs = signs
+        .filter(|sign| course.map_or(true, |course| sign.course.get_id() == course))
+        .collect::<Vec<_>>();
+    signs.sort_by(|a, b| b.start_time_mills.cmp(&a.start_time_mills));
+    for sign in signs {
+        println!("{sign}");
+    }
+}
+
+pub fn run(
+    db: &DataBase,
+    accounts: Option<String>,
+    course: Option<i64>,
+    all: bool,
+) -> Result<(), Error> {
+    let table = db.add_table::<AccountTable>()?;
+    let sessions = crate::cli::get_sessions(&table, &accounts)?;
+    let excludes = db.add_table::<ExcludeTable>()?;
+    let (valid_signs, other_signs, other_activities) =
+        Activity::get_all_activities(excludes, sessions.values(), all)
+            .map_err(|e| Error::CommandFailed(format!("获取签到活动失败：{e}")))?;
+    print_signs(valid_signs.keys(), course);
+    if all {
+        print_signs(other_signs.keys(), course);
+        let mut other_activities = other_activities
+            .keys()
+            .filter(|a| course.map_or(true, |course| a.course.get_id() == course))
+            .collect::<Vec<_>>();
+        other_activities.sort_by(|a, b| b.start_time_mills.cmp(&a.start_time_mills));
+        for a in other_activities {
+            println!(
+                "id: {}, name: {}, status: {}, time: {}, course: {}/{}",
+                a.id,
+                a.name,
+                a.status,
+                cxsign::utils::time_string_from_mills(a.start_time_mills),
+                a.course.get_id(),
+                a.course.get_name()
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use crate::cli::arg::{AliasCmds, LocationCmds};
+use cxsign::store::tables::{AliasTable, LocationTable};
+use cxsign::store::{DataBase, DataBaseTableTrait, DataFormat};
+use cxsign::utils::inquire_confirm;
+use cxsign::{Error, Location};
+use log::{info, warn};
+
+fn add_aliases(table: &AliasTable, location_id: i64, aliases: &[String]) -> Result<(), Error> {
+    for alias in aliases {
+        let alias = alias.trim();
+        if alias.is_empty() {
+            continue;
+        }
+        table.add_alias_or(alias, location_id, AliasTable::update_alias)?;
+        info!("已为位置[{location_id}]添加别名[{alias}]。");
+    }
+    Ok(())
+}
+
+fn no_location(location_id: i64) -> Error {
+    Error::CommandFailed(format!("没有该位置：[{location_id}]，请检查输入"))
+}
+
+pub fn run(db: &DataBase, command: LocationCmds) -> Result<(), Error> {
+    let table = db.add_table::<LocationTable>()?;
+    let alias_table = db.add_table::<AliasTable>()?;
+    match command {
+        LocationCmds::List { course } => {
+            let mut locations = if let Some(course) = course {
+                table
+                    .get_location_map_by_course(course)?
+                    .into_iter()
+                    .map(|(location_id, location)| (location_id, (course, location)))
+                    .collect::<Vec<_>>()
+            } else {
+                table.get_locations()?.into_iter().collect()
+            };
+            locations.sort_by_key(|(location_id, _)| *location_id);
+            for (location_id, (course, location)) in locations {
+                let aliases = alias_table.get_aliases(location_id)?;
+                println!(
+                    "位置 ID: {location_id}, 课程号: {course}, 位置: {location}, 别名: {aliases:?}"
+                );
+            }
+        }
+        LocationCmds::Add {
+            location,
+            course,
+            alias,
+        } => {
+            let location = Location::parse(&location).map_err(Error::CommandFailed)?;
+            let location_id = table.insert_location(course.unwrap_or(-1), &location)?;
+            info!("已添加位置[{location_id}]：{location}.");
+            add_aliases(&alias_table, location_id, &alias)?;
+        }
+        LocationCmds::Set {
+            location_id,
+            location,
+            course,
+        } => {
+            let Some((old_course, _)) = table.get_location(location_id)? else {
+                return Err(no_location(location_id));
+            };
+            let location = Location::parse(&location).map_err(Error::CommandFailed)?;
+            let course = course.unwrap_or(old_course);
+            table.update_location(location_id, course, &location)?;
+            info!("已修改位置[{location_id}]：{location}.");
+        }
+        LocationCmds::Remove { location_id, yes } => {
+            if !table.has_location(location_id)? {
+                return Err(no_location(location_id));
+            }
+            if yes || inquire_confirm("删除位置", "该位置的所有别名也将被删除。")
+            {
+                table.delete_location(location_id)?;
+                info!("已删除位置[{location_id}]。");
+            }
+        }
+        LocationCmds::Alias { command } => match command {
+            AliasCmds::List { location_id } => {
+                for alias in alias_table.get_aliases(location_id)? {
+                    println!("{alias}");
+                }
+            }
+            AliasCmds::Add {
+                location_id,
+                aliases,
+            } => {
+                if !table.has_location(location_id)? {
+                    return Err(no_location(location_id));
+                }
+                add_aliases(&alias_table, location_id, &aliases)?;
+            }
+            AliasCmds::Remove { aliases } => {
+                let mut missing = 0;
+                for alias in aliases {
+                    if alias_table.has_alias(&alias)? {
+                        alias_table.delete_alias(&alias)?;
+                        info!("已删除别名[{alias}]。");
+                    } else {
+                        missing += 1;
+                        warn!("没有该别名：[{alias}]。");
+                    }
+                }
+                if missing > 0 {
+                    return Err(Error::CommandFailed(format!("{missing} 个别名不存在")));
+                }
+            }
+        },
+        LocationCmds::Import { path, format } => {
+            let contents = std::fs::read_to_string(&path)
+                .map_err(|e| Error::CommandFailed(format!("无法读取文件 {path:?}：{e}")))?;
+            let format = format
+                .or_else(|| DataFormat::from_path(&path))
+                .unwrap_or_else(|| DataFormat::detect(&contents));
+            match LocationTable::import(db, contents, format) {
+                Ok(_) => info!("已从文件 {path:?} 导入位置。"),
+                Err(e @ Error::ParseError(_)) => {
+                    return Err(Error::CommandFailed(format!("位置导入失败：{e}")))
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        LocationCmds::Export { path, format } => {
+            let format = format
+                .or_else(|| path.as_deref().and_then(DataFormat::from_path))
+                .unwrap_or_default();
+            let contents = match table.export(format) {
+                Ok(contents) => contents,
+                Err(e @ Error::ParseError(_)) => {
+                    return Err(Error::CommandFailed(format!("位置导出失败：{e}")))
+                }
+                Err(e) => return Err(e),
+            };
+            if let Some(path) = path {
+                std::fs::write(&path, contents)
+                    .map_err(|e| Error::CommandFailed(format!("无法写入文件 {path:?}：{e}")))?;
+            } else {
+                print!("{contents}");
+            }
+        }
+    }
+    Ok(())
+}
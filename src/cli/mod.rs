@@ -0,0 +1,24 @@
+pub mod account;
+pub mod arg;
+pub mod backup;
+pub mod course;
+pub mod key;
+pub mod list;
+pub mod location;
+pub mod sign;
+
+use cxsign::store::tables::AccountTable;
+use cxsign::{Error, Session};
+use std::collections::HashMap;
+
+/// 获取指定账号的会话。未指定账号时获取所有账号的会话。
+pub fn get_sessions(
+    table: &AccountTable,
+    accounts: &Option<String>,
+) -> Result<HashMap<String, Session>, Error> {
+    if let Some(accounts) = accounts {
+        table.get_sessions_by_accounts_str(accounts)
+    } else {
+        table.get_sessions()
+    }
+}
@@ -0,0 +1,125 @@
+use crate::cli::arg::SignArgs;
+use cxsign::store::tables::{AccountTable, ExcludeTable};
+use cxsign::store::DataBase;
+use cxsign::{
+    Activity, DefaultGestureOrSigncodeSignner, DefaultLocationSignner, DefaultNormalOrRawSignner,
+    DefaultPhotoSignner, DefaultQrCodeSignner, Error, RawSign, Session, Sign, SignResult,
+    SignTrait, SignnerTrait,
+};
+use log::{info, warn};
+use std::collections::HashMap;
+
+fn sign_single(
+    db: &DataBase,
+    args: &SignArgs,
+    raw: RawSign,
+    sessions: &[Session],
+) -> Result<HashMap<String, SignResult>, Error> {
+    let Some(first) = sessions.first() else {
+        return Ok(HashMap::new());
+    };
+    let sessions = sessions.iter();
+    let results = match raw.to_sign(first) {
+        Sign::Photo(mut sign) => DefaultPhotoSignner::new(&args.pic).sign(&mut sign, sessions),
+        Sign::Normal(mut sign) => DefaultNormalOrRawSignner.sign(&mut sign, sessions),
+        Sign::QrCode(mut sign) => DefaultQrCodeSignner::new(
+            db,
+            &args.location,
+            &args.pic,
+            &args.enc,
+            #[cfg(any(target_os = "linux", target_os = "windows", target_os = "macos"))]
+            args.precisely,
+        )
+        .sign(&mut sign, sessions),
+        Sign::Gesture(mut sign) => {
+            if let Some(signcode) = args.signcode.as_ref() {
+                DefaultGestureOrSigncodeSignner::new(signcode).sign(&mut sign, sessions)
+            } else {
+                warn!(
+                    "手势签到[{}]需要提供手势，请使用 `--signcode` 参数。",
+                    sign.as_inner().name
+                );
+                return Ok(HashMap::new());
+            }
+        }
+        Sign::Location(mut sign) => {
+            DefaultLocationSignner::new(db, &args.location).sign(&mut sign, sessions)
+        }
+        Sign::Signcode(mut sign) => {
+            if let Some(signcode) = args.signcode.as_ref() {
+                DefaultGestureOrSigncodeSignner::new(signcode).sign(&mut sign, sessions)
+            } else {
+                warn!(
+                    "签到码签到[{}]需要提供签到码，请使用 `--signcode` 参数。",
+                    sign.as_inner().name
+                );
+                return Ok(HashMap::new());
+            }
+        }
+        Sign::Unknown(mut sign) => {
+            warn!("签到[{}]类型未知，尝试以普通签到的方式签到。", sign.name);
+            DefaultNormalOrRawSignner.sign(&mut sign, sessions)
+        }
+    }?;
+    Ok(results
+        .into_iter()
+        .map(|(session, result)| (session.get_stu_name().to_owned(), result))
+        .collect())
+}
+
+pub fn run(db: &DataBase, args: SignArgs) -> Result<(), Error> {
+    let table = db.add_table::<AccountTable>()?;
+    let sessions = crate::cli::get_sessions(&table, &args.accounts)?;
+    if sessions.is_empty() {
+        return Err(Error::CommandFailed("没有可用于签到的账号".to_owned()));
+    }
+    let excludes = db.add_table::<ExcludeTable>()?;
+    let (valid_signs, other_signs, _) =
+        Activity::get_all_activities(excludes, sessions.values(), false)
+            .map_err(|e| Error::CommandFailed(format!("获取签到活动失败：{e}")))?;
+    let signs = if let Some(active_id) = args.active_id {
+        let active_id = active_id.to_string();
+        let sign = valid_signs
+            .into_iter()
+            .chain(other_signs)
+            .find(|(sign, _)| sign.active_id == active_id);
+        if let Some(sign) = sign {
+            vec![sign]
+        } else {
+            return Err(Error::CommandFailed(format!(
+                "没有找到签到[{active_id}]，请检查输入或账号"
+            )));
+        }
+    } else {
+        if valid_signs.is_empty() {
+            info!("没有有效的签到。");
+        }
+        valid_signs.into_iter().collect()
+    };
+    // 各用户签到失败的次数，签到出错时计为所有用户均失败。
+    let mut failures = 0;
+    for (raw, sessions) in signs {
+        let name = raw.name.clone();
+        match sign_single(db, &args, raw, &sessions) {
+            Ok(results) => {
+                for (stu_name, result) in results {
+                    match result {
+                        SignResult::Susses => info!("用户[{stu_name}]签到[{name}]成功！"),
+                        SignResult::Fail { msg } => {
+                            failures += 1;
+                            warn!("用户[{stu_name}]签到[{name}]失败！失败信息：{msg}.")
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                failures += sessions.len();
+                warn!("签到[{name}]出错！错误信息：{e}.")
+            }
+        }
+    }
+    if failures > 0 {
+        return Err(Error::CommandFailed(format!("{failures} 次签到失败")));
+    }
+    Ok(())
+}
@@ -1 +1,71 @@
-fn main() {}
+mod cli;
+
+use clap::Parser;
+use cli::arg::{Args, MainCmds};
+use cxsign::store::DataBase;
+use cxsign::transport::{endpoint, record};
+use cxsign::utils::{WorkerPool, DIR};
+use log::{error, warn};
+
+fn main() {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let Args {
+        record,
+        concurrency,
+        key_file,
+        command,
+    } = Args::parse();
+    record::set_record_dir(record);
+    if let Some(concurrency) = concurrency {
+        WorkerPool::global().set_max_workers(concurrency);
+    }
+    if let Err(e) = endpoint::load_from_file(DIR.get_config_dir().join("endpoints.json")) {
+        warn!("接口配置加载失败，将使用默认地址！错误信息：{e}.");
+    }
+    endpoint::load_from_env();
+    let db = match DataBase::new(DIR.clone()) {
+        Ok(db) => db,
+        Err(e) => {
+            error!("数据库打开失败！错误信息：{e}.");
+            std::process::exit(1);
+        }
+    };
+    // 备份和恢复不读取敏感数据，无需解锁。
+    let needs_unlock = !matches!(command, MainCmds::Backup { .. } | MainCmds::Restore { .. });
+    let is_encrypted = db.is_encrypted().unwrap_or_else(|e| {
+        error!("数据库读取失败！错误信息：{e}.");
+        std::process::exit(1);
+    });
+    if needs_unlock && is_encrypted {
+        if let Err(e) = cli::key::unlock(&db, key_file) {
+            error!("数据库解锁失败，无法读取账号密码、Cookies 和会话配置！错误信息：{e}.");
+            std::process::exit(1);
+        }
+    }
+    let result = match command {
+        MainCmds::Account { command } => cli::account::run(&db, command),
+        MainCmds::Course { accounts } => cli::course::run(&db, accounts),
+        MainCmds::Location { command } => cli::location::run(&db, command),
+        MainCmds::List {
+            accounts,
+            course,
+            all,
+        } => cli::list::run(&db, accounts, course, all),
+        MainCmds::Sign(args) => cli::sign::run(&db, args),
+        MainCmds::Rekey {
+            new_key_file,
+            plain,
+        } => cli::key::rekey(&db, new_key_file, plain),
+        MainCmds::Backup { path } => cli::backup::backup(&db, path),
+        MainCmds::Restore {
+            path,
+            replace,
+            dry_run,
+            yes,
+        } => cli::backup::restore(&db, path, replace, dry_run, yes),
+    };
+    if let Err(e) = result {
+        error!("命令执行失败！错误信息：{e}.");
+        std::process::exit(1);
+    }
+}